@@ -1,4 +1,5 @@
 use clap::{Parser, Subcommand};
+use std::path::PathBuf;
 
 #[derive(Parser, Debug, Default)]
 pub struct AgentOptions {
@@ -13,6 +14,46 @@ pub struct AgentOptions {
 pub enum AgentCommands {
     #[command(about = "help for DID")]
     Did,
+    #[command(about = "Create a did:webvh DID instead of a Sidetree DID")]
+    CreateWebvh {
+        #[arg(long, help = "Domain and path of the DID, e.g. example.com:devices")]
+        uri: String,
+    },
+    #[command(about = "Rotate the sign and encrypt keys of the DID")]
+    RotateKeys,
+    #[command(about = "Publish a service endpoint, e.g. DIDCommMessaging, on the DID document")]
+    AddService {
+        #[arg(long, help = "Id of the service, replaced if it exists")]
+        id: String,
+        #[arg(long = "type", help = "Type of the service, e.g. DIDCommMessaging")]
+        r#type: String,
+        #[arg(long)]
+        endpoint: String,
+        #[arg(long)]
+        description: Option<String>,
+    },
+    #[command(about = "Remove a service endpoint from the DID document")]
+    RemoveService {
+        #[arg(long)]
+        id: String,
+    },
+    #[command(about = "Deactivate the DID permanently, e.g. to decommission the device")]
+    Deactivate {
+        #[arg(long, help = "Confirm the deactivation")]
+        yes: bool,
+    },
+    #[command(about = "Export the DID document signed with its sign key, for offline devices")]
+    ExportDid {
+        #[arg(long, help = "File to write the bundle to, stdout by default")]
+        output: Option<PathBuf>,
+    },
+    #[command(
+        about = "Import a DID document exported by another device, signed by one of did_resolver.trusted_keys"
+    )]
+    ImportDid {
+        #[arg(long, help = "Bundle written by export-did")]
+        file: PathBuf,
+    },
     #[command(about = "help for Network")]
     Network {
         #[command(subcommand)]
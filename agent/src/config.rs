@@ -1,13 +1,17 @@
 use home_config::HomeConfig;
+use protocol::did::file_store::write_atomically;
+use protocol::did::multi_method::DidMethod;
 use protocol::keyring::keypair::{
-    K256KeyPair, KeyPair, KeyPairHex, KeyPairing, KeyPairingError, X25519KeyPair,
+    Ed25519KeyPair, K256KeyPair, KeyPair, KeyPairHex, KeyPairing, KeyPairingError, SignKeyPair,
+    SignKeyType, X25519KeyPair,
 };
+use protocol::keyring::multihash::HashAlgorithm;
 use serde::Deserialize;
 use serde::Serialize;
 use std::env;
 use std::io;
 use std::io::Write;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::{
     fs,
     sync::{Arc, Mutex, Once},
@@ -15,14 +19,40 @@ use std::{
 use std::{fs::OpenOptions, sync::MutexGuard};
 use thiserror::Error;
 
+use crate::nodex::extension::cipher::CipherExtension;
+use crate::nodex::keyring::key_encryption::{
+    resolve_key_encryption_key, KeyEncryptionConfig, KeyEncryptionError, KeyEncryptionKey,
+    StoredKeyPair,
+};
 use crate::nodex::utils::UnwrapLog;
 
+// NOTE: 2: key pairs are stored encrypted when key_encryption is not plaintext.
+const SCHEMA_VERSION: u8 = 2;
+
 #[derive(Clone, Deserialize, Serialize)]
 struct KeyPairsConfig {
-    sign: Option<KeyPairHex>,
-    update: Option<KeyPairHex>,
-    recovery: Option<KeyPairHex>,
-    encrypt: Option<KeyPairHex>,
+    sign: Option<StoredKeyPair>,
+    update: Option<StoredKeyPair>,
+    recovery: Option<StoredKeyPair>,
+    encrypt: Option<StoredKeyPair>,
+    // Ed25519 update key pair of a did:webvh DID, which signs its log entries.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    webvh_update: Option<StoredKeyPair>,
+}
+
+// A pairwise did:peer DID and its key pairs, one per relationship with another device.
+#[derive(Clone, Deserialize, Serialize)]
+struct PeerIdentityConfig {
+    did: String,
+    key_pairs: KeyPairsConfig,
+}
+
+// NOTE: Labels of the key pairs submitted to an update of our own DID, until it succeeds.
+const PENDING_LABEL: &str = "pending";
+
+// NOTE: The DID is bound to the labels of the key pairs, so they can not be swapped.
+fn peer_label(did: &str, slot: &str) -> String {
+    format!("{}#{}", did, slot)
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -31,9 +61,29 @@ pub struct Extension {
     pub symbol: String,
 }
 
+impl From<Extension> for protocol::keyring::extension::Extension {
+    fn from(value: Extension) -> Self {
+        protocol::keyring::extension::Extension {
+            filename: value.filename,
+            symbol: value.symbol,
+        }
+    }
+}
+
+// What to do when the TRNG extension can not be loaded or fails its health test.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TRNGFallbackPolicy {
+    #[default]
+    Deny,
+    OsRng,
+}
+
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct TRNGExtensionConfig {
     pub read: Extension,
+    #[serde(default)]
+    pub fallback: TRNGFallbackPolicy,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -60,14 +110,67 @@ pub struct DidCommConfig {
     pub http_body_size_limit: usize,
 }
 
+// Seconds to cache resolved DIDs for. did:webvh `ttl` overrides `ttl`.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct DidCacheConfig {
+    pub capacity: usize,
+    pub ttl: u64,
+    pub negative_ttl: u64,
+}
+
+impl Default for DidCacheConfig {
+    fn default() -> Self {
+        DidCacheConfig {
+            capacity: 1024,
+            ttl: 300,
+            negative_ttl: 30,
+        }
+    }
+}
+
+// DID methods resolved when talking to other devices.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct DidResolverConfig {
+    pub methods: Vec<DidMethod>,
+    pub webvh_require_witness: bool,
+    pub cache: DidCacheConfig,
+    // RFC 7638 thumbprints of the sign keys whose DID documents import-did accepts.
+    pub trusted_keys: Vec<String>,
+    // hashAlgorithmInMultihashCode of the Sidetree node, e.g. "sha2-256" or "sha3-256".
+    pub sidetree_hash_algorithm: HashAlgorithm,
+}
+
+impl Default for DidResolverConfig {
+    fn default() -> Self {
+        DidResolverConfig {
+            methods: DidMethod::ALL.to_vec(),
+            webvh_require_witness: false,
+            cache: DidCacheConfig::default(),
+            trusted_keys: vec![],
+            sidetree_hash_algorithm: HashAlgorithm::default(),
+        }
+    }
+}
+
 #[derive(Deserialize, Serialize)]
 #[serde(default)]
 pub struct ConfigRoot {
     did: Option<String>,
     key_pairs: KeyPairsConfig,
+    // Key pairs submitted to an update of our own DID, promoted to key_pairs once it is applied.
+    pending_key_pairs: Option<KeyPairsConfig>,
+    peer_identities: Vec<PeerIdentityConfig>,
+    key_encryption: KeyEncryptionConfig,
     extensions: ExtensionsConfig,
     metrics: MetricsConfig,
     didcomm: DidCommConfig,
+    did_resolver: DidResolverConfig,
+    // Use the long-form of a new Sidetree DID, which is resolvable before it is anchored.
+    long_form_did: bool,
+    // Key type of the sign key pair generated for a new DID, "secp256k1" or "secp256r1".
+    sign_key_type: SignKeyType,
     is_initialized: bool,
     schema_version: u8,
 }
@@ -81,7 +184,11 @@ impl Default for ConfigRoot {
                 update: None,
                 recovery: None,
                 encrypt: None,
+                webvh_update: None,
             },
+            pending_key_pairs: None,
+            peer_identities: vec![],
+            key_encryption: KeyEncryptionConfig::default(),
             extensions: ExtensionsConfig {
                 trng: None,
                 secure_keystore: None,
@@ -95,8 +202,11 @@ impl Default for ConfigRoot {
             didcomm: DidCommConfig {
                 http_body_size_limit: 3 * 1024 * 1024,
             },
+            did_resolver: DidResolverConfig::default(),
+            long_form_did: false,
+            sign_key_type: SignKeyType::default(),
             is_initialized: false,
-            schema_version: 1,
+            schema_version: SCHEMA_VERSION,
         }
     }
 }
@@ -110,6 +220,14 @@ impl SingletonAppConfig {
     pub fn lock(&self) -> MutexGuard<'_, AppConfig> {
         self.inner.lock().unwrap()
     }
+
+    // NOTE: For tests, which must not touch the config.json in $HOME.
+    #[cfg(test)]
+    pub(crate) fn with_file(path: &Path) -> Self {
+        SingletonAppConfig {
+            inner: Arc::new(Mutex::new(AppConfig::open(HomeConfig::with_file(path)))),
+        }
+    }
 }
 
 #[allow(static_mut_refs)]
@@ -133,14 +251,23 @@ pub fn app_config() -> Box<SingletonAppConfig> {
 pub struct AppConfig {
     config: HomeConfig,
     root: ConfigRoot,
+    // NOTE: An error, e.g. an unset passphrase, is kept and returned when a key pair is
+    //       encrypted or decrypted, so that commands which never touch them still work.
+    kek: Result<Option<KeyEncryptionKey>, Arc<KeyEncryptionError>>,
 }
 
 #[derive(Error, Debug)]
 pub enum AppConfigError<E: std::error::Error> {
     #[error("key decode failed")]
     DecodeFailed(E),
-    #[error("failed to write config file")]
-    WriteError(home_config::JsonError),
+    #[error("failed to write config file: {0}")]
+    WriteError(#[from] io::Error),
+    #[error("failed to encrypt or decrypt key pair: {0}")]
+    KeyEncryption(#[from] KeyEncryptionError),
+    #[error("key pair is encrypted, but key_encryption is plaintext")]
+    KeyEncryptionKeyNotFound,
+    #[error("key encryption key is unavailable: {0}")]
+    KeyEncryptionKeyUnavailable(Arc<KeyEncryptionError>),
 }
 
 fn convert_to_key<U, V, T: KeyPair<U, V>>(
@@ -149,12 +276,6 @@ fn convert_to_key<U, V, T: KeyPair<U, V>>(
     T::from_hex_key_pair(config).map_err(AppConfigError::DecodeFailed)
 }
 
-#[inline]
-fn load_key_pair<U, V, T: KeyPair<U, V>>(kind: &Option<KeyPairHex>) -> Option<T> {
-    kind.as_ref()
-        .and_then(|key| convert_to_key(key).map_err(|e| log::error!("{:?}", e)).ok())
-}
-
 impl AppConfig {
     fn touch(path: &Path) -> io::Result<()> {
         let mut file = OpenOptions::new()
@@ -168,9 +289,17 @@ impl AppConfig {
 
     const APP_NAME: &'static str = "nodex";
     const CONFIG_FILE: &'static str = "config.json";
+    const DID_LOG_FILE: &'static str = "did.jsonl";
+    const DID_STORE_DIR: &'static str = "dids";
 
     fn new() -> Self {
-        let config = HomeConfig::with_config_dir(AppConfig::APP_NAME, AppConfig::CONFIG_FILE);
+        Self::open(HomeConfig::with_config_dir(
+            AppConfig::APP_NAME,
+            AppConfig::CONFIG_FILE,
+        ))
+    }
+
+    fn open(config: HomeConfig) -> Self {
         let config_dir = config.path().parent().unwrap();
 
         if !Path::exists(config.path()) {
@@ -178,21 +307,165 @@ impl AppConfig {
             Self::touch(config.path()).unwrap_log();
         }
 
-        let root = config.json::<ConfigRoot>().unwrap_log();
+        let mut root = config.json::<ConfigRoot>().unwrap_log();
 
-        AppConfig { root, config }
+        let key_encryption = root.key_encryption.clone();
+        let kek = Self::load_key_encryption_key(&mut root, config_dir).map_err(|e| {
+            log::error!("failed to load key encryption key: {}", e);
+            // NOTE: Never persist a salt or wrapped key of a KEK which is not usable.
+            root.key_encryption = key_encryption.clone();
+            Arc::new(e)
+        });
+        let is_changed = key_encryption != root.key_encryption;
+
+        let mut app_config = AppConfig { root, config, kek };
+        // NOTE: Key pairs left in plaintext are still loadable, so the agent goes on.
+        if let Err(e) = app_config.migrate(is_changed) {
+            log::error!("failed to migrate config: {}", e);
+        }
+        app_config
+    }
+
+    fn load_key_encryption_key(
+        root: &mut ConfigRoot,
+        config_dir: &Path,
+    ) -> Result<Option<KeyEncryptionKey>, KeyEncryptionError> {
+        let cipher = match (&root.key_encryption, &root.extensions.cipher) {
+            (KeyEncryptionConfig::CipherExtension { .. }, Some(cipher)) => {
+                Some(CipherExtension::load(&cipher.encrypt, &cipher.decrypt)?)
+            }
+            _ => None,
+        };
+        resolve_key_encryption_key(&mut root.key_encryption, config_dir, cipher)
+    }
+
+    fn kek<E: std::error::Error>(&self) -> Result<Option<&KeyEncryptionKey>, AppConfigError<E>> {
+        match &self.kek {
+            Ok(kek) => Ok(kek.as_ref()),
+            Err(e) => Err(AppConfigError::KeyEncryptionKeyUnavailable(e.clone())),
+        }
+    }
+
+    // Encrypts key pairs still stored in plaintext (e.g. written before schema_version 2).
+    fn migrate(&mut self, mut is_changed: bool) -> Result<(), AppConfigError<KeyPairingError>> {
+        if let Ok(Some(kek)) = self.kek.as_ref() {
+            let peers = self
+                .root
+                .peer_identities
+                .iter_mut()
+                .map(|v| (Some(v.did.as_str()), &mut v.key_pairs));
+            let pending = self
+                .root
+                .pending_key_pairs
+                .as_mut()
+                .map(|v| (Some(PENDING_LABEL), v));
+            for (did, key_pairs) in std::iter::once((None, &mut self.root.key_pairs))
+                .chain(pending)
+                .chain(peers)
+            {
+                let slots = [
+                    ("sign", &mut key_pairs.sign),
+                    ("update", &mut key_pairs.update),
+                    ("recovery", &mut key_pairs.recovery),
+                    ("encrypt", &mut key_pairs.encrypt),
+                    ("webvh_update", &mut key_pairs.webvh_update),
+                ];
+                for (slot, stored) in slots {
+                    if let Some(StoredKeyPair::Plaintext(hex)) = stored {
+                        let label = match did {
+                            Some(did) => peer_label(did, slot),
+                            None => slot.to_string(),
+                        };
+                        let encrypted = kek.encrypt(&label, hex)?;
+                        *stored = Some(StoredKeyPair::Encrypted(encrypted));
+                        is_changed = true;
+                    }
+                }
+            }
+        }
+        if self.root.schema_version < SCHEMA_VERSION {
+            log::info!(
+                "migrate config schema_version {} to {}",
+                self.root.schema_version,
+                SCHEMA_VERSION
+            );
+            self.root.schema_version = SCHEMA_VERSION;
+            is_changed = true;
+        }
+        if is_changed {
+            self.write()?;
+        }
+        Ok(())
+    }
+
+    // NOTE: None only when the key pair is absent. A key pair which fails to decrypt,
+    //       e.g. with a wrong passphrase, is an error so that it is never replaced.
+    fn load_key_pair<U, V, T: KeyPair<U, V>>(
+        &self,
+        label: &str,
+        stored: &Option<StoredKeyPair>,
+    ) -> Result<Option<T>, AppConfigError<T::Error>> {
+        let hex = match stored {
+            None => return Ok(None),
+            Some(StoredKeyPair::Plaintext(hex)) => hex.clone(),
+            Some(StoredKeyPair::Encrypted(encrypted)) => {
+                let kek = self
+                    .kek()?
+                    .ok_or(AppConfigError::KeyEncryptionKeyNotFound)?;
+                kek.decrypt(label, encrypted)?
+            }
+        };
+        convert_to_key(&hex).map(Some)
+    }
+
+    fn store_key_pair(
+        &self,
+        label: &str,
+        hex: KeyPairHex,
+    ) -> Result<StoredKeyPair, AppConfigError<KeyPairingError>> {
+        match self.kek()? {
+            Some(kek) => Ok(StoredKeyPair::Encrypted(kek.encrypt(label, &hex)?)),
+            None => Ok(StoredKeyPair::Plaintext(hex)),
+        }
     }
 
     pub fn write(&self) -> Result<(), AppConfigError<KeyPairingError>> {
-        self.config
-            .save_json(&self.root)
-            .map_err(AppConfigError::WriteError)
+        let json = serde_json::to_vec_pretty(&self.root).map_err(io::Error::from)?;
+        write_atomically(self.config.path(), &json)?;
+        Ok(())
+    }
+
+    // did.jsonl of a did:webvh DID, next to config.json.
+    pub fn did_log_path(&self) -> PathBuf {
+        self.config.path().with_file_name(Self::DID_LOG_FILE)
+    }
+
+    pub fn read_did_log(&self) -> io::Result<String> {
+        fs::read_to_string(self.did_log_path())
+    }
+
+    pub fn write_did_log(&self, did_log: &str) -> io::Result<()> {
+        write_atomically(&self.did_log_path(), did_log.as_bytes())
+    }
+
+    // Pinned DID documents, resolved when the network resolver fails.
+    pub fn did_store_path(&self) -> PathBuf {
+        self.config.path().with_file_name(Self::DID_STORE_DIR)
     }
 
     pub fn load_trng_read_sig(&self) -> Option<Extension> {
         self.root.extensions.trng.as_ref().map(|v| v.read.clone())
     }
 
+    pub fn load_trng_fallback_policy(&self) -> TRNGFallbackPolicy {
+        self.root
+            .extensions
+            .trng
+            .as_ref()
+            .map(|v| v.fallback)
+            .unwrap_or_default()
+    }
+
     pub fn load_secure_keystore_write_sig(&self) -> Option<Extension> {
         self.root
             .extensions
@@ -227,66 +500,224 @@ impl AppConfig {
             .map(|v| v.decrypt.clone())
     }
 
-    pub fn load_sign_key_pair(&self) -> Option<K256KeyPair> {
-        load_key_pair(&self.root.key_pairs.sign)
+    pub fn load_sign_key_pair(
+        &self,
+    ) -> Result<Option<SignKeyPair>, AppConfigError<KeyPairingError>> {
+        self.load_key_pair("sign", &self.root.key_pairs.sign)
+    }
+
+    pub fn save_sign_key_pair(
+        &mut self,
+        value: &SignKeyPair,
+    ) -> Result<(), AppConfigError<KeyPairingError>> {
+        let stored = self.store_key_pair("sign", value.to_hex_key_pair())?;
+        let previous = self.root.key_pairs.sign.replace(stored);
+        self.write()
+            .inspect_err(|_| self.root.key_pairs.sign = previous)
+    }
+
+    pub fn load_update_key_pair(
+        &self,
+    ) -> Result<Option<K256KeyPair>, AppConfigError<KeyPairingError>> {
+        self.load_key_pair("update", &self.root.key_pairs.update)
+    }
+
+    pub fn save_update_key_pair(
+        &mut self,
+        value: &K256KeyPair,
+    ) -> Result<(), AppConfigError<KeyPairingError>> {
+        let stored = self.store_key_pair("update", value.to_hex_key_pair())?;
+        let previous = self.root.key_pairs.update.replace(stored);
+        self.write()
+            .inspect_err(|_| self.root.key_pairs.update = previous)
+    }
+
+    pub fn load_recovery_key_pair(
+        &self,
+    ) -> Result<Option<K256KeyPair>, AppConfigError<KeyPairingError>> {
+        self.load_key_pair("recovery", &self.root.key_pairs.recovery)
+    }
+
+    pub fn save_recovery_key_pair(
+        &mut self,
+        value: &K256KeyPair,
+    ) -> Result<(), AppConfigError<KeyPairingError>> {
+        let stored = self.store_key_pair("recovery", value.to_hex_key_pair())?;
+        let previous = self.root.key_pairs.recovery.replace(stored);
+        self.write()
+            .inspect_err(|_| self.root.key_pairs.recovery = previous)
+    }
+
+    pub fn load_encrypt_key_pair(
+        &self,
+    ) -> Result<Option<X25519KeyPair>, AppConfigError<KeyPairingError>> {
+        self.load_key_pair("encrypt", &self.root.key_pairs.encrypt)
+    }
+
+    pub fn save_encrypt_key_pair(
+        &mut self,
+        value: &X25519KeyPair,
+    ) -> Result<(), AppConfigError<KeyPairingError>> {
+        let stored = self.store_key_pair("encrypt", value.to_hex_key_pair())?;
+        let previous = self.root.key_pairs.encrypt.replace(stored);
+        self.write()
+            .inspect_err(|_| self.root.key_pairs.encrypt = previous)
+    }
+
+    pub fn load_webvh_update_key_pair(
+        &self,
+    ) -> Result<Option<Ed25519KeyPair>, AppConfigError<KeyPairingError>> {
+        self.load_key_pair("webvh_update", &self.root.key_pairs.webvh_update)
+    }
+
+    pub fn save_webvh_update_key_pair(
+        &mut self,
+        value: &Ed25519KeyPair,
+    ) -> Result<(), AppConfigError<KeyPairingError>> {
+        let stored = self.store_key_pair("webvh_update", value.to_hex_key_pair())?;
+        let previous = self.root.key_pairs.webvh_update.replace(stored);
+        self.write()
+            .inspect_err(|_| self.root.key_pairs.webvh_update = previous)
+    }
+
+    // Replaces all key pairs with a single write of config.json.
+    pub fn save_keyring(
+        &mut self,
+        keyring: &KeyPairing,
+    ) -> Result<(), AppConfigError<KeyPairingError>> {
+        let key_pairs = KeyPairsConfig {
+            sign: Some(self.store_key_pair("sign", keyring.sign.to_hex_key_pair())?),
+            update: Some(self.store_key_pair("update", keyring.update.to_hex_key_pair())?),
+            recovery: Some(self.store_key_pair("recovery", keyring.recovery.to_hex_key_pair())?),
+            encrypt: Some(self.store_key_pair("encrypt", keyring.encrypt.to_hex_key_pair())?),
+            webvh_update: self.root.key_pairs.webvh_update.clone(),
+        };
+        let previous = std::mem::replace(&mut self.root.key_pairs, key_pairs);
+        self.write().inspect_err(|_| self.root.key_pairs = previous)
+    }
+
+    pub fn load_pending_keyring(
+        &self,
+    ) -> Result<Option<KeyPairing>, AppConfigError<KeyPairingError>> {
+        match self.root.pending_key_pairs.as_ref() {
+            Some(key_pairs) => self.load_labeled_keyring(PENDING_LABEL, key_pairs),
+            None => Ok(None),
+        }
     }
 
-    pub fn load_keyring(&self) -> Option<KeyPairing> {
-        let sign = self.load_sign_key_pair()?;
-        let update = self.load_update_key_pair()?;
-        let recovery = self.load_recovery_key_pair()?;
-        let encrypt = self.load_encrypt_key_pair()?;
-        Some(KeyPairing {
+    // Saves the key pairs about to be submitted, or clears them with None.
+    pub fn save_pending_keyring(
+        &mut self,
+        keyring: Option<&KeyPairing>,
+    ) -> Result<(), AppConfigError<KeyPairingError>> {
+        let key_pairs = keyring
+            .map(|keyring| self.store_labeled_keyring(PENDING_LABEL, keyring))
+            .transpose()?;
+        let previous = std::mem::replace(&mut self.root.pending_key_pairs, key_pairs);
+        self.write()
+            .inspect_err(|_| self.root.pending_key_pairs = previous)
+    }
+
+    fn load_labeled_keyring(
+        &self,
+        label: &str,
+        key_pairs: &KeyPairsConfig,
+    ) -> Result<Option<KeyPairing>, AppConfigError<KeyPairingError>> {
+        let (Some(sign), Some(update), Some(recovery), Some(encrypt)) = (
+            self.load_key_pair(&peer_label(label, "sign"), &key_pairs.sign)?,
+            self.load_key_pair(&peer_label(label, "update"), &key_pairs.update)?,
+            self.load_key_pair(&peer_label(label, "recovery"), &key_pairs.recovery)?,
+            self.load_key_pair(&peer_label(label, "encrypt"), &key_pairs.encrypt)?,
+        ) else {
+            return Ok(None);
+        };
+        Ok(Some(KeyPairing {
             sign,
             update,
             recovery,
             encrypt,
+        }))
+    }
+
+    fn store_labeled_keyring(
+        &self,
+        label: &str,
+        keyring: &KeyPairing,
+    ) -> Result<KeyPairsConfig, AppConfigError<KeyPairingError>> {
+        let store = |slot: &str, hex: KeyPairHex| {
+            self.store_key_pair(&peer_label(label, slot), hex).map(Some)
+        };
+        Ok(KeyPairsConfig {
+            sign: store("sign", keyring.sign.to_hex_key_pair())?,
+            update: store("update", keyring.update.to_hex_key_pair())?,
+            recovery: store("recovery", keyring.recovery.to_hex_key_pair())?,
+            encrypt: store("encrypt", keyring.encrypt.to_hex_key_pair())?,
+            webvh_update: None,
         })
     }
 
-    pub fn save_sign_key_pair(&mut self, value: &K256KeyPair) {
-        self.root.key_pairs.sign = Some(value.to_hex_key_pair());
-        self.write().unwrap();
-    }
-
-    pub fn load_update_key_pair(&self) -> Option<K256KeyPair> {
-        load_key_pair(&self.root.key_pairs.update)
+    pub fn get_did(&self) -> Option<String> {
+        self.root.did.clone()
     }
 
-    pub fn save_update_key_pair(&mut self, value: &K256KeyPair) {
-        self.root.key_pairs.update = Some(value.to_hex_key_pair());
-        self.write().unwrap();
+    pub fn get_peer_dids(&self) -> Vec<String> {
+        self.root
+            .peer_identities
+            .iter()
+            .map(|v| v.did.clone())
+            .collect()
+    }
+
+    pub fn load_peer_keyring(
+        &self,
+        did: &str,
+    ) -> Result<Option<KeyPairing>, AppConfigError<KeyPairingError>> {
+        match self.root.peer_identities.iter().find(|v| v.did == did) {
+            Some(identity) => self.load_labeled_keyring(did, &identity.key_pairs),
+            None => Ok(None),
+        }
     }
 
-    pub fn load_recovery_key_pair(&self) -> Option<K256KeyPair> {
-        load_key_pair(&self.root.key_pairs.recovery)
+    pub fn save_peer_keyring(
+        &mut self,
+        did: &str,
+        keyring: &KeyPairing,
+    ) -> Result<(), AppConfigError<KeyPairingError>> {
+        let identity = PeerIdentityConfig {
+            did: did.to_string(),
+            key_pairs: self.store_labeled_keyring(did, keyring)?,
+        };
+        self.root.peer_identities.push(identity);
+        self.write().inspect_err(|_| {
+            self.root.peer_identities.pop();
+        })
     }
 
-    pub fn save_recovery_key_pair(&mut self, value: &K256KeyPair) {
-        self.root.key_pairs.recovery = Some(value.to_hex_key_pair());
-        self.write().unwrap();
+    pub fn save_did(&mut self, value: &str) {
+        self.root.did = Some(value.to_string());
+        self.write().unwrap_log()
     }
 
-    pub fn load_encrypt_key_pair(&self) -> Option<X25519KeyPair> {
-        load_key_pair(&self.root.key_pairs.encrypt)
+    pub fn remove_did(&mut self) {
+        self.root.did = None;
+        self.root.is_initialized = false;
+        self.write().unwrap_log()
     }
 
-    pub fn save_encrypt_key_pair(&mut self, value: &X25519KeyPair) {
-        self.root.key_pairs.encrypt = Some(value.to_hex_key_pair());
-        self.write().unwrap();
+    pub fn get_didcomm_body_size(&self) -> usize {
+        self.root.didcomm.http_body_size_limit
     }
 
-    pub fn get_did(&self) -> Option<String> {
-        self.root.did.clone()
+    pub fn get_did_resolver_config(&self) -> DidResolverConfig {
+        self.root.did_resolver.clone()
     }
 
-    pub fn save_did(&mut self, value: &str) {
-        self.root.did = Some(value.to_string());
-        self.write().unwrap_log()
+    pub fn get_long_form_did(&self) -> bool {
+        self.root.long_form_did
     }
 
-    pub fn get_didcomm_body_size(&self) -> usize {
-        self.root.didcomm.http_body_size_limit
+    pub fn get_sign_key_type(&self) -> SignKeyType {
+        self.root.sign_key_type
     }
 
     pub fn get_metric_collect_interval(&self) -> u64 {
@@ -376,3 +807,158 @@ struct MetricsConfig {
     send_interval: u64,
     cache_capacity: usize,
 }
+
+#[cfg(test)]
+mod tests {
+    use protocol::rand_core::{OsRng, RngCore};
+
+    use super::*;
+
+    fn temp_dir() -> PathBuf {
+        let dir = env::temp_dir().join(format!("nodex-config-{}", OsRng.next_u64()));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_sidetree_hash_algorithm() {
+        let dir = temp_dir();
+        let path = dir.join("config.json");
+        fs::write(&path, b"{}").unwrap();
+        let config = AppConfig::open(HomeConfig::with_file(&path));
+        assert_eq!(
+            config.get_did_resolver_config().sidetree_hash_algorithm,
+            HashAlgorithm::Sha256
+        );
+
+        let root = serde_json::json!({ "did_resolver": { "sidetree_hash_algorithm": "sha3-256" } });
+        fs::write(&path, serde_json::to_vec(&root).unwrap()).unwrap();
+        let config = AppConfig::open(HomeConfig::with_file(&path));
+        assert_eq!(
+            config.get_did_resolver_config().sidetree_hash_algorithm,
+            HashAlgorithm::Sha3_256
+        );
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_migrate_plaintext_key_pairs() {
+        let dir = temp_dir();
+        let path = dir.join("config.json");
+        let keyring = KeyPairing::create_keyring(OsRng);
+        let hex = |v: KeyPairHex| serde_json::to_value(v).unwrap();
+        let key_pairs = serde_json::json!({
+            "sign": hex(keyring.sign.to_hex_key_pair()),
+            "update": hex(keyring.update.to_hex_key_pair()),
+            "recovery": hex(keyring.recovery.to_hex_key_pair()),
+            "encrypt": hex(keyring.encrypt.to_hex_key_pair()),
+        });
+        let schema_1 = serde_json::json!({
+            "did": "did:nodex:test:migrate",
+            "key_pairs": key_pairs,
+            "key_encryption": { "type": "machine_secret" },
+            "schema_version": 1,
+        });
+        fs::write(&path, serde_json::to_vec(&schema_1).unwrap()).unwrap();
+
+        let config = AppConfig::open(HomeConfig::with_file(&path));
+        let root: ConfigRoot = serde_json::from_slice(&fs::read(&path).unwrap()).unwrap();
+        assert_eq!(root.schema_version, SCHEMA_VERSION);
+        for stored in [
+            &root.key_pairs.sign,
+            &root.key_pairs.update,
+            &root.key_pairs.recovery,
+            &root.key_pairs.encrypt,
+        ] {
+            assert!(matches!(stored, Some(StoredKeyPair::Encrypted(_))));
+        }
+
+        // NOTE: The rewritten config loads the same key pairs, also when opened again.
+        for config in [config, AppConfig::open(HomeConfig::with_file(&path))] {
+            assert_eq!(config.get_did().as_deref(), Some("did:nodex:test:migrate"));
+            let loaded = serde_json::json!({
+                "sign": hex(config.load_sign_key_pair().unwrap().unwrap().to_hex_key_pair()),
+                "update": hex(config.load_update_key_pair().unwrap().unwrap().to_hex_key_pair()),
+                "recovery": hex(config.load_recovery_key_pair().unwrap().unwrap().to_hex_key_pair()),
+                "encrypt": hex(config.load_encrypt_key_pair().unwrap().unwrap().to_hex_key_pair()),
+            });
+            assert_eq!(loaded, key_pairs);
+        }
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_save_key_pair_write_failed() {
+        let dir = temp_dir();
+        let path = dir.join("config.json");
+        fs::write(&path, b"{}").unwrap();
+        let mut config = AppConfig::open(HomeConfig::with_file(&path));
+        let keyring = KeyPairing::create_keyring(OsRng);
+        config.save_sign_key_pair(&keyring.sign).unwrap();
+
+        // NOTE: A failed write is returned and leaves the loaded key pairs as they were.
+        fs::remove_dir_all(&dir).unwrap();
+        let result = config.save_sign_key_pair(&KeyPairing::create_keyring(OsRng).sign);
+        assert!(matches!(result, Err(AppConfigError::WriteError(_))));
+        let sign = config.load_sign_key_pair().unwrap().unwrap();
+        assert_eq!(sign.get_public_key(), keyring.sign.get_public_key());
+    }
+
+    #[test]
+    fn test_open_without_key_encryption_key() {
+        let dir = temp_dir();
+        let path = dir.join("config.json");
+        let missing = serde_json::json!({ "filename": dir.join("missing.so"), "symbol": "cipher" });
+        let root = serde_json::json!({
+            "did": "did:nodex:test:kek",
+            "key_encryption": { "type": "cipher_extension" },
+            "extensions": { "cipher": { "encrypt": missing, "decrypt": missing } },
+        });
+        fs::write(&path, serde_json::to_vec(&root).unwrap()).unwrap();
+
+        // NOTE: A cipher extension failing to load is returned by the key pairs only.
+        let mut config = AppConfig::open(HomeConfig::with_file(&path));
+        assert_eq!(config.get_did().as_deref(), Some("did:nodex:test:kek"));
+        assert!(config.load_sign_key_pair().unwrap().is_none());
+        let keyring = KeyPairing::create_keyring(OsRng);
+        assert!(matches!(
+            config.save_keyring(&keyring),
+            Err(AppConfigError::KeyEncryptionKeyUnavailable(_))
+        ));
+        assert!(matches!(
+            config.save_sign_key_pair(&keyring.sign),
+            Err(AppConfigError::KeyEncryptionKeyUnavailable(_))
+        ));
+        assert!(config.load_sign_key_pair().unwrap().is_none());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_load_undecryptable_key_pairs() {
+        let dir = temp_dir();
+        let path = dir.join("config.json");
+        fs::write(&path, br#"{"key_encryption":{"type":"machine_secret"}}"#).unwrap();
+
+        let mut config = AppConfig::open(HomeConfig::with_file(&path));
+        assert!(config.load_sign_key_pair().unwrap().is_none());
+        assert!(config.load_pending_keyring().unwrap().is_none());
+        let keyring = KeyPairing::create_keyring(OsRng);
+        config.save_keyring(&keyring).unwrap();
+        config.save_pending_keyring(Some(&keyring)).unwrap();
+
+        // NOTE: Another machine secret, e.g. a replaced keystore.secret, is not the same as absent keys.
+        let mut secret = [0u8; 32];
+        OsRng.fill_bytes(&mut secret);
+        fs::write(dir.join("keystore.secret"), secret).unwrap();
+        let config = AppConfig::open(HomeConfig::with_file(&path));
+        assert!(matches!(
+            config.load_sign_key_pair(),
+            Err(AppConfigError::KeyEncryption(_))
+        ));
+        assert!(matches!(
+            config.load_pending_keyring(),
+            Err(AppConfigError::KeyEncryption(_))
+        ));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -53,6 +53,10 @@ pub enum AgentErrorCode {
     SendEventInvalidOccurredAt = 1022,
     #[error("Bad Request")]
     MessageActivityBadRequest = 1023,
+    #[error("add or remove is required")]
+    UpdateServicesNoPatch = 1024,
+    #[error("from_did is not my DID")]
+    CreateDidCommMessageNotMyDid = 1025,
 
     #[error("this message is not addressed to me")]
     VerifyDidcommMessageNotAddressedToMe = 2001,
@@ -107,6 +111,16 @@ pub enum AgentErrorCode {
     SendEventInternal = 5011,
     #[error("Internal Server Error")]
     MessageActivityInternal = 5012,
+    #[error("Internal Server Error")]
+    RotateKeysInternal = 5013,
+    #[error("Internal Server Error")]
+    DeactivateIdentifierInternal = 5014,
+    #[error("Internal Server Error")]
+    UpdateServicesInternal = 5015,
+    #[error("Internal Server Error")]
+    CreatePeerIdentifierInternal = 5016,
+    #[error("Internal Server Error")]
+    JwksInternal = 5017,
 
     #[error("it have already been verified")]
     MessageActivityConflict = 6001,
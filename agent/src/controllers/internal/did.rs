@@ -0,0 +1,56 @@
+use crate::controllers::errors::AgentErrorCode;
+use axum::extract::Json;
+use protocol::did::sidetree::payload::ServiceEndpoint;
+use serde::{Deserialize, Serialize};
+
+// NOTE: POST /internal/did/rotate-keys
+pub async fn handler_rotate_keys() -> Result<Json<&'static str>, AgentErrorCode> {
+    let service = crate::services::nodex::NodeX::new();
+
+    match service.rotate_keys().await {
+        Ok(_) => Ok(Json("ok")),
+        Err(e) => {
+            log::error!("{:?}", e);
+            Err(AgentErrorCode::RotateKeysInternal)?
+        }
+    }
+}
+
+// NOTE: POST /internal/did/deactivate
+pub async fn handler_deactivate() -> Result<Json<&'static str>, AgentErrorCode> {
+    let service = crate::services::nodex::NodeX::new();
+
+    match service.deactivate_identifier().await {
+        Ok(_) => Ok(Json("ok")),
+        Err(e) => {
+            log::error!("{:?}", e);
+            Err(AgentErrorCode::DeactivateIdentifierInternal)?
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct UpdateServicesRequest {
+    #[serde(default)]
+    add: Vec<ServiceEndpoint>,
+    #[serde(default)]
+    remove: Vec<String>,
+}
+
+// NOTE: POST /internal/did/services
+pub async fn handler_update_services(
+    Json(json): Json<UpdateServicesRequest>,
+) -> Result<Json<&'static str>, AgentErrorCode> {
+    if json.add.is_empty() && json.remove.is_empty() {
+        Err(AgentErrorCode::UpdateServicesNoPatch)?
+    }
+    let service = crate::services::nodex::NodeX::new();
+
+    match service.update_services(json.add, json.remove).await {
+        Ok(_) => Ok(Json("ok")),
+        Err(e) => {
+            log::error!("{:?}", e);
+            Err(AgentErrorCode::UpdateServicesInternal)?
+        }
+    }
+}
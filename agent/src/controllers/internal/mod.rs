@@ -1,2 +1,3 @@
+pub mod did;
 pub mod network;
 pub mod version;
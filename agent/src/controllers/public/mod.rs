@@ -1,7 +1,9 @@
 pub mod nodex_create_didcomm_message;
 pub mod nodex_create_identifier;
+pub mod nodex_create_peer_identifier;
 pub mod nodex_create_verifiable_message;
 pub mod nodex_find_identifier;
+pub mod nodex_jwks;
 pub mod nodex_receive;
 pub mod nodex_verify_didcomm_message;
 pub mod nodex_verify_verifiable_message;
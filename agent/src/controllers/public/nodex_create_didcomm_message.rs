@@ -17,6 +17,9 @@ pub struct MessageContainer {
     message: String,
     #[serde(default)]
     operation_tag: String,
+    // One of our pairwise DIDs to send from, instead of our own DID.
+    #[serde(default)]
+    from_did: Option<String>,
 }
 
 pub async fn handler(Json(json): Json<MessageContainer>) -> Result<String, AgentErrorCode> {
@@ -35,13 +38,36 @@ pub async fn handler(Json(json): Json<MessageContainer>) -> Result<String, Agent
     let usecase =
         DidcommMessageUseCase::new(Studio::new(), utils::did_repository(), DidAccessorImpl {});
 
-    match usecase
-        .generate(json.destination_did, json.message, json.operation_tag, now)
-        .await
-    {
+    let generated = match json.from_did {
+        Some(from_did) => {
+            usecase
+                .generate_from(
+                    from_did,
+                    json.destination_did,
+                    json.message,
+                    json.operation_tag,
+                    now,
+                )
+                .await
+        }
+        None => {
+            usecase
+                .generate(json.destination_did, json.message, json.operation_tag, now)
+                .await
+        }
+    };
+    match generated {
         Ok(v) => Ok(v),
         Err(e) => match e {
             U::MessageActivity(e) => Err(utils::handle_status(e)),
+            U::NotMyDid(did) => {
+                log::warn!("not my DID. did = {}", did);
+                Err(AgentErrorCode::CreateDidCommMessageNotMyDid)?
+            }
+            U::DidAccessor(e) => {
+                log::error!("{:?}", e);
+                Err(AgentErrorCode::CreateDidcommMessageInternal)?
+            }
             U::ServiceGenerate(S::DidDocNotFound(target)) => {
                 log::warn!("target DID not found. did = {}", target);
                 Err(AgentErrorCode::CreateDidCommMessageNoDid)?
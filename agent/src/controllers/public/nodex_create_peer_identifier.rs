@@ -0,0 +1,25 @@
+use crate::controllers::errors::AgentErrorCode;
+use axum::extract::Json;
+use protocol::did::sidetree::payload::{DidResolutionResponse, ServiceEndpoint};
+use serde::{Deserialize, Serialize};
+
+// NOTE: POST /peer-identifiers
+#[derive(Deserialize, Serialize)]
+pub struct CreatePeerIdentifierRequest {
+    #[serde(default)]
+    services: Vec<ServiceEndpoint>,
+}
+
+pub async fn handler(
+    Json(json): Json<CreatePeerIdentifierRequest>,
+) -> Result<Json<DidResolutionResponse>, AgentErrorCode> {
+    let service = crate::services::nodex::NodeX::new();
+
+    match service.create_peer_identifier(json.services) {
+        Ok(v) => Ok(Json(v)),
+        Err(e) => {
+            log::error!("{:?}", e);
+            Err(AgentErrorCode::CreatePeerIdentifierInternal)?
+        }
+    }
+}
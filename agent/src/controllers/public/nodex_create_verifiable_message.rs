@@ -49,6 +49,10 @@ pub async fn handler(Json(json): Json<MessageContainer>) -> Result<String, Agent
                 }
                 Err(AgentErrorCode::CreateVerifiableMessageNoTargetDid)?
             }
+            U::DidAccessor(e) => {
+                log::error!("{:?}", e);
+                Err(AgentErrorCode::CreateVerifiableMessageInternal)?
+            }
             U::DidVcServiceGenerate(e) => {
                 log::error!("{:?}", e);
                 Err(AgentErrorCode::CreateVerifiableMessageInternal)?
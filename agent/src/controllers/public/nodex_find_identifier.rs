@@ -1,17 +1,26 @@
-use crate::controllers::errors::AgentErrorCode;
 use axum::extract::{Json, Path};
-use protocol::did::sidetree::payload::DidResolutionResponse;
+use axum::http::StatusCode;
+use protocol::did::resolution::{DidResolutionResult, ResolutionErrorCode};
 
-pub async fn handler(
-    did: Path<String>,
-) -> Result<Json<Option<DidResolutionResponse>>, AgentErrorCode> {
+// Statuses of the DID Resolution HTTP(S) binding.
+// https://w3c-ccg.github.io/did-resolution/#bindings-https
+fn status_code(result: &DidResolutionResult) -> StatusCode {
+    match result.error_code() {
+        None => StatusCode::OK,
+        Some(ResolutionErrorCode::InvalidDid) => StatusCode::BAD_REQUEST,
+        Some(ResolutionErrorCode::NotFound) => StatusCode::NOT_FOUND,
+        Some(ResolutionErrorCode::MethodNotSupported) => StatusCode::NOT_IMPLEMENTED,
+        Some(ResolutionErrorCode::Deactivated) => StatusCode::GONE,
+        Some(ResolutionErrorCode::InternalError) => StatusCode::INTERNAL_SERVER_ERROR,
+    }
+}
+
+pub async fn handler(did: Path<String>) -> (StatusCode, Json<DidResolutionResult>) {
     let service = crate::services::nodex::NodeX::new();
 
-    match service.find_identifier(&did).await {
-        Ok(v) => Ok(Json(v)),
-        Err(e) => {
-            log::error!("{:?}", e);
-            Err(AgentErrorCode::FindIdentifierInternal)?
-        }
+    let result = service.resolve_identifier(&did).await;
+    if result.error_code() == Some(ResolutionErrorCode::InternalError) {
+        log::error!("{:?}", result.did_resolution_metadata.error_message);
     }
+    (status_code(&result), Json(result))
 }
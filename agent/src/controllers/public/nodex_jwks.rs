@@ -0,0 +1,16 @@
+use crate::controllers::errors::AgentErrorCode;
+use axum::extract::Json;
+use protocol::keyring::jwk::Jwks;
+
+// NOTE: GET /jwks
+pub async fn handler() -> Result<Json<Jwks>, AgentErrorCode> {
+    let service = crate::services::nodex::NodeX::new();
+
+    match service.jwks() {
+        Ok(v) => Ok(Json(v)),
+        Err(e) => {
+            log::error!("{:?}", e);
+            Err(AgentErrorCode::JwksInternal)?
+        }
+    }
+}
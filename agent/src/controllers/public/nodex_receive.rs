@@ -64,7 +64,7 @@ impl MessageReceiveUsecase {
             log::info!("Receive message. message_id = {:?}", m.id);
             match DidCommEncryptedService::verify(
                 self.agent.did_repository(),
-                &DidAccessorImpl {}.get_my_keyring(),
+                &DidAccessorImpl {}.get_my_keyring()?,
                 &json_message,
             )
             .await
@@ -42,6 +42,10 @@ pub async fn handler(
                     log::warn!("this message is not addressed to me: {}", e);
                     Err(AgentErrorCode::VerifyDidcommMessageNotAddressedToMe)?
                 }
+                U::DidAccessor(e) => {
+                    log::error!("{:?}", e);
+                    Err(AgentErrorCode::VerifyDidcommMessageInternal)?
+                }
                 U::ServiceVerify(S::FindSender(e)) => {
                     log::warn!("cannot find sender: {}", e);
                     Err(AgentErrorCode::VerifyDidcommMessageNoSender)?
@@ -59,6 +63,10 @@ pub async fn handler(
                     log::warn!("verify failed: {}", e);
                     Err(AgentErrorCode::VerifyDidcommMessageVerifyFailed)?
                 }
+                U::ServiceVerify(S::Deactivated(target)) => {
+                    log::warn!("sender DID is deactivated. DID = {}", target);
+                    Err(AgentErrorCode::VerifyDidcommMessageVerifyFailed)?
+                }
                 U::ServiceVerify(S::DidDocNotFound(target)) => {
                     log::warn!("target DID not found. DID = {}", target);
                     Err(AgentErrorCode::VerifyDidcommMessageNoTargetDid)?
@@ -44,6 +44,10 @@ pub async fn handler(
                     log::warn!("find identifier error: {}", e);
                     Err(AgentErrorCode::VerifyVerifiableMessageNoIdentifier)?
                 }
+                U::DidVcServiceVerify(S::Deactivated(target)) => {
+                    log::warn!("target DID is deactivated. DID = {}", target);
+                    Err(AgentErrorCode::VerifyVerifiableMessageVerifyFailed)?
+                }
                 U::DidVcServiceVerify(S::DidDocNotFound(target)) => {
                     log::warn!("target DID not found. DID = {}", target);
                     Err(AgentErrorCode::VerifyVerifiableMessageNoTargetDid)?
@@ -3,6 +3,7 @@ use cli::AgentCommands;
 use dotenvy::dotenv;
 use mac_address::get_mac_address;
 use nodex::utils::UnwrapLog;
+use protocol::did::sidetree::payload::ServiceEndpoint;
 use services::metrics::{MetricsInMemoryCacheService, MetricsWatchService};
 use services::nodex::NodeX;
 use services::studio::Studio;
@@ -45,9 +46,78 @@ pub async fn run(controlled: bool, options: &cli::AgentOptions) -> std::io::Resu
 
     // NOTE: generate Key Chain
     let node_x = NodeX::new();
+    // NOTE: Must run before create_identifier, which creates a Sidetree DID otherwise.
+    if let Some(AgentCommands::CreateWebvh { uri }) = options.command.as_ref() {
+        if options.config {
+            let did = node_x.create_webvh_identifier(uri).unwrap_log();
+            println!(
+                "{} is created. Host {} at its did:webvh log url",
+                did,
+                app_config().lock().did_log_path().display()
+            );
+            return Ok(());
+        }
+    }
+    // NOTE: Does not need our own DID, so works offline.
+    if let Some(AgentCommands::ImportDid { file }) = options.command.as_ref() {
+        if options.config {
+            let bundle = fs::read_to_string(file).unwrap_log();
+            let did = node_x.import_did_document(&bundle).unwrap_log();
+            println!("{} is imported", did);
+            return Ok(());
+        }
+    }
     let device_did = node_x.create_identifier().await.unwrap();
 
     if options.config {
+        match options.command.as_ref() {
+            Some(AgentCommands::RotateKeys) => {
+                let did = node_x.rotate_keys().await.unwrap_log();
+                println!("Keys of {} are rotated", did);
+                return Ok(());
+            }
+            Some(AgentCommands::AddService {
+                id,
+                r#type,
+                endpoint,
+                description,
+            }) => {
+                let service = ServiceEndpoint {
+                    id: id.clone(),
+                    r#type: r#type.clone(),
+                    service_endpoint: endpoint.clone().into(),
+                    description: description.clone(),
+                };
+                let did = node_x
+                    .update_services(vec![service], vec![])
+                    .await
+                    .unwrap_log();
+                println!("{} is added to {}", id, did);
+                return Ok(());
+            }
+            Some(AgentCommands::RemoveService { id }) => {
+                let did = node_x
+                    .update_services(vec![], vec![id.clone()])
+                    .await
+                    .unwrap_log();
+                println!("{} is removed from {}", id, did);
+                return Ok(());
+            }
+            Some(AgentCommands::ExportDid { output }) => {
+                let bundle = node_x.export_did_document(&device_did).unwrap_log();
+                match output {
+                    Some(output) => fs::write(output, bundle).unwrap_log(),
+                    None => println!("{}", bundle),
+                }
+                return Ok(());
+            }
+            Some(AgentCommands::Deactivate { yes: true }) => {
+                let did = node_x.deactivate_identifier().await.unwrap_log();
+                println!("{} is deactivated", did);
+                return Ok(());
+            }
+            _ => {}
+        }
         use_cli(options.command.as_ref(), device_did.did_document.id.clone());
         return Ok(());
     }
@@ -128,6 +198,16 @@ fn use_cli(command: Option<&AgentCommands>, did: String) {
             AgentCommands::Did {} => {
                 println!("Node ID: {}", did);
             }
+            // NOTE: Handled in run.
+            AgentCommands::CreateWebvh { .. }
+            | AgentCommands::RotateKeys
+            | AgentCommands::AddService { .. }
+            | AgentCommands::RemoveService { .. }
+            | AgentCommands::ExportDid { .. }
+            | AgentCommands::ImportDid { .. } => {}
+            AgentCommands::Deactivate { .. } => {
+                println!("Deactivation can not be undone. Run again with --yes to confirm.");
+            }
             AgentCommands::Network { command } => match command {
                 cli::NetworkSubCommands::Set { key, value } => match key.as_str() {
                     SECRET_KEY => {
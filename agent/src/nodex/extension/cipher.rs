@@ -0,0 +1,135 @@
+use std::sync::Arc;
+
+use libloading::Library;
+use thiserror::Error;
+use zeroize::Zeroize;
+
+use crate::config::Extension;
+
+// Cipher extension ABI.
+//
+// int32_t encrypt(const uint8_t *in, size_t in_len, uint8_t *out, size_t out_len, size_t *written);
+// int32_t decrypt(const uint8_t *in, size_t in_len, uint8_t *out, size_t out_len, size_t *written);
+//   Writes the result into `out` (at most `out_len` bytes) and sets `*written`. Returns 0 on success.
+type CipherFn = unsafe extern "C" fn(
+    input: *const u8,
+    input_len: usize,
+    output: *mut u8,
+    output_len: usize,
+    written: *mut usize,
+) -> i32;
+
+// NOTE: Room for whatever IV and tag the extension adds around the input.
+const CIPHER_OVERHEAD: usize = 1024;
+
+#[derive(Debug, Error)]
+pub enum CipherError {
+    #[error("failed to load extension: {0}")]
+    Load(#[from] libloading::Error),
+    #[error("extension returned error code: {0}")]
+    Extension(i32),
+    #[error("extension wrote {0} bytes into a buffer of {1} bytes")]
+    InvalidLength(usize, usize),
+}
+
+#[derive(Clone)]
+pub struct CipherExtension {
+    encrypt: CipherFn,
+    decrypt: CipherFn,
+    // NOTE: `encrypt` and `decrypt` point into these libraries, so keep them loaded.
+    _libraries: Arc<(Library, Library)>,
+}
+
+impl CipherExtension {
+    pub fn load(encrypt: &Extension, decrypt: &Extension) -> Result<Self, CipherError> {
+        // SAFETY: The extensions are trusted configuration and must export the symbols with the ABI above.
+        unsafe {
+            let encrypt_library = Library::new(&encrypt.filename)?;
+            let encrypt = *encrypt_library.get::<CipherFn>(encrypt.symbol.as_bytes())?;
+            let decrypt_library = Library::new(&decrypt.filename)?;
+            let decrypt = *decrypt_library.get::<CipherFn>(decrypt.symbol.as_bytes())?;
+            Ok(CipherExtension {
+                encrypt,
+                decrypt,
+                _libraries: Arc::new((encrypt_library, decrypt_library)),
+            })
+        }
+    }
+
+    pub fn encrypt(&self, plaintext: &[u8]) -> Result<Vec<u8>, CipherError> {
+        call(self.encrypt, plaintext)
+    }
+
+    pub fn decrypt(&self, ciphertext: &[u8]) -> Result<Vec<u8>, CipherError> {
+        call(self.decrypt, ciphertext)
+    }
+}
+
+fn call(f: CipherFn, input: &[u8]) -> Result<Vec<u8>, CipherError> {
+    let mut output = vec![0u8; input.len() + CIPHER_OVERHEAD];
+    let mut written = 0usize;
+    // SAFETY: `input` and `output` are valid buffers of their lengths.
+    let code = unsafe {
+        f(
+            input.as_ptr(),
+            input.len(),
+            output.as_mut_ptr(),
+            output.len(),
+            &mut written,
+        )
+    };
+    if code != 0 {
+        output.zeroize();
+        return Err(CipherError::Extension(code));
+    }
+    // NOTE: Never take a clamped result, which would look valid but be truncated.
+    let len = output.len();
+    if written > len {
+        output.zeroize();
+        return Err(CipherError::InvalidLength(written, len));
+    }
+    output.truncate(written);
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    unsafe extern "C" fn reverse(
+        input: *const u8,
+        input_len: usize,
+        output: *mut u8,
+        _output_len: usize,
+        written: *mut usize,
+    ) -> i32 {
+        let input = std::slice::from_raw_parts(input, input_len);
+        for (i, v) in input.iter().rev().enumerate() {
+            *output.add(i) = *v;
+        }
+        *written = input_len;
+        0
+    }
+
+    unsafe extern "C" fn overflow(
+        _input: *const u8,
+        _input_len: usize,
+        _output: *mut u8,
+        output_len: usize,
+        written: *mut usize,
+    ) -> i32 {
+        *written = output_len + 1;
+        0
+    }
+
+    #[test]
+    fn test_call() {
+        assert_eq!(call(reverse, b"abc").unwrap(), b"cba");
+
+        let result = call(overflow, b"abc");
+        assert!(matches!(
+            result,
+            Err(CipherError::InvalidLength(written, len)) if written == len + 1
+        ));
+    }
+}
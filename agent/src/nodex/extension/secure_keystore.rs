@@ -1,28 +1,102 @@
-use protocol::keyring::keypair::{K256KeyPair, X25519KeyPair};
+use std::sync::Arc;
 
-use crate::config::SingletonAppConfig;
+use libloading::Library;
+use protocol::keyring::keypair::{
+    Ed25519KeyPair, K256KeyPair, KeyPair, KeyPairHex, KeyPairing, KeyPairingError, SignKeyPair,
+    X25519KeyPair,
+};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use zeroize::Zeroize;
+
+use crate::config::{AppConfigError, Extension, SingletonAppConfig};
 
 pub enum SecureKeyStoreKey<'a> {
-    Sign(&'a K256KeyPair),
+    Sign(&'a SignKeyPair),
     Update(&'a K256KeyPair),
     Recovery(&'a K256KeyPair),
     Encrypt(&'a X25519KeyPair),
+    WebvhUpdate(&'a Ed25519KeyPair),
 }
 
-#[derive(Debug)]
+// NOTE: The discriminants are passed to the secure keystore extension as `key_type`.
+#[derive(Debug, Clone, Copy)]
+#[repr(u32)]
 pub enum SecureKeyStoreType {
-    Sign,
-    Update,
-    Recovery,
-    Encrypt,
+    Sign = 0,
+    Update = 1,
+    Recovery = 2,
+    Encrypt = 3,
+    Pending = 4,
+    WebvhUpdate = 5,
+}
+
+#[derive(Debug, Error)]
+pub enum SecureKeyStoreError {
+    #[error("failed to load extension: {0}")]
+    Load(#[from] libloading::Error),
+    #[error("extension returned error code: {0}")]
+    Extension(i32),
+    #[error("failed to serialize key pair: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("failed to decode key pair: {0}")]
+    KeyPairing(#[from] KeyPairingError),
+    #[error("failed to write config: {0}")]
+    Config(#[from] AppConfigError<KeyPairingError>),
+    #[error("extension wrote {0} bytes into a buffer of {1} bytes")]
+    InvalidLength(usize, usize),
 }
 
 pub trait SecureKeyStore {
-    fn write(&self, key_pair: &SecureKeyStoreKey);
-    fn read_sign(&self) -> Option<K256KeyPair>;
-    fn read_update(&self) -> Option<K256KeyPair>;
-    fn read_recovery(&self) -> Option<K256KeyPair>;
-    fn read_encrypt(&self) -> Option<X25519KeyPair>;
+    fn write(&self, key_pair: &SecureKeyStoreKey) -> Result<(), SecureKeyStoreError>;
+    // NOTE: Backends which can replace all key pairs at once should override this.
+    //       Otherwise the stored key pairs are restored when one of the writes fails,
+    //       so that the keystore is not left with a mix of old and new key pairs.
+    fn write_keyring(&self, keyring: &KeyPairing) -> Result<(), SecureKeyStoreError> {
+        let (sign, update, recovery, encrypt) = (
+            self.read_sign()?,
+            self.read_update()?,
+            self.read_recovery()?,
+            self.read_encrypt()?,
+        );
+        let previous = [
+            sign.as_ref().map(SecureKeyStoreKey::Sign),
+            update.as_ref().map(SecureKeyStoreKey::Update),
+            recovery.as_ref().map(SecureKeyStoreKey::Recovery),
+            encrypt.as_ref().map(SecureKeyStoreKey::Encrypt),
+        ];
+        let next = [
+            SecureKeyStoreKey::Sign(&keyring.sign),
+            SecureKeyStoreKey::Update(&keyring.update),
+            SecureKeyStoreKey::Recovery(&keyring.recovery),
+            SecureKeyStoreKey::Encrypt(&keyring.encrypt),
+        ];
+        for (i, key_pair) in next.iter().enumerate() {
+            if let Err(e) = self.write(key_pair) {
+                for key_pair in previous[..i].iter().flatten() {
+                    if let Err(e) = self.write(key_pair) {
+                        log::error!("failed to restore key pair: {:?}", e);
+                    }
+                }
+                return Err(e);
+            }
+        }
+        Ok(())
+    }
+    // NOTE: None only when the key pair is not stored. A failure to read one is an error,
+    //       so that the stored key pairs are never taken as absent and replaced.
+    fn read_sign(&self) -> Result<Option<SignKeyPair>, SecureKeyStoreError>;
+    fn read_update(&self) -> Result<Option<K256KeyPair>, SecureKeyStoreError>;
+    fn read_recovery(&self) -> Result<Option<K256KeyPair>, SecureKeyStoreError>;
+    fn read_encrypt(&self) -> Result<Option<X25519KeyPair>, SecureKeyStoreError>;
+    fn read_webvh_update(&self) -> Result<Option<Ed25519KeyPair>, SecureKeyStoreError>;
+    // The keyring submitted to an update of our own DID until it is promoted,
+    // stored apart from the key pairs in use. None clears it.
+    fn write_pending_keyring(
+        &self,
+        keyring: Option<&KeyPairing>,
+    ) -> Result<(), SecureKeyStoreError>;
+    fn read_pending_keyring(&self) -> Result<Option<KeyPairing>, SecureKeyStoreError>;
 }
 
 #[derive(Clone)]
@@ -42,41 +116,530 @@ fn k2t(k: &SecureKeyStoreKey) -> SecureKeyStoreType {
         SecureKeyStoreKey::Update(_) => SecureKeyStoreType::Update,
         SecureKeyStoreKey::Recovery(_) => SecureKeyStoreType::Recovery,
         SecureKeyStoreKey::Encrypt(_) => SecureKeyStoreType::Encrypt,
+        SecureKeyStoreKey::WebvhUpdate(_) => SecureKeyStoreType::WebvhUpdate,
+    }
+}
+
+fn k2hex(k: &SecureKeyStoreKey) -> KeyPairHex {
+    match k {
+        SecureKeyStoreKey::Sign(k) => k.to_hex_key_pair(),
+        SecureKeyStoreKey::Update(k) => k.to_hex_key_pair(),
+        SecureKeyStoreKey::Recovery(k) => k.to_hex_key_pair(),
+        SecureKeyStoreKey::Encrypt(k) => k.to_hex_key_pair(),
+        SecureKeyStoreKey::WebvhUpdate(k) => k.to_hex_key_pair(),
     }
 }
 
 impl SecureKeyStore for FileBaseKeyStore {
-    fn write(&self, key_pair: &SecureKeyStoreKey) {
+    fn write(&self, key_pair: &SecureKeyStoreKey) -> Result<(), SecureKeyStoreError> {
         log::info!("Called: write_internal (type: {:?})", k2t(key_pair));
 
         let mut config = self.config.lock();
 
         match key_pair {
-            SecureKeyStoreKey::Sign(k) => config.save_sign_key_pair(k),
-            SecureKeyStoreKey::Update(k) => config.save_update_key_pair(k),
-            SecureKeyStoreKey::Recovery(k) => config.save_recovery_key_pair(k),
-            SecureKeyStoreKey::Encrypt(k) => config.save_encrypt_key_pair(k),
+            SecureKeyStoreKey::Sign(k) => config.save_sign_key_pair(k)?,
+            SecureKeyStoreKey::Update(k) => config.save_update_key_pair(k)?,
+            SecureKeyStoreKey::Recovery(k) => config.save_recovery_key_pair(k)?,
+            SecureKeyStoreKey::Encrypt(k) => config.save_encrypt_key_pair(k)?,
+            SecureKeyStoreKey::WebvhUpdate(k) => config.save_webvh_update_key_pair(k)?,
         };
+        Ok(())
+    }
+
+    fn write_keyring(&self, keyring: &KeyPairing) -> Result<(), SecureKeyStoreError> {
+        log::info!("Called: write_keyring");
+        Ok(self.config.lock().save_keyring(keyring)?)
     }
 
-    fn read_sign(&self) -> Option<K256KeyPair> {
+    fn read_sign(&self) -> Result<Option<SignKeyPair>, SecureKeyStoreError> {
         log::debug!("Called: read_internal (type: sign)");
         let config = self.config.lock();
-        config.load_sign_key_pair()
+        Ok(config.load_sign_key_pair()?)
     }
-    fn read_update(&self) -> Option<K256KeyPair> {
+    fn read_update(&self) -> Result<Option<K256KeyPair>, SecureKeyStoreError> {
         log::debug!("Called: read_internal (type: update)");
         let config = self.config.lock();
-        config.load_update_key_pair()
+        Ok(config.load_update_key_pair()?)
     }
-    fn read_recovery(&self) -> Option<K256KeyPair> {
+    fn read_recovery(&self) -> Result<Option<K256KeyPair>, SecureKeyStoreError> {
         log::debug!("Called: read_internal (type: recovery)");
         let config = self.config.lock();
-        config.load_recovery_key_pair()
+        Ok(config.load_recovery_key_pair()?)
     }
-    fn read_encrypt(&self) -> Option<X25519KeyPair> {
+    fn read_encrypt(&self) -> Result<Option<X25519KeyPair>, SecureKeyStoreError> {
         log::debug!("Called: read_internal (type: encrypt)");
         let config = self.config.lock();
-        config.load_encrypt_key_pair()
+        Ok(config.load_encrypt_key_pair()?)
+    }
+    fn read_webvh_update(&self) -> Result<Option<Ed25519KeyPair>, SecureKeyStoreError> {
+        log::debug!("Called: read_internal (type: webvh_update)");
+        let config = self.config.lock();
+        Ok(config.load_webvh_update_key_pair()?)
+    }
+
+    fn write_pending_keyring(
+        &self,
+        keyring: Option<&KeyPairing>,
+    ) -> Result<(), SecureKeyStoreError> {
+        log::info!("Called: write_pending_keyring");
+        Ok(self.config.lock().save_pending_keyring(keyring)?)
+    }
+    fn read_pending_keyring(&self) -> Result<Option<KeyPairing>, SecureKeyStoreError> {
+        log::debug!("Called: read_pending_keyring");
+        Ok(self.config.lock().load_pending_keyring()?)
+    }
+}
+
+// Secure keystore extension ABI.
+//
+// Key pairs are exchanged as the JSON used in config.json,
+// `{"public_key":"<hex>","secret_key":"<hex>"}`, and `key_type` is a SecureKeyStoreType.
+//
+// int32_t write(uint32_t key_type, const uint8_t *buf, size_t len);
+//   Stores `len` bytes of `buf`. Returns 0 on success.
+// int32_t read(uint32_t key_type, uint8_t *buf, size_t len, size_t *written);
+//   Copies the stored key pair into `buf` (at most `len` bytes) and sets `*written`.
+//   Returns 0 on success, 1 when no key pair is stored for `key_type`,
+//   2 when the key pair is longer than `len`, with its length set to `*written`.
+//
+// `key_type` 5 is the Ed25519 update key pair of a did:webvh DID.
+// `key_type` 4 is the pending keyring, `{"sign":{..},"update":{..},"recovery":{..},"encrypt":{..}}`,
+// and a write of 0 bytes clears it. Extensions without this slot return an error for it,
+// which refuses key rotations rather than keeping the new key pairs out of the keystore.
+type ExtensionWriteFn = unsafe extern "C" fn(key_type: u32, buf: *const u8, len: usize) -> i32;
+type ExtensionReadFn =
+    unsafe extern "C" fn(key_type: u32, buf: *mut u8, len: usize, written: *mut usize) -> i32;
+
+const EXTENSION_NOT_FOUND: i32 = 1;
+const EXTENSION_BUFFER_TOO_SMALL: i32 = 2;
+const EXTENSION_READ_BUFFER_SIZE: usize = 1024;
+const EXTENSION_READ_BUFFER_MAX_SIZE: usize = 64 * 1024;
+
+#[derive(Clone)]
+pub struct ExtensionKeyStore {
+    write: ExtensionWriteFn,
+    read: ExtensionReadFn,
+    // NOTE: `write` and `read` point into these libraries, so keep them loaded.
+    _libraries: Arc<Vec<Library>>,
+}
+
+impl ExtensionKeyStore {
+    pub fn load(write: &Extension, read: &Extension) -> Result<Self, SecureKeyStoreError> {
+        // SAFETY: The extensions are trusted configuration and must export the symbols with the ABI above.
+        unsafe {
+            let write_library = Library::new(&write.filename)?;
+            let write = *write_library.get::<ExtensionWriteFn>(write.symbol.as_bytes())?;
+            let read_library = Library::new(&read.filename)?;
+            let read = *read_library.get::<ExtensionReadFn>(read.symbol.as_bytes())?;
+            Ok(ExtensionKeyStore {
+                write,
+                read,
+                _libraries: Arc::new(vec![write_library, read_library]),
+            })
+        }
+    }
+
+    fn read_internal<T>(
+        &self,
+        key_type: SecureKeyStoreType,
+        decode: impl Fn(&[u8]) -> Result<T, SecureKeyStoreError>,
+    ) -> Result<Option<T>, SecureKeyStoreError> {
+        log::debug!("Called: read_internal (type: {:?})", key_type);
+        let mut len = EXTENSION_READ_BUFFER_SIZE;
+        loop {
+            let mut buf = vec![0u8; len];
+            let mut written = 0usize;
+            // SAFETY: `buf` is a valid writable buffer of `buf.len()` bytes.
+            let code =
+                unsafe { (self.read)(key_type as u32, buf.as_mut_ptr(), buf.len(), &mut written) };
+            // NOTE: Never decode a clamped key pair, which may look valid but be truncated.
+            let result = match code {
+                // NOTE: A cleared slot reads as empty.
+                0 if written == 0 => Ok(None),
+                0 if written <= len => decode(&buf[..written]).map(Some),
+                0 => Err(SecureKeyStoreError::InvalidLength(written, len)),
+                EXTENSION_NOT_FOUND => Ok(None),
+                // NOTE: Retried with the length the extension asks for, up to a limit.
+                EXTENSION_BUFFER_TOO_SMALL
+                    if len < written && written <= EXTENSION_READ_BUFFER_MAX_SIZE =>
+                {
+                    buf.zeroize();
+                    len = written;
+                    continue;
+                }
+                EXTENSION_BUFFER_TOO_SMALL => Err(SecureKeyStoreError::InvalidLength(written, len)),
+                code => Err(SecureKeyStoreError::Extension(code)),
+            };
+            buf.zeroize();
+            return result;
+        }
+    }
+}
+
+fn decode_key_pair<U, V, T: KeyPair<U, V>>(buf: &[u8]) -> Result<T, SecureKeyStoreError>
+where
+    SecureKeyStoreError: From<T::Error>,
+{
+    let hex: KeyPairHex = serde_json::from_slice(buf)?;
+    Ok(T::from_hex_key_pair(&hex)?)
+}
+
+#[derive(Serialize, Deserialize)]
+struct KeyPairingHex {
+    sign: KeyPairHex,
+    update: KeyPairHex,
+    recovery: KeyPairHex,
+    encrypt: KeyPairHex,
+}
+
+fn decode_keyring(buf: &[u8]) -> Result<KeyPairing, SecureKeyStoreError> {
+    let hex: KeyPairingHex = serde_json::from_slice(buf)?;
+    Ok(KeyPairing {
+        sign: SignKeyPair::from_hex_key_pair(&hex.sign)?,
+        update: K256KeyPair::from_hex_key_pair(&hex.update)?,
+        recovery: K256KeyPair::from_hex_key_pair(&hex.recovery)?,
+        encrypt: X25519KeyPair::from_hex_key_pair(&hex.encrypt)?,
+    })
+}
+
+impl ExtensionKeyStore {
+    fn write_internal(
+        &self,
+        key_type: SecureKeyStoreType,
+        mut buf: Vec<u8>,
+    ) -> Result<(), SecureKeyStoreError> {
+        log::info!("Called: write_internal (type: {:?})", key_type);
+        // SAFETY: `buf` is a valid readable buffer of `buf.len()` bytes.
+        let code = unsafe { (self.write)(key_type as u32, buf.as_ptr(), buf.len()) };
+        buf.zeroize();
+        match code {
+            0 => Ok(()),
+            code => Err(SecureKeyStoreError::Extension(code)),
+        }
+    }
+}
+
+impl SecureKeyStore for ExtensionKeyStore {
+    fn write(&self, key_pair: &SecureKeyStoreKey) -> Result<(), SecureKeyStoreError> {
+        self.write_internal(k2t(key_pair), serde_json::to_vec(&k2hex(key_pair))?)
+    }
+
+    fn read_sign(&self) -> Result<Option<SignKeyPair>, SecureKeyStoreError> {
+        self.read_internal(SecureKeyStoreType::Sign, decode_key_pair)
+    }
+    fn read_update(&self) -> Result<Option<K256KeyPair>, SecureKeyStoreError> {
+        self.read_internal(SecureKeyStoreType::Update, decode_key_pair)
+    }
+    fn read_recovery(&self) -> Result<Option<K256KeyPair>, SecureKeyStoreError> {
+        self.read_internal(SecureKeyStoreType::Recovery, decode_key_pair)
+    }
+    fn read_encrypt(&self) -> Result<Option<X25519KeyPair>, SecureKeyStoreError> {
+        self.read_internal(SecureKeyStoreType::Encrypt, decode_key_pair)
+    }
+    fn read_webvh_update(&self) -> Result<Option<Ed25519KeyPair>, SecureKeyStoreError> {
+        self.read_internal(SecureKeyStoreType::WebvhUpdate, decode_key_pair)
+    }
+
+    fn write_pending_keyring(
+        &self,
+        keyring: Option<&KeyPairing>,
+    ) -> Result<(), SecureKeyStoreError> {
+        let buf = match keyring {
+            Some(keyring) => serde_json::to_vec(&KeyPairingHex {
+                sign: keyring.sign.to_hex_key_pair(),
+                update: keyring.update.to_hex_key_pair(),
+                recovery: keyring.recovery.to_hex_key_pair(),
+                encrypt: keyring.encrypt.to_hex_key_pair(),
+            })?,
+            None => vec![],
+        };
+        self.write_internal(SecureKeyStoreType::Pending, buf)
+    }
+    fn read_pending_keyring(&self) -> Result<Option<KeyPairing>, SecureKeyStoreError> {
+        self.read_internal(SecureKeyStoreType::Pending, decode_keyring)
+    }
+}
+
+// Uses the secure keystore extension when it is configured, otherwise config.json.
+#[derive(Clone)]
+pub enum ConfiguredKeyStore {
+    File(FileBaseKeyStore),
+    Extension(ExtensionKeyStore),
+}
+
+impl ConfiguredKeyStore {
+    pub fn new(config: Box<SingletonAppConfig>) -> Result<Self, SecureKeyStoreError> {
+        let extension = {
+            let config = config.lock();
+            config
+                .load_secure_keystore_write_sig()
+                .zip(config.load_secure_keystore_read_sig())
+        };
+        match extension {
+            Some((write, read)) => Ok(ConfiguredKeyStore::Extension(ExtensionKeyStore::load(
+                &write, &read,
+            )?)),
+            None => Ok(ConfiguredKeyStore::File(FileBaseKeyStore::new(config))),
+        }
+    }
+}
+
+impl SecureKeyStore for ConfiguredKeyStore {
+    fn write(&self, key_pair: &SecureKeyStoreKey) -> Result<(), SecureKeyStoreError> {
+        match self {
+            ConfiguredKeyStore::File(k) => k.write(key_pair),
+            ConfiguredKeyStore::Extension(k) => k.write(key_pair),
+        }
+    }
+    fn write_keyring(&self, keyring: &KeyPairing) -> Result<(), SecureKeyStoreError> {
+        match self {
+            ConfiguredKeyStore::File(k) => k.write_keyring(keyring),
+            ConfiguredKeyStore::Extension(k) => k.write_keyring(keyring),
+        }
+    }
+    fn read_sign(&self) -> Result<Option<SignKeyPair>, SecureKeyStoreError> {
+        match self {
+            ConfiguredKeyStore::File(k) => k.read_sign(),
+            ConfiguredKeyStore::Extension(k) => k.read_sign(),
+        }
+    }
+    fn read_update(&self) -> Result<Option<K256KeyPair>, SecureKeyStoreError> {
+        match self {
+            ConfiguredKeyStore::File(k) => k.read_update(),
+            ConfiguredKeyStore::Extension(k) => k.read_update(),
+        }
+    }
+    fn read_recovery(&self) -> Result<Option<K256KeyPair>, SecureKeyStoreError> {
+        match self {
+            ConfiguredKeyStore::File(k) => k.read_recovery(),
+            ConfiguredKeyStore::Extension(k) => k.read_recovery(),
+        }
+    }
+    fn read_encrypt(&self) -> Result<Option<X25519KeyPair>, SecureKeyStoreError> {
+        match self {
+            ConfiguredKeyStore::File(k) => k.read_encrypt(),
+            ConfiguredKeyStore::Extension(k) => k.read_encrypt(),
+        }
+    }
+    fn read_webvh_update(&self) -> Result<Option<Ed25519KeyPair>, SecureKeyStoreError> {
+        match self {
+            ConfiguredKeyStore::File(k) => k.read_webvh_update(),
+            ConfiguredKeyStore::Extension(k) => k.read_webvh_update(),
+        }
+    }
+    fn write_pending_keyring(
+        &self,
+        keyring: Option<&KeyPairing>,
+    ) -> Result<(), SecureKeyStoreError> {
+        match self {
+            ConfiguredKeyStore::File(k) => k.write_pending_keyring(keyring),
+            ConfiguredKeyStore::Extension(k) => k.write_pending_keyring(keyring),
+        }
+    }
+    fn read_pending_keyring(&self) -> Result<Option<KeyPairing>, SecureKeyStoreError> {
+        match self {
+            ConfiguredKeyStore::File(k) => k.read_pending_keyring(),
+            ConfiguredKeyStore::Extension(k) => k.read_pending_keyring(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::{Cell, RefCell};
+    use std::collections::BTreeMap;
+
+    use protocol::rand_core::{OsRng, RngCore};
+
+    use super::*;
+
+    thread_local! {
+        static STORED: RefCell<BTreeMap<u32, Vec<u8>>> = const { RefCell::new(BTreeMap::new()) };
+        static FAIL_ON: Cell<Option<u32>> = const { Cell::new(None) };
+    }
+
+    unsafe extern "C" fn write(key_type: u32, buf: *const u8, len: usize) -> i32 {
+        if FAIL_ON.get() == Some(key_type) {
+            return -1;
+        }
+        let buf = std::slice::from_raw_parts(buf, len).to_vec();
+        STORED.with_borrow_mut(|stored| stored.insert(key_type, buf));
+        0
+    }
+
+    unsafe extern "C" fn read(key_type: u32, buf: *mut u8, len: usize, written: *mut usize) -> i32 {
+        STORED.with_borrow(|stored| match stored.get(&key_type) {
+            Some(v) if v.len() <= len => {
+                std::ptr::copy_nonoverlapping(v.as_ptr(), buf, v.len());
+                *written = v.len();
+                0
+            }
+            Some(v) => {
+                *written = v.len();
+                EXTENSION_BUFFER_TOO_SMALL
+            }
+            None => EXTENSION_NOT_FOUND,
+        })
+    }
+
+    // NOTE: Claims to have written more than the buffer holds.
+    unsafe extern "C" fn read_overflow(
+        _key_type: u32,
+        _buf: *mut u8,
+        len: usize,
+        written: *mut usize,
+    ) -> i32 {
+        *written = len + 1;
+        0
+    }
+
+    // NOTE: The test extension keeps the key pairs per thread, so each test starts empty.
+    fn extension_keystore() -> ExtensionKeyStore {
+        ExtensionKeyStore {
+            write,
+            read,
+            _libraries: Arc::new(vec![]),
+        }
+    }
+
+    fn hex<U, V, T: KeyPair<U, V>>(key_pair: Option<T>) -> Option<String> {
+        key_pair.map(|v| serde_json::to_string(&v.to_hex_key_pair()).unwrap())
+    }
+
+    fn assert_keyring(keystore: &impl SecureKeyStore, keyring: &KeyPairing) {
+        assert_eq!(
+            hex(keystore.read_sign().unwrap()),
+            hex(Some(keyring.sign.clone()))
+        );
+        assert_eq!(
+            hex(keystore.read_update().unwrap()),
+            hex(Some(keyring.update.clone()))
+        );
+        assert_eq!(
+            hex(keystore.read_recovery().unwrap()),
+            hex(Some(keyring.recovery.clone()))
+        );
+        assert_eq!(
+            hex(keystore.read_encrypt().unwrap()),
+            hex(Some(keyring.encrypt.clone()))
+        );
+    }
+
+    #[test]
+    fn test_extension_keystore() {
+        let keystore = extension_keystore();
+        assert!(keystore.read_sign().unwrap().is_none());
+
+        let keyring = KeyPairing::create_keyring(OsRng);
+        keystore.write_keyring(&keyring).unwrap();
+        assert_keyring(&keystore, &keyring);
+
+        let update = K256KeyPair::new(protocol::k256::SecretKey::random(&mut OsRng));
+        keystore.write(&SecureKeyStoreKey::Update(&update)).unwrap();
+        assert_eq!(hex(keystore.read_update().unwrap()), hex(Some(update)));
+
+        // NOTE: The did:webvh update key pair is stored apart from the Sidetree one.
+        let webvh_update =
+            Ed25519KeyPair::new(protocol::ed25519_dalek::SigningKey::generate(&mut OsRng));
+        keystore
+            .write(&SecureKeyStoreKey::WebvhUpdate(&webvh_update))
+            .unwrap();
+        assert_eq!(
+            hex(keystore.read_webvh_update().unwrap()),
+            hex(Some(webvh_update))
+        );
+        assert_keyring(
+            &keystore,
+            &KeyPairing {
+                update: keystore.read_update().unwrap().unwrap(),
+                ..keyring
+            },
+        );
+    }
+
+    #[test]
+    fn test_extension_keystore_pending_keyring() {
+        let keystore = extension_keystore();
+        assert!(keystore.read_pending_keyring().unwrap().is_none());
+
+        let keyring = KeyPairing::create_keyring(OsRng);
+        keystore.write_keyring(&keyring).unwrap();
+        let pending = KeyPairing::create_keyring(OsRng);
+        keystore.write_pending_keyring(Some(&pending)).unwrap();
+        let stored = keystore.read_pending_keyring().unwrap().unwrap();
+        assert_eq!(hex(Some(stored.update)), hex(Some(pending.update.clone())));
+        assert_eq!(hex(Some(stored.sign)), hex(Some(pending.sign.clone())));
+        // NOTE: The key pairs in use are kept until the pending keyring is promoted.
+        assert_keyring(&keystore, &keyring);
+
+        keystore.write_pending_keyring(None).unwrap();
+        assert!(keystore.read_pending_keyring().unwrap().is_none());
+
+        // NOTE: An extension without the pending slot refuses to stage the keyring.
+        FAIL_ON.set(Some(SecureKeyStoreType::Pending as u32));
+        let result = keystore.write_pending_keyring(Some(&pending));
+        FAIL_ON.set(None);
+        assert!(matches!(result, Err(SecureKeyStoreError::Extension(-1))));
+    }
+
+    #[test]
+    fn test_extension_keystore_read_long_key_pair() {
+        let keystore = extension_keystore();
+        let keyring = KeyPairing::create_keyring(OsRng);
+        let mut json = serde_json::to_vec(&keyring.update.to_hex_key_pair()).unwrap();
+        json.resize(EXTENSION_READ_BUFFER_SIZE * 2, b' ');
+        STORED.with_borrow_mut(|stored| stored.insert(SecureKeyStoreType::Update as u32, json));
+        assert_eq!(
+            hex(keystore.read_update().unwrap()),
+            hex(Some(keyring.update))
+        );
+
+        let json = vec![b' '; EXTENSION_READ_BUFFER_MAX_SIZE + 1];
+        STORED.with_borrow_mut(|stored| stored.insert(SecureKeyStoreType::Update as u32, json));
+        assert!(matches!(
+            keystore.read_update(),
+            Err(SecureKeyStoreError::InvalidLength(_, _))
+        ));
+
+        let keystore = ExtensionKeyStore {
+            read: read_overflow,
+            ..extension_keystore()
+        };
+        assert!(matches!(
+            keystore.read_sign(),
+            Err(SecureKeyStoreError::InvalidLength(written, len)) if written == len + 1
+        ));
+    }
+
+    #[test]
+    fn test_extension_keystore_write_keyring_failed() {
+        let keystore = extension_keystore();
+        let keyring = KeyPairing::create_keyring(OsRng);
+        keystore.write_keyring(&keyring).unwrap();
+
+        FAIL_ON.set(Some(SecureKeyStoreType::Recovery as u32));
+        let result = keystore.write_keyring(&KeyPairing::create_keyring(OsRng));
+        FAIL_ON.set(None);
+        assert!(matches!(result, Err(SecureKeyStoreError::Extension(-1))));
+        // NOTE: The key pairs written before the failure are restored.
+        assert_keyring(&keystore, &keyring);
+    }
+
+    #[test]
+    fn test_configured_keystore() {
+        let dir = std::env::temp_dir().join(format!("nodex-keystore-{}", OsRng.next_u64()));
+        let config = SingletonAppConfig::with_file(&dir.join("config.json"));
+        // NOTE: Without the extension in config.json, the key pairs are stored in it.
+        let keystore = ConfiguredKeyStore::new(Box::new(config)).unwrap();
+        assert!(matches!(keystore, ConfiguredKeyStore::File(_)));
+        let keyring = KeyPairing::create_keyring(OsRng);
+        keystore.write_keyring(&keyring).unwrap();
+        assert_keyring(&keystore, &keyring);
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        let keystore = ConfiguredKeyStore::Extension(extension_keystore());
+        let keyring = KeyPairing::create_keyring(OsRng);
+        keystore.write_keyring(&keyring).unwrap();
+        assert_keyring(&keystore, &keyring);
     }
 }
@@ -0,0 +1,309 @@
+use std::{fs, io, path::Path};
+
+use argon2::Argon2;
+use chacha20poly1305::{
+    aead::{Aead, KeyInit, Payload},
+    XChaCha20Poly1305, XNonce,
+};
+use protocol::did::file_store::write_atomically;
+use protocol::keyring::keypair::KeyPairHex;
+use protocol::rand_core::{OsRng, RngCore};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use zeroize::{Zeroize, ZeroizeOnDrop, Zeroizing};
+
+use crate::nodex::extension::cipher::{CipherError, CipherExtension};
+
+pub const PASSPHRASE_ENV: &str = "NODEX_KEYSTORE_PASSPHRASE";
+const MACHINE_SECRET_FILE: &str = "keystore.secret";
+
+const KEK_LENGTH: usize = 32;
+const SALT_LENGTH: usize = 16;
+const NONCE_LENGTH: usize = 24;
+
+// How the key-encryption key (KEK) wrapping the secret keys in config.json is obtained.
+// NOTE: Plaintext unless another mode is configured, since each mode needs a secret
+//       kept apart from config.json to protect anything.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum KeyEncryptionConfig {
+    #[default]
+    Plaintext,
+    // Argon2id over the passphrase in NODEX_KEYSTORE_PASSPHRASE.
+    Passphrase {
+        salt: Option<String>,
+    },
+    // Random secret in a file only readable by the agent user, keystore.secret next to
+    // config.json unless `path` is set. It only keeps the secret keys out of config.json
+    // and gives no protection against anyone who can read the config directory.
+    // Set `path` to another directory, or use another mode, if that matters.
+    MachineSecret {
+        path: Option<String>,
+    },
+    // Random KEK stored wrapped by the cipher extension.
+    CipherExtension {
+        wrapped_key: Option<String>,
+    },
+}
+
+#[derive(Debug, Error)]
+pub enum KeyEncryptionError {
+    #[error("{PASSPHRASE_ENV} is not set")]
+    PassphraseNotSet,
+    #[error("cipher extension is not configured")]
+    CipherExtensionNotConfigured,
+    #[error("invalid key length: {0}")]
+    InvalidKeyLength(usize),
+    #[error("failed to derive key: {0}")]
+    Argon2(String),
+    #[error("failed to encrypt or decrypt key pair")]
+    Crypt,
+    #[error("io error: {0}")]
+    Io(#[from] io::Error),
+    #[error("from hex error: {0}")]
+    FromHex(#[from] hex::FromHexError),
+    #[error("json error: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("cipher extension error: {0}")]
+    Cipher(#[from] CipherError),
+}
+
+#[derive(Clone, Deserialize, Serialize)]
+pub struct EncryptedKeyPairHex {
+    nonce: String,
+    ciphertext: String,
+}
+
+// NOTE: A key pair in config.json is either encrypted or, for configs written
+//       before schema_version 2 or in plaintext mode, the bare KeyPairHex.
+#[derive(Clone, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum StoredKeyPair {
+    Encrypted(EncryptedKeyPairHex),
+    Plaintext(KeyPairHex),
+}
+
+#[derive(Zeroize, ZeroizeOnDrop)]
+pub struct KeyEncryptionKey([u8; KEK_LENGTH]);
+
+impl KeyEncryptionKey {
+    pub fn generate() -> Self {
+        let mut key = [0u8; KEK_LENGTH];
+        OsRng.fill_bytes(&mut key);
+        KeyEncryptionKey(key)
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, KeyEncryptionError> {
+        let key: [u8; KEK_LENGTH] = bytes
+            .try_into()
+            .map_err(|_| KeyEncryptionError::InvalidKeyLength(bytes.len()))?;
+        Ok(KeyEncryptionKey(key))
+    }
+
+    pub fn from_passphrase(passphrase: &[u8], salt: &[u8]) -> Result<Self, KeyEncryptionError> {
+        let mut key = [0u8; KEK_LENGTH];
+        Argon2::default()
+            .hash_password_into(passphrase, salt, &mut key)
+            .map_err(|e| KeyEncryptionError::Argon2(e.to_string()))?;
+        Ok(KeyEncryptionKey(key))
+    }
+
+    // `label` is bound as associated data, so an entry can not be swapped into another slot.
+    pub fn encrypt(
+        &self,
+        label: &str,
+        key_pair: &KeyPairHex,
+    ) -> Result<EncryptedKeyPairHex, KeyEncryptionError> {
+        let plaintext = Zeroizing::new(serde_json::to_vec(key_pair)?);
+        let mut nonce = [0u8; NONCE_LENGTH];
+        OsRng.fill_bytes(&mut nonce);
+        let ciphertext = XChaCha20Poly1305::new(&self.0.into())
+            .encrypt(
+                XNonce::from_slice(&nonce),
+                Payload {
+                    msg: &plaintext,
+                    aad: label.as_bytes(),
+                },
+            )
+            .map_err(|_| KeyEncryptionError::Crypt)?;
+        Ok(EncryptedKeyPairHex {
+            nonce: hex::encode(nonce),
+            ciphertext: hex::encode(ciphertext),
+        })
+    }
+
+    pub fn decrypt(
+        &self,
+        label: &str,
+        encrypted: &EncryptedKeyPairHex,
+    ) -> Result<KeyPairHex, KeyEncryptionError> {
+        let nonce = hex::decode(&encrypted.nonce)?;
+        if nonce.len() != NONCE_LENGTH {
+            return Err(KeyEncryptionError::Crypt);
+        }
+        let ciphertext = hex::decode(&encrypted.ciphertext)?;
+        let plaintext = XChaCha20Poly1305::new(&self.0.into())
+            .decrypt(
+                XNonce::from_slice(&nonce),
+                Payload {
+                    msg: &ciphertext,
+                    aad: label.as_bytes(),
+                },
+            )
+            .map_err(|_| KeyEncryptionError::Crypt)?;
+        let plaintext = Zeroizing::new(plaintext);
+        Ok(serde_json::from_slice(&plaintext)?)
+    }
+}
+
+fn load_or_create_machine_secret(path: &Path) -> Result<KeyEncryptionKey, KeyEncryptionError> {
+    if path.exists() {
+        let secret = Zeroizing::new(fs::read(path)?);
+        return KeyEncryptionKey::from_bytes(&secret);
+    }
+    // NOTE: Written and synced before config.json holds any key pair encrypted with it,
+    //       so that a crash never leaves config.json with a missing or empty secret.
+    let key = KeyEncryptionKey::generate();
+    write_atomically(path, &key.0)?;
+    Ok(key)
+}
+
+// Resolves the KEK, filling in the salt or wrapped key on first use.
+// Returns None in plaintext mode.
+pub fn resolve_key_encryption_key(
+    config: &mut KeyEncryptionConfig,
+    config_dir: &Path,
+    cipher: Option<CipherExtension>,
+) -> Result<Option<KeyEncryptionKey>, KeyEncryptionError> {
+    match config {
+        KeyEncryptionConfig::Plaintext => Ok(None),
+        KeyEncryptionConfig::Passphrase { salt } => {
+            let passphrase = Zeroizing::new(
+                std::env::var(PASSPHRASE_ENV).map_err(|_| KeyEncryptionError::PassphraseNotSet)?,
+            );
+            let salt = match salt {
+                Some(salt) => hex::decode(salt)?,
+                None => {
+                    let mut value = vec![0u8; SALT_LENGTH];
+                    OsRng.fill_bytes(&mut value);
+                    *salt = Some(hex::encode(&value));
+                    value
+                }
+            };
+            Ok(Some(KeyEncryptionKey::from_passphrase(
+                passphrase.as_bytes(),
+                &salt,
+            )?))
+        }
+        KeyEncryptionConfig::MachineSecret { path } => {
+            let path = path
+                .as_ref()
+                .map(|v| Path::new(v).to_path_buf())
+                .unwrap_or_else(|| config_dir.join(MACHINE_SECRET_FILE));
+            Ok(Some(load_or_create_machine_secret(&path)?))
+        }
+        KeyEncryptionConfig::CipherExtension { wrapped_key } => {
+            let cipher = cipher.ok_or(KeyEncryptionError::CipherExtensionNotConfigured)?;
+            match wrapped_key {
+                Some(wrapped) => {
+                    let key = Zeroizing::new(cipher.decrypt(&hex::decode(wrapped)?)?);
+                    Ok(Some(KeyEncryptionKey::from_bytes(&key)?))
+                }
+                None => {
+                    let key = KeyEncryptionKey::generate();
+                    *wrapped_key = Some(hex::encode(cipher.encrypt(&key.0)?));
+                    Ok(Some(key))
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use protocol::keyring::keypair::{K256KeyPair, KeyPair};
+
+    use super::*;
+
+    fn key_pair() -> K256KeyPair {
+        K256KeyPair::new(protocol::k256::SecretKey::random(&mut OsRng))
+    }
+
+    #[test]
+    fn test_encrypt_and_decrypt() {
+        let kek = KeyEncryptionKey::generate();
+        let key_pair = key_pair();
+
+        let encrypted = kek.encrypt("sign", &key_pair.to_hex_key_pair()).unwrap();
+        let decrypted = kek.decrypt("sign", &encrypted).unwrap();
+        let decrypted = K256KeyPair::from_hex_key_pair(&decrypted).unwrap();
+        assert_eq!(decrypted.get_public_key(), key_pair.get_public_key());
+
+        assert!(kek.decrypt("update", &encrypted).is_err());
+        assert!(KeyEncryptionKey::generate()
+            .decrypt("sign", &encrypted)
+            .is_err());
+    }
+
+    #[test]
+    fn test_passphrase_is_deterministic() {
+        let salt = [0u8; SALT_LENGTH];
+        let kek = KeyEncryptionKey::from_passphrase(b"passphrase", &salt).unwrap();
+        let encrypted = kek.encrypt("sign", &key_pair().to_hex_key_pair()).unwrap();
+
+        let kek = KeyEncryptionKey::from_passphrase(b"passphrase", &salt).unwrap();
+        assert!(kek.decrypt("sign", &encrypted).is_ok());
+        let kek = KeyEncryptionKey::from_passphrase(b"wrong", &salt).unwrap();
+        assert!(kek.decrypt("sign", &encrypted).is_err());
+    }
+
+    #[test]
+    fn test_stored_key_pair_accepts_plaintext() {
+        let hex = serde_json::to_string(&key_pair().to_hex_key_pair()).unwrap();
+        let stored: StoredKeyPair = serde_json::from_str(&hex).unwrap();
+        assert!(matches!(stored, StoredKeyPair::Plaintext(_)));
+
+        let kek = KeyEncryptionKey::generate();
+        let encrypted = kek.encrypt("sign", &key_pair().to_hex_key_pair()).unwrap();
+        let json = serde_json::to_string(&StoredKeyPair::Encrypted(encrypted)).unwrap();
+        let stored: StoredKeyPair = serde_json::from_str(&json).unwrap();
+        assert!(matches!(stored, StoredKeyPair::Encrypted(_)));
+    }
+
+    #[test]
+    fn test_plaintext_is_default() {
+        let mut config = KeyEncryptionConfig::default();
+        let dir = std::env::temp_dir().join(format!("nodex-kek-{}", OsRng.next_u64()));
+        assert!(resolve_key_encryption_key(&mut config, &dir, None)
+            .unwrap()
+            .is_none());
+        assert!(!dir.exists());
+    }
+
+    #[test]
+    fn test_machine_secret_is_reused() {
+        let dir = std::env::temp_dir().join(format!("nodex-kek-{}", OsRng.next_u64()));
+        fs::create_dir_all(&dir).unwrap();
+        let mut config = KeyEncryptionConfig::MachineSecret { path: None };
+
+        let first = resolve_key_encryption_key(&mut config, &dir, None)
+            .unwrap()
+            .unwrap();
+        let second = resolve_key_encryption_key(&mut config, &dir, None)
+            .unwrap()
+            .unwrap();
+        assert_eq!(first.0, second.0);
+        // NOTE: Only the secret itself is left, readable by the owner only.
+        let path = dir.join(MACHINE_SECRET_FILE);
+        assert_eq!(fs::read(&path).unwrap(), first.0);
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 1);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,14 +1,17 @@
 use crate::{
-    config::SingletonAppConfig,
-    nodex::extension::secure_keystore::{SecureKeyStore, SecureKeyStoreKey},
+    config::{AppConfigError, SingletonAppConfig, TRNGFallbackPolicy},
+    nodex::extension::secure_keystore::{SecureKeyStore, SecureKeyStoreError, SecureKeyStoreKey},
 };
-use protocol::keyring::keypair::{K256KeyPair, X25519KeyPair};
-use protocol::rand_core::OsRng;
+use protocol::keyring::extension::trng::{Trng, TrngError};
+use protocol::keyring::keypair::{
+    Ed25519KeyPair, K256KeyPair, KeyPair, KeyPairing, SignKeyPair, X25519KeyPair,
+};
+use protocol::rand_core::{CryptoRng, OsRng, RngCore};
 
 use thiserror::Error;
 
 pub struct KeyPairingWithConfig<S: SecureKeyStore> {
-    sign: K256KeyPair,
+    sign: SignKeyPair,
     update: K256KeyPair,
     recovery: K256KeyPair,
     encrypt: X25519KeyPair,
@@ -24,6 +27,60 @@ pub enum KeyPairingError {
     KeyNotFound,
     #[error("DID not found")]
     DIDNotFound,
+    #[error("TRNG extension failed: {0}")]
+    Trng(#[from] TrngError),
+    #[error("secure keystore failed: {0}")]
+    SecureKeyStore(#[from] SecureKeyStoreError),
+    #[error("failed to write config: {0}")]
+    Config(#[from] AppConfigError<protocol::keyring::keypair::KeyPairingError>),
+}
+
+fn load_trng(config: &SingletonAppConfig) -> Result<Option<Trng>, KeyPairingError> {
+    let (extension, fallback) = {
+        let config = config.lock();
+        (
+            config.load_trng_read_sig(),
+            config.load_trng_fallback_policy(),
+        )
+    };
+    let Some(extension) = extension else {
+        return Ok(None);
+    };
+    let trng = Trng::load(&extension.into()).and_then(|mut trng| {
+        trng.health_test()?;
+        Ok(trng)
+    });
+    match (trng, fallback) {
+        (Ok(trng), TRNGFallbackPolicy::OsRng) => Ok(Some(trng.with_os_rng_fallback())),
+        (Ok(trng), TRNGFallbackPolicy::Deny) => Ok(Some(trng)),
+        (Err(e), TRNGFallbackPolicy::OsRng) => {
+            log::warn!("TRNG extension is unavailable, fallback to OsRng: {}", e);
+            Ok(None)
+        }
+        (Err(e), TRNGFallbackPolicy::Deny) => Err(e.into()),
+    }
+}
+
+// Keeps the recovery key pair, which is only needed for Sidetree recover operations.
+fn rotate_keyring<T: RngCore + CryptoRng>(current: &KeyPairing, mut csprng: T) -> KeyPairing {
+    KeyPairing {
+        sign: SignKeyPair::new(current.sign.key_type(), &mut csprng),
+        update: K256KeyPair::new(protocol::k256::SecretKey::random(&mut csprng)),
+        recovery: current.recovery.clone(),
+        encrypt: X25519KeyPair::new(protocol::x25519_dalek::StaticSecret::random_from_rng(
+            &mut csprng,
+        )),
+    }
+}
+
+// Generates the key pairs of a pairwise did:peer DID, which are saved in config.json
+// rather than the secure keystore.
+pub fn create_peer_keyring(config: &SingletonAppConfig) -> Result<KeyPairing, KeyPairingError> {
+    let keyring = match load_trng(config)? {
+        Some(mut trng) => KeyPairing::create_keyring(trng.seed_rng()?),
+        None => KeyPairing::create_keyring(OsRng),
+    };
+    Ok(keyring)
 }
 
 impl<S: SecureKeyStore> KeyPairingWithConfig<S> {
@@ -32,16 +89,16 @@ impl<S: SecureKeyStore> KeyPairingWithConfig<S> {
         secure_keystore: S,
     ) -> Result<Self, KeyPairingError> {
         let sign = secure_keystore
-            .read_sign()
+            .read_sign()?
             .ok_or(KeyPairingError::KeyNotFound)?;
         let update = secure_keystore
-            .read_update()
+            .read_update()?
             .ok_or(KeyPairingError::KeyNotFound)?;
         let recovery = secure_keystore
-            .read_recovery()
+            .read_recovery()?
             .ok_or(KeyPairingError::KeyNotFound)?;
         let encrypt = secure_keystore
-            .read_encrypt()
+            .read_encrypt()?
             .ok_or(KeyPairingError::KeyNotFound)?;
 
         Ok(KeyPairingWithConfig {
@@ -54,18 +111,26 @@ impl<S: SecureKeyStore> KeyPairingWithConfig<S> {
         })
     }
 
-    pub fn create_keyring(config: Box<SingletonAppConfig>, secure_keystore: S) -> Self {
-        // TODO: extension trng support
-        let keyring = protocol::keyring::keypair::KeyPairing::create_keyring(OsRng);
+    pub fn create_keyring(
+        config: Box<SingletonAppConfig>,
+        secure_keystore: S,
+    ) -> Result<Self, KeyPairingError> {
+        let sign_key_type = config.lock().get_sign_key_type();
+        let keyring = match load_trng(&config)? {
+            Some(mut trng) => {
+                KeyPairing::create_keyring_with_sign_key_type(trng.seed_rng()?, sign_key_type)
+            }
+            None => KeyPairing::create_keyring_with_sign_key_type(OsRng, sign_key_type),
+        };
 
-        KeyPairingWithConfig {
+        Ok(KeyPairingWithConfig {
             sign: keyring.sign,
             update: keyring.update,
             recovery: keyring.recovery,
             encrypt: keyring.encrypt,
             config,
             secure_keystore,
-        }
+        })
     }
 
     pub fn get_keyring(&self) -> protocol::keyring::keypair::KeyPairing {
@@ -77,20 +142,95 @@ impl<S: SecureKeyStore> KeyPairingWithConfig<S> {
         }
     }
 
-    pub fn save(&mut self, did: &str) {
-        self.secure_keystore
-            .write(&SecureKeyStoreKey::Sign(&self.sign));
-        self.secure_keystore
-            .write(&SecureKeyStoreKey::Update(&self.update));
+    // Generates new sign, update and encrypt key pairs without saving them.
+    pub fn rotate_keyring(&self) -> Result<KeyPairing, KeyPairingError> {
+        let current = self.get_keyring();
+        let keyring = match load_trng(&self.config)? {
+            Some(mut trng) => rotate_keyring(&current, trng.seed_rng()?),
+            None => rotate_keyring(&current, OsRng),
+        };
+        Ok(keyring)
+    }
+
+    // Generates a new update key pair without saving it, for updates keeping the other keys.
+    pub fn rotate_update_key(&self) -> Result<KeyPairing, KeyPairingError> {
+        let update = match load_trng(&self.config)? {
+            Some(mut trng) => protocol::k256::SecretKey::random(&mut trng.seed_rng()?),
+            None => protocol::k256::SecretKey::random(&mut OsRng),
+        };
+        Ok(KeyPairing {
+            update: K256KeyPair::new(update),
+            ..self.get_keyring()
+        })
+    }
+
+    pub fn save_keyring(&mut self, keyring: KeyPairing) -> Result<(), KeyPairingError> {
+        self.secure_keystore.write_keyring(&keyring)?;
+        self.sign = keyring.sign;
+        self.update = keyring.update;
+        self.recovery = keyring.recovery;
+        self.encrypt = keyring.encrypt;
+        Ok(())
+    }
+
+    // The keyring submitted to an update of our own DID, which has not been promoted yet.
+    pub fn load_pending_keyring(&self) -> Result<Option<KeyPairing>, KeyPairingError> {
+        Ok(self.secure_keystore.read_pending_keyring()?)
+    }
+
+    // NOTE: Save a keyring before it is submitted, so that it is never lost
+    //       even if the agent stops before the update returns.
+    //       It fails, and so does the update, when the secure keystore cannot stage it.
+    pub fn save_pending_keyring(&self, keyring: &KeyPairing) -> Result<(), KeyPairingError> {
+        Ok(self.secure_keystore.write_pending_keyring(Some(keyring))?)
+    }
+
+    // Replaces the key pairs with the pending keyring, once its update is applied.
+    pub fn promote_pending_keyring(&mut self, keyring: KeyPairing) -> Result<(), KeyPairingError> {
+        self.save_keyring(keyring)?;
+        Ok(self.secure_keystore.write_pending_keyring(None)?)
+    }
+
+    pub fn get_update_key_pair(&self) -> &K256KeyPair {
+        &self.update
+    }
+
+    pub fn get_recovery_key_pair(&self) -> &K256KeyPair {
+        &self.recovery
+    }
+
+    // NOTE: did:webvh needs an Ed25519 update key, which is generated apart from
+    //       the Sidetree update key pair and stored in the secure keystore.
+    pub fn create_webvh_update_key_pair(&self) -> Result<Ed25519KeyPair, KeyPairingError> {
+        let secret_key = match load_trng(&self.config)? {
+            Some(mut trng) => protocol::ed25519_dalek::SigningKey::generate(&mut trng.seed_rng()?),
+            None => protocol::ed25519_dalek::SigningKey::generate(&mut OsRng),
+        };
+        let key_pair = Ed25519KeyPair::new(secret_key);
         self.secure_keystore
-            .write(&SecureKeyStoreKey::Recovery(&self.recovery));
+            .write(&SecureKeyStoreKey::WebvhUpdate(&key_pair))?;
+        Ok(key_pair)
+    }
+
+    pub fn get_webvh_update_key_pair(&self) -> Result<Ed25519KeyPair, KeyPairingError> {
         self.secure_keystore
-            .write(&SecureKeyStoreKey::Encrypt(&self.encrypt));
+            .read_webvh_update()?
+            .ok_or(KeyPairingError::KeyNotFound)
+    }
+
+    pub fn save(&mut self, did: &str) -> Result<(), KeyPairingError> {
+        self.secure_keystore.write_keyring(&self.get_keyring())?;
         {
             let mut config = self.config.lock();
             config.save_did(did);
             config.save_is_initialized(true);
         }
+        Ok(())
+    }
+
+    // NOTE: The key pairs are left as they are and get replaced when a new DID is created.
+    pub fn remove_identifier(&mut self) {
+        self.config.lock().remove_did();
     }
 
     pub fn get_identifier(&self) -> Result<String, KeyPairingError> {
@@ -100,3 +240,31 @@ impl<S: SecureKeyStore> KeyPairingWithConfig<S> {
             .ok_or(KeyPairingError::DIDNotFound)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use protocol::keyring::keypair::SignKeyType;
+
+    use super::*;
+
+    #[test]
+    fn test_rotate_keyring() {
+        let current = KeyPairing::create_keyring_with_sign_key_type(OsRng, SignKeyType::Secp256r1);
+        let rotated = rotate_keyring(&current, OsRng);
+
+        assert_eq!(rotated.sign.key_type(), SignKeyType::Secp256r1);
+        assert_ne!(rotated.sign.get_public_key(), current.sign.get_public_key());
+        assert_ne!(
+            rotated.update.get_public_key(),
+            current.update.get_public_key()
+        );
+        assert_ne!(
+            rotated.encrypt.get_public_key(),
+            current.encrypt.get_public_key()
+        );
+        assert_eq!(
+            rotated.recovery.get_public_key(),
+            current.recovery.get_public_key()
+        );
+    }
+}
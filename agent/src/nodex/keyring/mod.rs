@@ -1 +1,2 @@
+pub mod key_encryption;
 pub mod keypair;
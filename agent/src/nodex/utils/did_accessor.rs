@@ -1,8 +1,41 @@
+use std::sync::OnceLock;
+
 use protocol::keyring::keypair::KeyPairing;
+use thiserror::Error;
+
+use crate::nodex::{
+    extension::secure_keystore::{ConfiguredKeyStore, SecureKeyStoreError},
+    keyring::keypair::{KeyPairingError, KeyPairingWithConfig},
+};
+
+#[derive(Debug, Error)]
+pub enum DidAccessorError {
+    #[error("failed to load secure keystore: {0}")]
+    SecureKeyStore(#[from] SecureKeyStoreError),
+    #[error("failed to load keyring: {0}")]
+    KeyPairing(#[from] KeyPairingError),
+}
 
 pub trait DidAccessor {
     fn get_my_did(&self) -> String;
-    fn get_my_keyring(&self) -> KeyPairing;
+    fn get_my_keyring(&self) -> Result<KeyPairing, DidAccessorError>;
+    // The keyring of `did` if it is our own DID or one of our pairwise DIDs.
+    fn get_keyring_of(&self, did: &str) -> Result<Option<KeyPairing>, DidAccessorError> {
+        if did != self.get_my_did() {
+            return Ok(None);
+        }
+        self.get_my_keyring().map(Some)
+    }
+}
+
+// NOTE: Loading the keystore may dlopen the secure keystore extension, so do it once per process.
+fn keystore() -> Result<ConfiguredKeyStore, SecureKeyStoreError> {
+    static KEYSTORE: OnceLock<ConfiguredKeyStore> = OnceLock::new();
+    if let Some(keystore) = KEYSTORE.get() {
+        return Ok(keystore.clone());
+    }
+    let keystore = ConfiguredKeyStore::new(crate::app_config())?;
+    Ok(KEYSTORE.get_or_init(|| keystore).clone())
 }
 
 pub struct DidAccessorImpl {}
@@ -14,25 +47,49 @@ impl DidAccessor for DidAccessorImpl {
         config.get_did().unwrap().to_string()
     }
 
-    fn get_my_keyring(&self) -> KeyPairing {
+    fn get_my_keyring(&self) -> Result<KeyPairing, DidAccessorError> {
+        let keyring = KeyPairingWithConfig::load_keyring(crate::app_config(), keystore()?)?;
+        Ok(keyring.get_keyring())
+    }
+
+    fn get_keyring_of(&self, did: &str) -> Result<Option<KeyPairing>, DidAccessorError> {
         let config = crate::app_config();
         let config = config.lock();
-        config.load_keyring().expect("failed to load keyring")
+        if config.get_did().as_deref() != Some(did) {
+            return Ok(config
+                .load_peer_keyring(did)
+                .map_err(KeyPairingError::from)?);
+        }
+        // NOTE: get_my_keyring locks the config again.
+        drop(config);
+        self.get_my_keyring().map(Some)
     }
 }
 
 #[cfg(test)]
 pub mod mocks {
+    use std::collections::BTreeMap;
+
     use super::*;
 
     pub struct MockDidAccessor {
         my_did: String,
         my_keyring: KeyPairing,
+        peers: BTreeMap<String, KeyPairing>,
     }
 
     impl MockDidAccessor {
         pub fn new(my_did: String, my_keyring: KeyPairing) -> MockDidAccessor {
-            MockDidAccessor { my_did, my_keyring }
+            MockDidAccessor {
+                my_did,
+                my_keyring,
+                peers: BTreeMap::new(),
+            }
+        }
+
+        pub fn with_peer(mut self, did: String, keyring: KeyPairing) -> Self {
+            self.peers.insert(did, keyring);
+            self
         }
     }
 
@@ -41,8 +98,15 @@ pub mod mocks {
             self.my_did.clone()
         }
 
-        fn get_my_keyring(&self) -> KeyPairing {
-            self.my_keyring.clone()
+        fn get_my_keyring(&self) -> Result<KeyPairing, DidAccessorError> {
+            Ok(self.my_keyring.clone())
+        }
+
+        fn get_keyring_of(&self, did: &str) -> Result<Option<KeyPairing>, DidAccessorError> {
+            if did == self.my_did {
+                return Ok(Some(self.my_keyring.clone()));
+            }
+            Ok(self.peers.get(did).cloned())
         }
     }
 }
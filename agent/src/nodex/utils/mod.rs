@@ -1,6 +1,7 @@
 pub mod did_accessor;
 pub mod sidetree_client;
 pub mod studio_client;
+pub mod web_did_client;
 
 pub trait UnwrapLog<T, E> {
     fn unwrap_log(self) -> T;
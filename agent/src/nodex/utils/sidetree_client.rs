@@ -17,22 +17,12 @@ impl SideTreeClient {
             client: reqwest::Client::new(),
         })
     }
-}
 
-#[derive(Debug, thiserror::Error)]
-pub enum SideTreeClientError {
-    #[error("parse error: {0}")]
-    ParseError(#[from] ParseError),
-    #[error("reqwest error: {0:?}")]
-    ReqwestError(#[from] reqwest::Error),
-}
-
-impl SidetreeHttpClient for SideTreeClient {
-    type Error = SideTreeClientError;
-    async fn post_create_identifier(
+    // NOTE: Sidetree accepts every operation type on the same endpoint.
+    async fn post_operation(
         &self,
         body: &str,
-    ) -> Result<SidetreeHttpClientResponse, Self::Error> {
+    ) -> Result<SidetreeHttpClientResponse, SideTreeClientError> {
         let url = self.base_url.join("/api/v1/operations")?;
 
         let response = self
@@ -50,6 +40,42 @@ impl SidetreeHttpClient for SideTreeClient {
 
         Ok(response)
     }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum SideTreeClientError {
+    #[error("parse error: {0}")]
+    ParseError(#[from] ParseError),
+    #[error("reqwest error: {0:?}")]
+    ReqwestError(#[from] reqwest::Error),
+}
+
+impl SidetreeHttpClient for SideTreeClient {
+    type Error = SideTreeClientError;
+    async fn post_create_identifier(
+        &self,
+        body: &str,
+    ) -> Result<SidetreeHttpClientResponse, Self::Error> {
+        self.post_operation(body).await
+    }
+    async fn post_update_identifier(
+        &self,
+        body: &str,
+    ) -> Result<SidetreeHttpClientResponse, Self::Error> {
+        self.post_operation(body).await
+    }
+    async fn post_recover_identifier(
+        &self,
+        body: &str,
+    ) -> Result<SidetreeHttpClientResponse, Self::Error> {
+        self.post_operation(body).await
+    }
+    async fn post_deactivate_identifier(
+        &self,
+        body: &str,
+    ) -> Result<SidetreeHttpClientResponse, Self::Error> {
+        self.post_operation(body).await
+    }
     async fn get_find_identifier(
         &self,
         did: &str,
@@ -1,10 +1,9 @@
 use super::did_accessor::{DidAccessor, DidAccessorImpl};
-use crate::nodex::utils::sidetree_client::SideTreeClient;
+use crate::repository::did_repository::{did_repository, AgentDidRepository};
 use crate::{network_config, server_config};
 use anyhow::Context;
 use chrono::Utc;
 use hmac::{Hmac, Mac};
-use protocol::did::did_repository::DidRepositoryImpl;
 use protocol::didcomm::encrypted::{DidCommEncryptedService, DidCommServiceWithAttachment};
 use protocol::verifiable_credentials::types::VerifiableCredentials;
 use reqwest::{
@@ -23,7 +22,7 @@ pub struct StudioClientConfig {
 pub struct StudioClient {
     pub base_url: Url,
     pub instance: reqwest::Client,
-    pub didcomm_service: DidCommServiceWithAttachment<DidRepositoryImpl<SideTreeClient>>,
+    pub didcomm_service: DidCommServiceWithAttachment<AgentDidRepository>,
     pub did_accessor: DidAccessorImpl,
 }
 
@@ -32,8 +31,7 @@ impl StudioClient {
         let url = Url::parse(&_config.base_url.to_string())?;
         let client = reqwest::Client::new();
         let server_config = server_config();
-        let sidetree_client = SideTreeClient::new(&server_config.did_http_endpoint())?;
-        let did_repository = DidRepositoryImpl::new(sidetree_client);
+        let did_repository = did_repository()?;
         let didcomm_service =
             DidCommServiceWithAttachment::new(did_repository, server_config.did_attachment_link());
         let did_accessor = DidAccessorImpl {};
@@ -117,7 +115,7 @@ impl StudioClient {
             "os": os,
         });
         let my_did = self.did_accessor.get_my_did();
-        let my_keyring = self.did_accessor.get_my_keyring();
+        let my_keyring = self.did_accessor.get_my_keyring()?;
 
         let model = VerifiableCredentials::new(my_did, json!(message), Utc::now());
         let payload = self
@@ -136,7 +134,7 @@ impl StudioClient {
         project_did: &str,
     ) -> anyhow::Result<reqwest::Response> {
         let my_did = self.did_accessor.get_my_did();
-        let my_keyring = self.did_accessor.get_my_keyring();
+        let my_keyring = self.did_accessor.get_my_keyring()?;
 
         let model = VerifiableCredentials::new(my_did, serde_json::Value::Null, Utc::now());
         let payload = self
@@ -161,7 +159,7 @@ impl StudioClient {
             "is_verified": is_verified,
         });
         let my_did = self.did_accessor.get_my_did();
-        let my_keyring = self.did_accessor.get_my_keyring();
+        let my_keyring = self.did_accessor.get_my_keyring()?;
 
         let model = VerifiableCredentials::new(my_did, payload, Utc::now());
         let payload = self
@@ -179,7 +177,7 @@ impl StudioClient {
         project_did: &str,
     ) -> anyhow::Result<reqwest::Response> {
         let my_did = self.did_accessor.get_my_did();
-        let my_keyring = self.did_accessor.get_my_keyring();
+        let my_keyring = self.did_accessor.get_my_keyring()?;
 
         let model = VerifiableCredentials::new(my_did, serde_json::Value::Null, Utc::now());
         let payload = self
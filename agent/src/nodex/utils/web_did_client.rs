@@ -0,0 +1,62 @@
+use protocol::did_web::client::{DidWebHttpClient, DidWebHttpClientResponse};
+use protocol::did_webvh::service::client::{DidWebvhHttpClient, DidWebvhHttpClientResponse};
+use std::time::Duration;
+
+// NOTE: A slow or stalled host must not hold up resolution, e.g. of incoming messages.
+const TIMEOUT: Duration = Duration::from_secs(30);
+
+// Fetches the files of web-hosted DID methods (did:web, did:webvh) over HTTPS.
+#[derive(Clone)]
+pub struct WebDidClient {
+    client: reqwest::Client,
+}
+
+impl Default for WebDidClient {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl WebDidClient {
+    pub fn new() -> Self {
+        // NOTE: Like reqwest::Client::new, this fails only if the TLS backend cannot be initialized.
+        let client = reqwest::Client::builder()
+            .timeout(TIMEOUT)
+            .build()
+            .expect("Failed to build reqwest client");
+        Self { client }
+    }
+
+    async fn get(&self, url: &str) -> Result<(reqwest::StatusCode, String), WebDidClientError> {
+        let response = self.client.get(url).send().await?;
+        let status = response.status();
+        let body = response.text().await?;
+        Ok((status, body))
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum WebDidClientError {
+    #[error("reqwest error: {0:?}")]
+    ReqwestError(#[from] reqwest::Error),
+}
+
+impl DidWebHttpClient for WebDidClient {
+    type Error = WebDidClientError;
+    async fn get_did_document(&self, url: &str) -> Result<DidWebHttpClientResponse, Self::Error> {
+        let (status, body) = self.get(url).await?;
+        Ok(DidWebHttpClientResponse::new(status, body))
+    }
+}
+
+impl DidWebvhHttpClient for WebDidClient {
+    type Error = WebDidClientError;
+    async fn get_did_log(&self, url: &str) -> Result<DidWebvhHttpClientResponse, Self::Error> {
+        let (status, body) = self.get(url).await?;
+        Ok(DidWebvhHttpClientResponse::new(status, body))
+    }
+    async fn get_did_witness(&self, url: &str) -> Result<DidWebvhHttpClientResponse, Self::Error> {
+        let (status, body) = self.get(url).await?;
+        Ok(DidWebvhHttpClientResponse::new(status, body))
+    }
+}
@@ -1,11 +1,80 @@
+use std::sync::OnceLock;
+use std::time::Duration;
+
+use protocol::did::cached::{CachedDidRepository, DidCache};
+use protocol::did::did_repository::DidRepositoryImpl;
+use protocol::did::fallback::FallbackDidRepository;
+use protocol::did::file_store::FileDidRepository;
+use protocol::did::multi_method::MultiMethodDidRepository;
+use protocol::did_web::did_repository::DidWebRepository;
+use protocol::did_webvh::service::resolver::DidWebvhResolver;
+
+use crate::config::DidCacheConfig;
+use crate::nodex::utils::sidetree_client::SideTreeClient;
+use crate::nodex::utils::web_did_client::WebDidClient;
+use crate::{app_config, server_config};
+
+pub type AgentDidRepository = CachedDidRepository<
+    FallbackDidRepository<
+        MultiMethodDidRepository<DidRepositoryImpl<SideTreeClient>, WebDidClient, WebDidClient>,
+        FileDidRepository,
+    >,
+>;
+
+// NOTE: Shared by all repositories, since they are created per request.
+fn did_cache(config: &DidCacheConfig) -> DidCache {
+    static DID_CACHE: OnceLock<DidCache> = OnceLock::new();
+    DID_CACHE
+        .get_or_init(|| {
+            DidCache::new(
+                config.capacity,
+                Duration::from_secs(config.ttl),
+                Duration::from_secs(config.negative_ttl),
+            )
+        })
+        .clone()
+}
+
+// Resolves the DID methods enabled by did_resolver of config.json,
+// falling back to the pinned documents when it fails, e.g. offline.
+// NOTE: Without any method enabled, only the pinned documents are resolved.
+// Our own DID is managed on Sidetree at NODEX_DID_HTTP_ENDPOINT.
+pub fn did_repository() -> anyhow::Result<AgentDidRepository> {
+    let server_config = server_config();
+    let sidetree_client = SideTreeClient::new(&server_config.did_http_endpoint())?;
+    let (config, did_store_path) = {
+        let app_config = app_config();
+        let app_config = app_config.lock();
+        (
+            app_config.get_did_resolver_config(),
+            app_config.did_store_path(),
+        )
+    };
+    let web_client = WebDidClient::new();
+
+    let repository = MultiMethodDidRepository::new(
+        DidRepositoryImpl::new(sidetree_client).hash_algorithm(config.sidetree_hash_algorithm),
+        DidWebvhResolver::new(web_client.clone()).require_witness(config.webvh_require_witness),
+        DidWebRepository::new(web_client),
+    )
+    .methods(config.methods);
+    let repository = FallbackDidRepository::new(repository, FileDidRepository::new(did_store_path));
+    Ok(CachedDidRepository::new(
+        repository,
+        did_cache(&config.cache),
+    ))
+}
+
 #[cfg(test)]
 pub mod mocks {
     use std::{collections::BTreeMap, convert::TryFrom};
 
     use protocol::{
         did::{
-            did_repository::{CreateIdentifierError, DidRepository, FindIdentifierError},
-            sidetree::payload::{DidDocument, DidPublicKey, DidResolutionResponse, MethodMetadata},
+            did_repository::{DidResolver, FindIdentifierError},
+            sidetree::payload::{
+                DidPublicKey, DidResolutionResponse, MethodMetadata, SidetreeDidDocument,
+            },
         },
         keyring::jwk::Jwk,
         keyring::keypair::{KeyPair, KeyPairing},
@@ -33,15 +102,8 @@ pub mod mocks {
     #[derive(Debug, thiserror::Error)]
     pub enum DummyError {}
 
-    impl DidRepository for MockDidRepository {
-        type CreateIdentifierError = CreateIdentifierError<DummyError>;
+    impl DidResolver for MockDidRepository {
         type FindIdentifierError = FindIdentifierError<DummyError>;
-        async fn create_identifier(
-            &self,
-            _keyring: KeyPairing,
-        ) -> Result<DidResolutionResponse, Self::CreateIdentifierError> {
-            unimplemented!()
-        }
         async fn find_identifier(
             &self,
             did: &str,
@@ -55,14 +117,21 @@ pub mod mocks {
                                 id: "#signingKey".to_string(),
                                 controller: String::new(),
                                 r#type: "EcdsaSecp256k1VerificationKey2019".to_string(),
-                                public_key_jwk: Jwk::try_from(keyring.sign.get_public_key())
-                                    .unwrap(),
+                                public_key_jwk: Some(
+                                    Jwk::try_from(keyring.sign.get_public_key()).unwrap(),
+                                ),
+                                public_key_multibase: None,
+                                public_key_base58: None,
+                                purposes: None,
                             },
                             DidPublicKey {
                                 id: "#encryptionKey".to_string(),
                                 controller: String::new(),
                                 r#type: "X25519KeyAgreementKey2019".to_string(),
-                                public_key_jwk: Jwk::from(keyring.encrypt.get_public_key()),
+                                public_key_jwk: Some(Jwk::from(keyring.encrypt.get_public_key())),
+                                public_key_multibase: None,
+                                public_key_base58: None,
+                                purposes: None,
                             },
                         ]
                     })
@@ -70,17 +139,20 @@ pub mod mocks {
 
                 let response = DidResolutionResponse {
                     context: "https://www.w3.org/ns/did-resolution/v1".to_string(),
-                    did_document: DidDocument {
+                    did_document: SidetreeDidDocument {
                         id: did.to_string(),
                         public_key: Some(public_keys),
                         service: None,
                         authentication: Some(vec!["signingKey".to_string()]),
-                    },
+                    }
+                    .into(),
                     method_metadata: MethodMetadata {
                         published: true,
                         recovery_commitment: None,
                         update_commitment: None,
+                        ttl: None,
                     },
+                    did_document_metadata: None,
                 };
                 Ok(Some(response))
             } else {
@@ -92,32 +164,28 @@ pub mod mocks {
     #[derive(Clone, Copy)]
     pub struct NoPublicKeyDidRepository;
 
-    impl DidRepository for NoPublicKeyDidRepository {
-        type CreateIdentifierError = CreateIdentifierError<DummyError>;
+    impl DidResolver for NoPublicKeyDidRepository {
         type FindIdentifierError = FindIdentifierError<DummyError>;
-        async fn create_identifier(
-            &self,
-            _keyring: KeyPairing,
-        ) -> Result<DidResolutionResponse, Self::CreateIdentifierError> {
-            unimplemented!()
-        }
         async fn find_identifier(
             &self,
             did: &str,
         ) -> Result<Option<DidResolutionResponse>, Self::FindIdentifierError> {
             Ok(Some(DidResolutionResponse {
                 context: "https://www.w3.org/ns/did-resolution/v1".to_string(),
-                did_document: DidDocument {
+                did_document: SidetreeDidDocument {
                     id: did.to_string(),
                     public_key: None,
                     service: None,
                     authentication: None,
-                },
+                }
+                .into(),
                 method_metadata: MethodMetadata {
                     published: true,
                     recovery_commitment: None,
                     update_commitment: None,
+                    ttl: None,
                 },
+                did_document_metadata: None,
             }))
         }
     }
@@ -125,32 +193,28 @@ pub mod mocks {
     #[derive(Clone, Copy)]
     pub struct IllegalPublicKeyLengthDidRepository;
 
-    impl DidRepository for IllegalPublicKeyLengthDidRepository {
-        type CreateIdentifierError = CreateIdentifierError<DummyError>;
+    impl DidResolver for IllegalPublicKeyLengthDidRepository {
         type FindIdentifierError = FindIdentifierError<DummyError>;
-        async fn create_identifier(
-            &self,
-            _keyring: KeyPairing,
-        ) -> Result<DidResolutionResponse, Self::CreateIdentifierError> {
-            unimplemented!()
-        }
         async fn find_identifier(
             &self,
             did: &str,
         ) -> Result<Option<DidResolutionResponse>, Self::FindIdentifierError> {
             Ok(Some(DidResolutionResponse {
                 context: "https://www.w3.org/ns/did-resolution/v1".to_string(),
-                did_document: DidDocument {
+                did_document: SidetreeDidDocument {
                     id: did.to_string(),
                     public_key: Some(vec![]),
                     service: None,
                     authentication: None,
-                },
+                }
+                .into(),
                 method_metadata: MethodMetadata {
                     published: true,
                     recovery_commitment: None,
                     update_commitment: None,
+                    ttl: None,
                 },
+                did_document_metadata: None,
             }))
         }
     }
@@ -192,10 +192,15 @@ pub fn make_router() -> Router {
             "/identifiers",
             post(controllers::public::nodex_create_identifier::handler),
         )
+        .route(
+            "/peer-identifiers",
+            post(controllers::public::nodex_create_peer_identifier::handler),
+        )
         .route(
             "/identifiers/{did}",
             get(controllers::public::nodex_find_identifier::handler),
         )
+        .route("/jwks", get(controllers::public::nodex_jwks::handler))
         .route(
             "/create-verifiable-message",
             post(controllers::public::nodex_create_verifiable_message::handler),
@@ -236,4 +241,16 @@ pub fn make_router() -> Router {
             "/internal/network",
             post(controllers::internal::network::handler),
         )
+        .route(
+            "/internal/did/rotate-keys",
+            post(controllers::internal::did::handler_rotate_keys),
+        )
+        .route(
+            "/internal/did/deactivate",
+            post(controllers::internal::did::handler_deactivate),
+        )
+        .route(
+            "/internal/did/services",
+            post(controllers::internal::did::handler_update_services),
+        )
 }
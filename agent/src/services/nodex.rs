@@ -1,15 +1,27 @@
-use crate::nodex::extension::secure_keystore::FileBaseKeyStore;
+use crate::app_config;
+use crate::nodex::extension::secure_keystore::ConfiguredKeyStore;
 use crate::nodex::keyring;
-use crate::nodex::utils::sidetree_client::SideTreeClient;
-use crate::{app_config, server_config};
+use crate::repository::did_repository::{did_repository, AgentDidRepository};
 use anyhow;
+use chrono::Utc;
 use controller::managers::{
     resource::ResourceManagerTrait,
     runtime::{RuntimeManagerImpl, RuntimeManagerWithoutAsync, State},
 };
 use controller::validator::storage::check_storage;
-use protocol::did::did_repository::{DidRepository, DidRepositoryImpl};
-use protocol::did::sidetree::payload::DidResolutionResponse;
+use protocol::did::did_repository::{long_form_identifier, DidRepository, DidResolver};
+use protocol::did::file_store::{export_did_document, FileDidRepository};
+use protocol::did::multi_method::DidMethod;
+use protocol::did::resolution::{resolve_identifier, DidResolutionResult};
+use protocol::did::sidetree::payload::{verify_commitment, DidResolutionResponse, ServiceEndpoint};
+use protocol::did_key::did_repository::PublicKeyFormat;
+use protocol::did_peer::did_repository::{did_peer, DidPeerRepository};
+use protocol::did_webvh::domain::did::{Did, DidWebvh};
+use protocol::did_webvh::domain::did_log::{parse_did_log, verify_did_log};
+use protocol::did_webvh::service::creator::{create_genesis_entry_from_keyring, serialize_did_log};
+use protocol::keyring::jwk::{Jwk, JwkThumbprintError, Jwks};
+use protocol::keyring::keypair::{KeyPair, KeyPairing};
+use protocol::verifiable_credentials::jws::JwsVerifyingKey;
 
 #[cfg(windows)]
 mod windows_imports {
@@ -20,47 +32,305 @@ mod windows_imports {
 use windows_imports::*;
 
 pub struct NodeX {
-    did_repository: DidRepositoryImpl<SideTreeClient>,
+    did_repository: AgentDidRepository,
 }
 
 impl NodeX {
     pub fn new() -> Self {
-        let server_config = server_config();
-        let sidetree_client = SideTreeClient::new(&server_config.did_http_endpoint()).unwrap();
-        let did_repository = DidRepositoryImpl::new(sidetree_client);
+        let did_repository = did_repository().unwrap();
 
         NodeX { did_repository }
     }
 
-    pub fn did_repository(&self) -> &DidRepositoryImpl<SideTreeClient> {
+    pub fn did_repository(&self) -> &AgentDidRepository {
         &self.did_repository
     }
 
     pub async fn create_identifier(&self) -> anyhow::Result<DidResolutionResponse> {
         // NOTE: find did
         let config = app_config();
-        let keystore = FileBaseKeyStore::new(config.clone());
-        if let Some(did) =
-            keyring::keypair::KeyPairingWithConfig::load_keyring(config.clone(), keystore.clone())
-                .ok()
-                .and_then(|v| v.get_identifier().ok())
-        {
-            if let Some(json) = self.find_identifier(&did).await? {
-                return Ok(json);
+        let keystore = ConfiguredKeyStore::new(config.clone())?;
+        // NOTE: A new keyring is created only when the keys are absent. The keys of
+        //       a configured DID which fail to load, e.g. with a wrong passphrase,
+        //       must never be replaced.
+        let did = match keyring::keypair::KeyPairingWithConfig::load_keyring(
+            config.clone(),
+            keystore.clone(),
+        ) {
+            Ok(v) => v.get_identifier().ok(),
+            Err(keyring::keypair::KeyPairingError::KeyNotFound) => None,
+            Err(e) => return Err(e.into()),
+        };
+        if let Some(did) = did {
+            match Self::did_method(&did)? {
+                DidMethod::Webvh => return self.find_local_webvh_identifier(&did),
+                _ => {
+                    if let Some(json) = self.find_identifier(&did).await? {
+                        Self::pin_identifier(&json);
+                        return Ok(json);
+                    }
+                }
             }
         }
 
         let mut keyring_with_config =
-            keyring::keypair::KeyPairingWithConfig::create_keyring(config, keystore);
-        let res = self
+            keyring::keypair::KeyPairingWithConfig::create_keyring(config, keystore)?;
+        let keyring = keyring_with_config.get_keyring();
+        // NOTE: Save the key pairs before submitting, so that they are never lost
+        //       once the DID is created, even if the rest fails.
+        keyring_with_config.save_keyring(keyring.clone())?;
+        let mut res = self
             .did_repository
-            .create_identifier(keyring_with_config.get_keyring())
+            .create_identifier(keyring.clone())
             .await?;
-        keyring_with_config.save(&res.did_document.id);
+        // NOTE: The long-form DID is usable right away, while the creation is being anchored.
+        if app_config().lock().get_long_form_did() {
+            res.did_document.id = long_form_identifier(&res.did_document.id, &keyring)?;
+        }
+        keyring_with_config.save(&res.did_document.id)?;
+        Self::pin_identifier(&res);
 
         Ok(res)
     }
 
+    // The method of our own DID, did:nodex unless it is minted by create_webvh_identifier.
+    fn did_method(did: &str) -> anyhow::Result<DidMethod> {
+        let did: Did = did.parse()?;
+        DidMethod::from_name(did.get_method())
+            .ok_or_else(|| anyhow::anyhow!("did method is not supported: {}", did))
+    }
+
+    fn did_store() -> FileDidRepository {
+        FileDidRepository::new(app_config().lock().did_store_path())
+    }
+
+    // NOTE: Our own DID is pinned, so that the agent starts without the network.
+    fn pin_identifier(response: &DidResolutionResponse) {
+        if let Err(e) = Self::did_store().pin(response) {
+            log::warn!("failed to pin {}: {}", response.did_document.id, e);
+        }
+    }
+
+    // A bundle of our own DID document signed with its sign key, to be imported by offline devices.
+    pub fn export_did_document(&self, response: &DidResolutionResponse) -> anyhow::Result<String> {
+        let config = app_config();
+        let keystore = ConfiguredKeyStore::new(config.clone())?;
+        let keyring_with_config =
+            keyring::keypair::KeyPairingWithConfig::load_keyring(config, keystore)?;
+        let bundle = export_did_document(response, &keyring_with_config.get_keyring(), Utc::now())?;
+        Ok(serde_json::to_string_pretty(&bundle)?)
+    }
+
+    // Pins the DID document of a bundle made by export_did_document,
+    // after checking its signature by one of the trusted keys of config.json.
+    pub fn import_did_document(&self, bundle: &str) -> anyhow::Result<String> {
+        let trusted_keys = app_config().lock().get_did_resolver_config().trusted_keys;
+        let did = Self::did_store()
+            .trusted_keys(trusted_keys)
+            .import(bundle)?
+            .did_document
+            .id;
+        self.did_repository.invalidate(&did);
+        Ok(did)
+    }
+
+    // Mints a did:webvh DID from a new keyring, as an alternative to the Sidetree DID.
+    // NOTE: did.jsonl is written next to config.json and has to be hosted at
+    //       the did:webvh log url to be resolvable by others.
+    pub fn create_webvh_identifier(&self, uri: &str) -> anyhow::Result<String> {
+        let config = app_config();
+        let keystore = ConfiguredKeyStore::new(config.clone())?;
+        if let Some(did) = config.lock().get_did() {
+            anyhow::bail!("{} already exists", did);
+        }
+
+        let mut keyring_with_config =
+            keyring::keypair::KeyPairingWithConfig::create_keyring(config.clone(), keystore)?;
+        let update_key_pair = keyring_with_config.create_webvh_update_key_pair()?;
+        let (did, entry) = create_genesis_entry_from_keyring(
+            uri,
+            &keyring_with_config.get_keyring(),
+            &update_key_pair,
+        )?;
+        let did = did.get_did().to_string();
+        config.lock().write_did_log(&serialize_did_log(&[entry])?)?;
+        keyring_with_config.save(&did)?;
+
+        Ok(did)
+    }
+
+    fn find_local_webvh_identifier(&self, did: &str) -> anyhow::Result<DidResolutionResponse> {
+        let did_log = app_config().lock().read_did_log()?;
+        let did: DidWebvh = did.parse()?;
+        let resolution = verify_did_log(&did, &parse_did_log(&did_log)?)?;
+        Ok(resolution.into())
+    }
+
+    // Rotates the sign, encrypt and update keys of the device DID.
+    // NOTE: The new keyring is saved as pending before the update is submitted, and promoted
+    //       to the current keys once Sidetree accepts it. If the agent stops in between,
+    //       the pending keyring is promoted or submitted again by the next update.
+    pub async fn rotate_keys(&self) -> anyhow::Result<String> {
+        let config = app_config();
+        let keystore = ConfiguredKeyStore::new(config.clone())?;
+        let mut keyring_with_config =
+            keyring::keypair::KeyPairingWithConfig::load_keyring(config, keystore)?;
+        let did = keyring_with_config.get_identifier()?;
+        match Self::did_method(&did)? {
+            DidMethod::Nodex => {}
+            method => anyhow::bail!("key rotation of did:{} is not supported", method.name()),
+        }
+
+        let keyring = match self.pending_keyring(&did, &mut keyring_with_config).await? {
+            Some(pending) => pending,
+            None => keyring_with_config.rotate_keyring()?,
+        };
+        keyring_with_config.save_pending_keyring(&keyring)?;
+        self.did_repository
+            .update_identifier(&did, keyring_with_config.get_update_key_pair(), &keyring)
+            .await?;
+        // NOTE: Accepted is not anchored yet. Until the update is anchored, resolvers still
+        //       see the old keys, so what the device signs with the new sign key does not
+        //       verify for them and messages to the old encrypt key can not be decrypted.
+        keyring_with_config.promote_pending_keyring(keyring)?;
+
+        Ok(did)
+    }
+
+    // Adds `services` to our own DID document and removes the services of `remove_ids`,
+    // e.g. to publish the DIDCommMessaging endpoint other agents deliver messages to.
+    pub async fn update_services(
+        &self,
+        services: Vec<ServiceEndpoint>,
+        remove_ids: Vec<String>,
+    ) -> anyhow::Result<String> {
+        let config = app_config();
+        let keystore = ConfiguredKeyStore::new(config.clone())?;
+        let mut keyring_with_config =
+            keyring::keypair::KeyPairingWithConfig::load_keyring(config, keystore)?;
+        let did = keyring_with_config.get_identifier()?;
+        match Self::did_method(&did)? {
+            DidMethod::Nodex => {}
+            method => anyhow::bail!("service update of did:{} is not supported", method.name()),
+        }
+
+        let keyring = match self.pending_keyring(&did, &mut keyring_with_config).await? {
+            // NOTE: The services do not publish the keys but the update key of a pending rotation.
+            Some(pending)
+                if pending.sign.get_public_key()
+                    == keyring_with_config.get_keyring().sign.get_public_key() =>
+            {
+                pending
+            }
+            Some(_) => anyhow::bail!("key rotation of {} is pending, rotate keys again", did),
+            None => keyring_with_config.rotate_update_key()?,
+        };
+        keyring_with_config.save_pending_keyring(&keyring)?;
+        self.did_repository
+            .update_services(
+                &did,
+                keyring_with_config.get_update_key_pair(),
+                &keyring.update,
+                services,
+                remove_ids,
+            )
+            .await?;
+        keyring_with_config.promote_pending_keyring(keyring)?;
+
+        Ok(did)
+    }
+
+    // Settles the keyring left pending by an update of our own DID which did not return.
+    // It is promoted if the update is applied, otherwise returned to be submitted again,
+    // since the update may still be anchored later.
+    async fn pending_keyring(
+        &self,
+        did: &str,
+        keyring_with_config: &mut keyring::keypair::KeyPairingWithConfig<ConfiguredKeyStore>,
+    ) -> anyhow::Result<Option<KeyPairing>> {
+        let Some(pending) = keyring_with_config.load_pending_keyring()? else {
+            return Ok(None);
+        };
+        self.did_repository.invalidate(did);
+        let is_applied = self
+            .find_identifier(did)
+            .await?
+            .and_then(|v| v.method_metadata.update_commitment)
+            .is_some_and(|v| verify_commitment(&v, pending.update.get_public_key()));
+        if is_applied {
+            log::info!("pending keyring of {} is applied", did);
+            keyring_with_config.promote_pending_keyring(pending)?;
+            return Ok(None);
+        }
+        Ok(Some(pending))
+    }
+
+    // Deactivates the device DID, e.g. when the device is retired or compromised.
+    // A new DID is created on the next start.
+    pub async fn deactivate_identifier(&self) -> anyhow::Result<String> {
+        let config = app_config();
+        let keystore = ConfiguredKeyStore::new(config.clone())?;
+        let mut keyring_with_config =
+            keyring::keypair::KeyPairingWithConfig::load_keyring(config, keystore)?;
+        let did = keyring_with_config.get_identifier()?;
+        match Self::did_method(&did)? {
+            DidMethod::Nodex => {}
+            method => anyhow::bail!("deactivation of did:{} is not supported", method.name()),
+        }
+
+        self.did_repository
+            .deactivate_identifier(&did, keyring_with_config.get_recovery_key_pair())
+            .await?;
+        keyring_with_config.remove_identifier();
+        // NOTE: Unpin before invalidating, so that the pinned copy is never resolved
+        //       and cached again as if the DID were still active.
+        if let Err(e) = Self::did_store().unpin(&did) {
+            log::warn!("failed to unpin {}: {}", did, e);
+        }
+        self.did_repository.invalidate(&did);
+
+        Ok(did)
+    }
+
+    // The public keys of the device DID as a JWK Set, with their thumbprints as `kid`,
+    // e.g. for API gateways verifying JWS signed by the device.
+    pub fn jwks(&self) -> anyhow::Result<Jwks> {
+        let config = app_config();
+        let keystore = ConfiguredKeyStore::new(config.clone())?;
+        let keyring =
+            keyring::keypair::KeyPairingWithConfig::load_keyring(config, keystore)?.get_keyring();
+        let sign_key = keyring.sign.get_public_key();
+        let sign = Jwk::try_from(sign_key)?
+            .with_use("sig")
+            .with_alg(sign_key.alg());
+        let encrypt = Jwk::from(keyring.encrypt.get_public_key()).with_use("enc");
+        let keys = [sign, encrypt]
+            .into_iter()
+            .map(|v| {
+                let kid = v.thumbprint()?;
+                Ok(v.with_kid(kid))
+            })
+            .collect::<Result<_, JwkThumbprintError>>()?;
+        Ok(Jwks { keys })
+    }
+
+    // Creates a pairwise did:peer DID for a relationship with another device, so that
+    // our relationships are not linkable through the device DID.
+    // NOTE: A did:peer DID is resolved from itself, so there is nothing to publish.
+    pub fn create_peer_identifier(
+        &self,
+        services: Vec<ServiceEndpoint>,
+    ) -> anyhow::Result<DidResolutionResponse> {
+        let config = app_config();
+        let keyring = keyring::keypair::create_peer_keyring(&config)?;
+        let did = did_peer(&keyring, &services)?;
+        config.lock().save_peer_keyring(&did, &keyring)?;
+
+        Ok(DidPeerRepository::new()
+            .public_key_format(PublicKeyFormat::JsonWebKey2020)
+            .resolve(&did)?)
+    }
+
     pub async fn find_identifier(
         &self,
         did: &str,
@@ -70,6 +340,10 @@ impl NodeX {
         Ok(res)
     }
 
+    pub async fn resolve_identifier(&self, did: &str) -> DidResolutionResult {
+        resolve_identifier(&self.did_repository, did).await
+    }
+
     pub async fn update_version(&self, binary_url: &str) -> anyhow::Result<()> {
         #[cfg(windows)]
         {
@@ -1,9 +1,9 @@
 use crate::nodex::utils::did_accessor::{DidAccessor, DidAccessorImpl};
-use crate::nodex::utils::sidetree_client::SideTreeClient;
 use crate::repository::attribute_repository::{AttributeStoreRepository, AttributeStoreRequest};
 use crate::repository::custom_metric_repository::{
     CustomMetricStoreRepository, CustomMetricStoreRequest,
 };
+use crate::repository::did_repository::{did_repository, AgentDidRepository};
 use crate::repository::event_repository::{EventStoreRepository, EventStoreRequest};
 use crate::repository::message_activity_repository::MessageActivityHttpError;
 use crate::repository::metric_repository::{MetricStoreRepository, MetricsWithTimestamp};
@@ -15,7 +15,6 @@ use crate::{
     },
 };
 use anyhow::Context;
-use protocol::did::did_repository::DidRepositoryImpl;
 use protocol::didcomm::encrypted::DidCommEncryptedService;
 use protocol::verifiable_credentials::did_vc::DidVcService;
 use protocol::verifiable_credentials::types::VerifiableCredentials;
@@ -44,7 +43,7 @@ struct ErrorResponse {
 
 pub struct Studio {
     http_client: StudioClient,
-    did_repository: DidRepositoryImpl<SideTreeClient>,
+    did_repository: AgentDidRepository,
     did_accessor: DidAccessorImpl,
 }
 
@@ -86,9 +85,7 @@ impl Studio {
             }
         };
 
-        let sidetree_client = SideTreeClient::new(&server_config.did_http_endpoint())
-            .expect("failed to create sidetree client");
-        let did_repository = DidRepositoryImpl::new(sidetree_client);
+        let did_repository = did_repository().expect("failed to create did repository");
         let did_accessor = DidAccessorImpl {};
 
         Studio {
@@ -250,10 +247,11 @@ impl Studio {
         request: T,
     ) -> anyhow::Result<()> {
         let my_did = self.did_accessor.get_my_did();
-        let my_keyring = self.did_accessor.get_my_keyring();
+        let my_keyring = self.did_accessor.get_my_keyring()?;
         let model =
             VerifiableCredentials::new(my_did, serde_json::to_value(request)?, chrono::Utc::now());
         let payload = DidVcService::generate(&self.did_repository, model, &my_keyring)
+            .await
             .context("failed to generate payload")?;
         let payload = serde_json::to_string(&payload).context("failed to serialize")?;
 
@@ -312,7 +310,10 @@ impl MessageActivityRepository for Studio {
             network.get_project_did().expect("project_did is not set")
         };
         let my_did = self.did_accessor.get_my_did();
-        let my_keyring = self.did_accessor.get_my_keyring();
+        let my_keyring = self
+            .did_accessor
+            .get_my_keyring()
+            .context("failed to load keyring")?;
 
         let model = VerifiableCredentials::new(my_did, json!(request), request.occurred_at);
         let payload = DidCommEncryptedService::generate(
@@ -368,7 +369,10 @@ impl MessageActivityRepository for Studio {
             network.get_project_did().expect("project_did is not set")
         };
         let my_did = self.did_accessor.get_my_did();
-        let my_keyring = self.did_accessor.get_my_keyring();
+        let my_keyring = self
+            .did_accessor
+            .get_my_keyring()
+            .context("failed to load keyring")?;
 
         let model = VerifiableCredentials::new(my_did, json!(request), request.verified_at);
         let payload = DidCommEncryptedService::generate(
@@ -418,7 +422,7 @@ impl MetricStoreRepository for Studio {
         let mut metrics = request;
         while !metrics.is_empty() {
             let my_did = self.did_accessor.get_my_did();
-            let my_keyring = self.did_accessor.get_my_keyring();
+            let my_keyring = self.did_accessor.get_my_keyring()?;
             let mut metrics_str = Vec::new();
             let mut current_size = 0;
 
@@ -437,6 +441,7 @@ impl MetricStoreRepository for Studio {
 
             let model = VerifiableCredentials::new(my_did, json!(metrics_str), chrono::Utc::now());
             let payload = DidVcService::generate(&self.did_repository, model, &my_keyring)
+                .await
                 .context("failed to generate payload")?;
 
             let payload = serde_json::to_string(&payload).context("failed to serialize")?;
@@ -10,7 +10,7 @@ use protocol::{
 };
 
 use crate::{
-    nodex::utils::did_accessor::DidAccessor,
+    nodex::utils::did_accessor::{DidAccessor, DidAccessorError},
     repository::message_activity_repository::{
         CreatedMessageActivityRequest, MessageActivityRepository, VerifiedMessageActivityRequest,
         VerifiedStatus,
@@ -36,6 +36,10 @@ where
 {
     #[error("encrypted service error: {0}")]
     ServiceGenerate(E),
+    #[error("{0} is not my DID")]
+    NotMyDid(String),
+    #[error("did accessor error: {0}")]
+    DidAccessor(#[from] DidAccessorError),
     #[error("message activity error: {0}")]
     MessageActivity(F),
     #[error("failed serialize/deserialize : {0}")]
@@ -53,6 +57,8 @@ where
     //TODO: Add tests
     #[error("This message is not addressed to me")]
     NotAddressedToMe,
+    #[error("did accessor error: {0}")]
+    DidAccessor(#[from] DidAccessorError),
     #[error("message activity error: {0}")]
     MessageActivity(F),
     #[error("failed serialize/deserialize : {0}")]
@@ -80,6 +86,24 @@ where
         operation_tag: String,
         now: DateTime<Utc>,
     ) -> Result<String, GenerateDidcommMessageUseCaseError<D::GenerateError, R::Error>> {
+        let my_did = self.did_accessor.get_my_did();
+        self.generate_from(my_did, destination_did, message, operation_tag, now)
+            .await
+    }
+
+    // Generates a message from `my_did`, our own DID or one of our pairwise DIDs.
+    pub async fn generate_from(
+        &self,
+        my_did: String,
+        destination_did: String,
+        message: String,
+        operation_tag: String,
+        now: DateTime<Utc>,
+    ) -> Result<String, GenerateDidcommMessageUseCaseError<D::GenerateError, R::Error>> {
+        let my_keyring = self
+            .did_accessor
+            .get_keyring_of(&my_did)?
+            .ok_or_else(|| GenerateDidcommMessageUseCaseError::NotMyDid(my_did.clone()))?;
         let message_id = Uuid::new_v4();
 
         let message = EncodedMessage {
@@ -88,17 +112,11 @@ where
             created_at: now.to_rfc3339(),
         };
         let message = serde_json::to_value(message)?;
-        let my_did = self.did_accessor.get_my_did();
 
         let model = VerifiableCredentials::new(my_did.clone(), message, now);
         let didcomm_message = self
             .didcomm_service
-            .generate(
-                model,
-                &self.did_accessor.get_my_keyring(),
-                &destination_did,
-                None,
-            )
+            .generate(model, &my_keyring, &destination_did, None)
             .await
             .map_err(GenerateDidcommMessageUseCaseError::ServiceGenerate)?;
 
@@ -125,13 +143,21 @@ where
         now: DateTime<Utc>,
     ) -> Result<VerifiableCredentials, VerifyDidcommMessageUseCaseError<D::VerifyError, R::Error>>
     {
-        let my_did = self.did_accessor.get_my_did();
-        if !message.find_receivers().contains(&my_did) {
-            return Err(VerifyDidcommMessageUseCaseError::NotAddressedToMe);
-        }
+        // NOTE: The message may be addressed to one of our pairwise DIDs.
+        let (my_did, my_keyring) = message
+            .find_receivers()
+            .into_iter()
+            .find_map(|did| {
+                self.did_accessor
+                    .get_keyring_of(&did)
+                    .map(|keyring| keyring.map(|keyring| (did, keyring)))
+                    .transpose()
+            })
+            .transpose()?
+            .ok_or(VerifyDidcommMessageUseCaseError::NotAddressedToMe)?;
         let verified = self
             .didcomm_service
-            .verify(&self.did_accessor.get_my_keyring(), &message)
+            .verify(&my_keyring, &message)
             .await
             .map_err(VerifyDidcommMessageUseCaseError::ServiceVerify)?;
         let verified = verified.message;
@@ -166,8 +192,12 @@ struct EncodedMessage {
 mod tests {
     use serde_json;
 
+    use protocol::did_key::did_repository::PublicKeyFormat;
+    use protocol::did_peer::did_repository::{did_peer, DidPeerRepository};
     use protocol::didcomm::encrypted::DidCommEncryptedServiceGenerateError;
     use protocol::didcomm::encrypted::DidCommEncryptedServiceVerifyError;
+    use protocol::keyring::keypair::KeyPairing;
+    use protocol::rand_core::OsRng;
 
     use crate::nodex::utils::did_accessor::mocks::MockDidAccessor;
     use crate::repository::did_repository::mocks::MockDidRepository;
@@ -213,18 +243,87 @@ mod tests {
         assert_eq!(encoded_message.payload, message);
     }
 
+    #[tokio::test]
+    async fn test_create_and_verify_with_peer_did() {
+        let presets = TestPresets::default();
+        let from_peer_keyring = KeyPairing::create_keyring(OsRng);
+        let to_peer_keyring = KeyPairing::create_keyring(OsRng);
+        let from_peer_did = did_peer(&from_peer_keyring, &[]).unwrap();
+        let to_peer_did = did_peer(&to_peer_keyring, &[]).unwrap();
+        let repo = DidPeerRepository::new().public_key_format(PublicKeyFormat::JsonWebKey2020);
+
+        let usecase = DidcommMessageUseCase::new(
+            MockMessageActivityRepository::create_success(),
+            repo.clone(),
+            MockDidAccessor::new(presets.from_did, presets.from_keyring)
+                .with_peer(from_peer_did.clone(), from_peer_keyring),
+        );
+        let generated = usecase
+            .generate_from(
+                from_peer_did.clone(),
+                to_peer_did.clone(),
+                "Hello".to_string(),
+                "test".to_string(),
+                Utc::now(),
+            )
+            .await
+            .unwrap();
+        let generated = serde_json::from_str::<DidCommMessage>(&generated).unwrap();
+        assert_eq!(generated.find_receivers(), vec![to_peer_did.clone()]);
+
+        let usecase = DidcommMessageUseCase::new(
+            MockMessageActivityRepository::verify_success(),
+            repo,
+            MockDidAccessor::new(presets.to_did, presets.to_keyring)
+                .with_peer(to_peer_did, to_peer_keyring),
+        );
+        let verified = usecase.verify(generated, Utc::now()).await.unwrap();
+        assert_eq!(verified.issuer.id, from_peer_did);
+    }
+
     mod generate_failed {
         use crate::nodex::utils::did_accessor::mocks::MockDidAccessor;
 
         use super::*;
 
+        #[tokio::test]
+        async fn test_generate_not_my_did() {
+            let presets = TestPresets::default();
+
+            let usecase = DidcommMessageUseCase::new(
+                MockMessageActivityRepository::create_success(),
+                presets.create_mock_did_repository(),
+                MockDidAccessor::new(presets.from_did, presets.from_keyring),
+            );
+
+            let generated = usecase
+                .generate_from(
+                    presets.to_did.clone(),
+                    presets.to_did.clone(),
+                    "Hello".to_string(),
+                    "test".to_string(),
+                    Utc::now(),
+                )
+                .await;
+
+            if let Err(GenerateDidcommMessageUseCaseError::NotMyDid(did)) = generated {
+                assert_eq!(did, presets.to_did);
+            } else {
+                panic!("unexpected result: {:?}", generated);
+            }
+        }
+
         #[tokio::test]
         async fn test_generate_did_not_found() {
             let presets = TestPresets::default();
 
+            // NOTE: Only the sender, whose sign key the proof refers to, is found.
             let usecase = DidcommMessageUseCase::new(
                 MockMessageActivityRepository::create_success(),
-                MockDidRepository::empty(),
+                MockDidRepository::from_pairs([(
+                    presets.from_did.clone(),
+                    presets.from_keyring.clone(),
+                )]),
                 MockDidAccessor::new(presets.from_did, presets.from_keyring),
             );
 
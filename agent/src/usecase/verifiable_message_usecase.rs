@@ -1,9 +1,9 @@
-use crate::nodex::utils::did_accessor::DidAccessor;
+use crate::nodex::utils::did_accessor::{DidAccessor, DidAccessorError};
 use crate::repository::message_activity_repository::*;
 use chrono::DateTime;
 use chrono::Utc;
 use protocol::{
-    did::did_repository::DidRepository,
+    did::did_repository::DidResolver,
     verifiable_credentials::{did_vc::DidVcService, types::VerifiableCredentials},
 };
 use serde::{Deserialize, Serialize};
@@ -13,7 +13,7 @@ use uuid::Uuid;
 pub struct VerifiableMessageUseCase<R, D, S, A>
 where
     R: MessageActivityRepository,
-    D: DidRepository,
+    D: DidResolver,
     S: DidVcService,
     A: DidAccessor,
 {
@@ -36,6 +36,8 @@ where
     MessageActivity(F),
     #[error("destination did not found")]
     DestinationNotFound(Option<D>),
+    #[error("did accessor error: {0}")]
+    DidAccessor(#[from] DidAccessorError),
     #[error("failed serialize/deserialize : {0}")]
     Json(#[from] serde_json::Error),
 }
@@ -59,7 +61,7 @@ where
 impl<R, D, S, A> VerifiableMessageUseCase<R, D, S, A>
 where
     R: MessageActivityRepository,
-    D: DidRepository,
+    D: DidResolver,
     S: DidVcService,
     A: DidAccessor,
 {
@@ -106,7 +108,8 @@ where
         let model = VerifiableCredentials::new(my_did.clone(), message, now);
         let vc = self
             .vc_service
-            .generate(model, &self.did_accessor.get_my_keyring())
+            .generate(model, &self.did_accessor.get_my_keyring()?)
+            .await
             .map_err(CreateVerifiableMessageUseCaseError::DidVcServiceGenerate)?;
 
         let result = serde_json::to_string(&vc)?;
@@ -0,0 +1,431 @@
+// A DidRepository caching find_identifier results of another one.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use super::did_repository::{DidRepository, DidResolver};
+use super::sidetree::payload::{DidResolutionResponse, ServiceEndpoint};
+use crate::keyring::keypair::{K256KeyPair, KeyPairing};
+
+struct CacheEntry {
+    // None caches that the DID was not found.
+    response: Option<DidResolutionResponse>,
+    expires_at: Instant,
+}
+
+#[derive(Default)]
+struct CacheState {
+    entries: HashMap<String, CacheEntry>,
+    // Bumped by every invalidation. Resolutions started before a DID was invalidated,
+    // or before the cache was cleared, are not cached.
+    generation: u64,
+    invalidated: HashMap<String, u64>,
+    cleared: u64,
+}
+
+impl CacheState {
+    fn invalidated_since(&self, did: &str, generation: u64) -> bool {
+        self.cleared > generation || self.invalidated.get(did).is_some_and(|v| *v > generation)
+    }
+}
+
+// Resolution results by DID, shared by its clones.
+#[derive(Clone)]
+pub struct DidCache {
+    state: Arc<Mutex<CacheState>>,
+    capacity: usize,
+    ttl: Duration,
+    negative_ttl: Duration,
+}
+
+impl DidCache {
+    // `ttl` applies to found DIDs without their own ttl, `negative_ttl` to not found ones.
+    pub fn new(capacity: usize, ttl: Duration, negative_ttl: Duration) -> Self {
+        Self {
+            state: Arc::new(Mutex::new(CacheState::default())),
+            capacity,
+            ttl,
+            negative_ttl,
+        }
+    }
+
+    // Some(None) is a cached not found.
+    fn get(&self, did: &str) -> Option<Option<DidResolutionResponse>> {
+        let mut state = self.state.lock().unwrap();
+        let entries = &mut state.entries;
+        match entries.get(did) {
+            Some(entry) if entry.expires_at > Instant::now() => Some(entry.response.clone()),
+            Some(_) => {
+                entries.remove(did);
+                None
+            }
+            None => None,
+        }
+    }
+
+    fn generation(&self) -> u64 {
+        self.state.lock().unwrap().generation
+    }
+
+    // NOTE: `generation` is the one before resolving `did`. The response may be stale
+    //       if `did` has been invalidated since, but not if only other DIDs have.
+    fn insert(&self, did: &str, response: Option<DidResolutionResponse>, generation: u64) {
+        let ttl = match response.as_ref() {
+            // NOTE: The ttl of the resolution, e.g. of did:webvh, only shortens the configured one,
+            //       so that a DID controller cannot pin its document in our cache.
+            Some(response) => response
+                .method_metadata
+                .ttl
+                .map(|v| Duration::from_secs(v.into()).min(self.ttl))
+                .unwrap_or(self.ttl),
+            None => self.negative_ttl,
+        };
+        if self.capacity == 0 || ttl.is_zero() {
+            return;
+        }
+        let now = Instant::now();
+        let mut state = self.state.lock().unwrap();
+        if state.invalidated_since(did, generation) {
+            return;
+        }
+        let entries = &mut state.entries;
+        if !entries.contains_key(did) && entries.len() >= self.capacity {
+            entries.retain(|_, v| v.expires_at > now);
+            // NOTE: Evicts the entry expiring first, when all of them are still valid.
+            if entries.len() >= self.capacity {
+                let oldest = entries
+                    .iter()
+                    .min_by_key(|(_, v)| v.expires_at)
+                    .map(|(k, _)| k.clone());
+                if let Some(oldest) = oldest {
+                    entries.remove(&oldest);
+                }
+            }
+        }
+        entries.insert(
+            did.to_string(),
+            CacheEntry {
+                response,
+                expires_at: now + ttl,
+            },
+        );
+    }
+
+    pub fn invalidate(&self, did: &str) {
+        let mut state = self.state.lock().unwrap();
+        state.generation += 1;
+        let generation = state.generation;
+        state.invalidated.insert(did.to_string(), generation);
+        state.entries.remove(did);
+    }
+
+    pub fn clear(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.generation += 1;
+        state.cleared = state.generation;
+        state.invalidated.clear();
+        state.entries.clear();
+    }
+
+    pub fn len(&self) -> usize {
+        self.state.lock().unwrap().entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+// NOTE: Errors are not cached. Operations changing a DID invalidate it once they return,
+//       whether they succeed or not, since the node may have applied a failed request.
+#[derive(Clone)]
+pub struct CachedDidRepository<R: DidResolver> {
+    inner: R,
+    cache: DidCache,
+}
+
+impl<R: DidResolver> CachedDidRepository<R> {
+    pub fn new(inner: R, cache: DidCache) -> Self {
+        Self { inner, cache }
+    }
+
+    pub fn invalidate(&self, did: &str) {
+        self.cache.invalidate(did);
+    }
+
+    pub fn cache(&self) -> &DidCache {
+        &self.cache
+    }
+}
+
+impl<R> DidResolver for CachedDidRepository<R>
+where
+    R: DidResolver + Send,
+{
+    type FindIdentifierError = R::FindIdentifierError;
+
+    async fn find_identifier(
+        &self,
+        did: &str,
+    ) -> Result<Option<DidResolutionResponse>, Self::FindIdentifierError> {
+        if let Some(response) = self.cache.get(did) {
+            return Ok(response);
+        }
+        let generation = self.cache.generation();
+        let response = self.inner.find_identifier(did).await?;
+        self.cache.insert(did, response.clone(), generation);
+        Ok(response)
+    }
+}
+
+impl<R> DidRepository for CachedDidRepository<R>
+where
+    R: DidRepository + Send,
+{
+    type CreateIdentifierError = R::CreateIdentifierError;
+    type UpdateIdentifierError = R::UpdateIdentifierError;
+    type RecoverIdentifierError = R::RecoverIdentifierError;
+    type DeactivateIdentifierError = R::DeactivateIdentifierError;
+
+    async fn create_identifier(
+        &self,
+        keyring: KeyPairing,
+    ) -> Result<DidResolutionResponse, Self::CreateIdentifierError> {
+        let response = self.inner.create_identifier(keyring).await?;
+        self.cache.invalidate(&response.did_document.id);
+        Ok(response)
+    }
+
+    async fn update_identifier(
+        &self,
+        did: &str,
+        current_update: &K256KeyPair,
+        keyring: &KeyPairing,
+    ) -> Result<(), Self::UpdateIdentifierError> {
+        let result = self
+            .inner
+            .update_identifier(did, current_update, keyring)
+            .await;
+        self.cache.invalidate(did);
+        result
+    }
+
+    async fn recover_identifier(
+        &self,
+        did: &str,
+        current_recovery: &K256KeyPair,
+        keyring: &KeyPairing,
+    ) -> Result<(), Self::RecoverIdentifierError> {
+        let result = self
+            .inner
+            .recover_identifier(did, current_recovery, keyring)
+            .await;
+        self.cache.invalidate(did);
+        result
+    }
+
+    async fn update_services(
+        &self,
+        did: &str,
+        current_update: &K256KeyPair,
+        next_update: &K256KeyPair,
+        services: Vec<ServiceEndpoint>,
+        remove_ids: Vec<String>,
+    ) -> Result<(), Self::UpdateIdentifierError> {
+        let result = self
+            .inner
+            .update_services(did, current_update, next_update, services, remove_ids)
+            .await;
+        self.cache.invalidate(did);
+        result
+    }
+
+    async fn deactivate_identifier(
+        &self,
+        did: &str,
+        current_recovery: &K256KeyPair,
+    ) -> Result<(), Self::DeactivateIdentifierError> {
+        let result = self
+            .inner
+            .deactivate_identifier(did, current_recovery)
+            .await;
+        self.cache.invalidate(did);
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use rand_core::OsRng;
+
+    use super::*;
+    use crate::did::did_repository::mocks::MockDidRepository;
+    use crate::did::test_utils::create_random_did;
+
+    type MockFindIdentifierError = <MockDidRepository as DidResolver>::FindIdentifierError;
+
+    // Counts find_identifier calls reaching the inner repository.
+    #[derive(Clone)]
+    struct CountingDidRepository {
+        inner: MockDidRepository,
+        ttl: Option<u32>,
+        count: Arc<AtomicUsize>,
+    }
+
+    impl DidResolver for CountingDidRepository {
+        type FindIdentifierError = MockFindIdentifierError;
+        async fn find_identifier(
+            &self,
+            did: &str,
+        ) -> Result<Option<DidResolutionResponse>, MockFindIdentifierError> {
+            self.count.fetch_add(1, Ordering::SeqCst);
+            let mut response = self.inner.find_identifier(did).await?;
+            if let Some(response) = response.as_mut() {
+                response.method_metadata.ttl = self.ttl;
+            }
+            Ok(response)
+        }
+    }
+
+    fn cached_repository(
+        dids: &[&str],
+        ttl: Option<u32>,
+        cache: DidCache,
+    ) -> (CachedDidRepository<CountingDidRepository>, Arc<AtomicUsize>) {
+        let map = dids
+            .iter()
+            .map(|did| (did.to_string(), KeyPairing::create_keyring(OsRng)))
+            .collect::<BTreeMap<_, _>>();
+        let count = Arc::new(AtomicUsize::new(0));
+        let inner = CountingDidRepository {
+            inner: MockDidRepository::from_single(map),
+            ttl,
+            count: count.clone(),
+        };
+        (CachedDidRepository::new(inner, cache), count)
+    }
+
+    #[tokio::test]
+    async fn test_find_identifier_cached() {
+        let did = create_random_did();
+        let unknown = create_random_did();
+        let cache = DidCache::new(16, Duration::from_secs(60), Duration::from_secs(60));
+        let (repository, count) = cached_repository(&[&did], None, cache);
+
+        for _ in 0..3 {
+            assert!(repository.find_identifier(&did).await.unwrap().is_some());
+            assert!(repository
+                .find_identifier(&unknown)
+                .await
+                .unwrap()
+                .is_none());
+        }
+        assert_eq!(count.load(Ordering::SeqCst), 2);
+        assert_eq!(repository.cache().len(), 2);
+
+        repository.invalidate(&did);
+        assert!(repository.find_identifier(&did).await.unwrap().is_some());
+        assert!(repository
+            .find_identifier(&unknown)
+            .await
+            .unwrap()
+            .is_none());
+        assert_eq!(count.load(Ordering::SeqCst), 3);
+
+        repository.cache().clear();
+        assert!(repository.cache().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_find_identifier_ttl() {
+        let did = create_random_did();
+        let unknown = create_random_did();
+
+        // NOTE: A zero ttl is not cached at all.
+        let cache = DidCache::new(16, Duration::ZERO, Duration::ZERO);
+        let (repository, count) = cached_repository(&[&did], None, cache);
+        repository.find_identifier(&did).await.unwrap();
+        repository.find_identifier(&did).await.unwrap();
+        repository.find_identifier(&unknown).await.unwrap();
+        repository.find_identifier(&unknown).await.unwrap();
+        assert_eq!(count.load(Ordering::SeqCst), 4);
+
+        // The ttl of the resolution takes precedence when shorter.
+        let cache = DidCache::new(16, Duration::from_secs(60), Duration::ZERO);
+        let (repository, count) = cached_repository(&[&did], Some(0), cache.clone());
+        repository.find_identifier(&did).await.unwrap();
+        repository.find_identifier(&did).await.unwrap();
+        assert_eq!(count.load(Ordering::SeqCst), 2);
+        assert!(cache.is_empty());
+
+        let cache = DidCache::new(16, Duration::from_secs(60), Duration::ZERO);
+        let (repository, count) = cached_repository(&[&did], Some(60), cache);
+        repository.find_identifier(&did).await.unwrap();
+        repository.find_identifier(&did).await.unwrap();
+        assert_eq!(count.load(Ordering::SeqCst), 1);
+
+        // NOTE: A longer one is clamped to the configured ttl.
+        let cache = DidCache::new(16, Duration::ZERO, Duration::ZERO);
+        let (repository, count) = cached_repository(&[&did], Some(60), cache.clone());
+        repository.find_identifier(&did).await.unwrap();
+        repository.find_identifier(&did).await.unwrap();
+        assert_eq!(count.load(Ordering::SeqCst), 2);
+        assert!(cache.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_invalidate_during_find_identifier() {
+        let did = create_random_did();
+        let cache = DidCache::new(16, Duration::from_secs(60), Duration::from_secs(60));
+        let (repository, _) = cached_repository(&[&did], None, cache.clone());
+        let response = repository.inner.find_identifier(&did).await.unwrap();
+
+        // NOTE: A resolution started before an update must not cache the old document.
+        let generation = cache.generation();
+        cache.invalidate(&did);
+        cache.insert(&did, response.clone(), generation);
+        assert!(cache.get(&did).is_none());
+
+        cache.insert(&did, response.clone(), cache.generation());
+        assert!(cache.get(&did).is_some());
+
+        // NOTE: Invalidating another DID does not discard it.
+        let generation = cache.generation();
+        cache.invalidate(&create_random_did());
+        cache.invalidate(&did);
+        cache.insert(&did, response.clone(), generation);
+        assert!(cache.get(&did).is_none());
+        let generation = cache.generation();
+        cache.invalidate(&create_random_did());
+        cache.insert(&did, response.clone(), generation);
+        assert!(cache.get(&did).is_some());
+
+        let generation = cache.generation();
+        cache.clear();
+        cache.insert(&did, response, generation);
+        assert!(cache.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_find_identifier_capacity() {
+        let dids = (0..3).map(|_| create_random_did()).collect::<Vec<_>>();
+        let dids = dids.iter().map(String::as_str).collect::<Vec<_>>();
+        let cache = DidCache::new(2, Duration::from_secs(60), Duration::from_secs(60));
+        let (repository, count) = cached_repository(&dids, None, cache);
+
+        for did in dids.iter() {
+            repository.find_identifier(did).await.unwrap();
+        }
+        assert_eq!(repository.cache().len(), 2);
+
+        // NOTE: The first one expires first, so it was evicted.
+        repository.find_identifier(dids[2]).await.unwrap();
+        assert_eq!(count.load(Ordering::SeqCst), 3);
+        repository.find_identifier(dids[0]).await.unwrap();
+        assert_eq!(count.load(Ordering::SeqCst), 4);
+    }
+}
@@ -0,0 +1,608 @@
+// DID documents as defined by DID Core, shared by every DID method.
+// https://www.w3.org/TR/did-core/#core-properties
+
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_json::{Map, Value};
+
+use super::sidetree::payload::{DidPublicKey, ServiceEndpoint, SidetreeDidDocument};
+use crate::keyring::jwk::Jwk;
+use crate::keyring::multikey::{Multikey, MultikeyError, MultikeyToJwkError};
+
+pub const DID_CONTEXT: &str = "https://www.w3.org/ns/did/v1";
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct DidDocument {
+    #[serde(rename = "@context", default, deserialize_with = "one_or_many")]
+    pub context: Vec<String>,
+    pub id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub deactivated: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub verification_method: Option<Vec<VerificationMethod>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub authentication: Option<Vec<VerificationRelationship>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub assertion_method: Option<Vec<VerificationRelationship>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub key_agreement: Option<Vec<VerificationRelationship>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub capability_invocation: Option<Vec<VerificationRelationship>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub capability_delegation: Option<Vec<VerificationRelationship>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub service: Option<Vec<Service>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub also_known_as: Option<Vec<String>>,
+    #[serde(
+        default,
+        deserialize_with = "option_one_or_many",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub controller: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub created: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct VerificationMethod {
+    pub id: String,
+    #[serde(rename = "type")]
+    pub r#type: String,
+    pub controller: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub public_key_multibase: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub public_key_jwk: Option<Jwk>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub public_key_base58: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub blockchain_account_id: Option<String>,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum PublicKeyDecodeError {
+    #[error("no public key in {0}")]
+    NoPublicKey(String),
+    #[error("invalid base58: {0}")]
+    Base58(#[from] bs58::decode::Error),
+    #[error("Failed to decode public key: {0}")]
+    Multikey(#[from] MultikeyError),
+    #[error("Failed to convert to JWK: {0}")]
+    Jwk(#[from] MultikeyToJwkError),
+}
+
+impl VerificationMethod {
+    // The public key as JWK, whichever of publicKeyJwk, publicKeyMultibase or publicKeyBase58.
+    // NOTE: publicKeyBase58 has no multicodec prefix, so its curve is told by the type.
+    pub fn public_key(&self) -> Result<Jwk, PublicKeyDecodeError> {
+        if let Some(jwk) = &self.public_key_jwk {
+            return Ok(jwk.clone());
+        }
+        let key = match (&self.public_key_multibase, &self.public_key_base58) {
+            (Some(multibase), _) => Multikey::decode(multibase)?,
+            (None, Some(base58)) => {
+                Multikey::from_key_type(&self.r#type, &bs58::decode(base58).into_vec()?)?
+            }
+            (None, None) => return Err(PublicKeyDecodeError::NoPublicKey(self.id.clone())),
+        };
+        Ok(key.to_jwk()?)
+    }
+}
+
+// A verification method referenced by its id or embedded in the relationship.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum VerificationRelationship {
+    Reference(String),
+    Embedded(Box<VerificationMethod>),
+}
+
+impl VerificationRelationship {
+    pub fn id(&self) -> &str {
+        match self {
+            VerificationRelationship::Reference(id) => id,
+            VerificationRelationship::Embedded(method) => &method.id,
+        }
+    }
+}
+
+impl From<String> for VerificationRelationship {
+    fn from(id: String) -> Self {
+        VerificationRelationship::Reference(id)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum VerificationRelationshipKind {
+    Authentication,
+    AssertionMethod,
+    KeyAgreement,
+    CapabilityInvocation,
+    CapabilityDelegation,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Service {
+    pub id: String,
+    #[serde(rename = "type")]
+    pub r#type: String,
+    pub service_endpoint: ServiceEndpointValue,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+}
+
+// A URI, a map, e.g. a DIDComm endpoint with `accept` and `routingKeys`, or a set of them.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum ServiceEndpointValue {
+    Uri(String),
+    Map(Map<String, Value>),
+    Set(Vec<ServiceEndpointValue>),
+}
+
+impl From<String> for ServiceEndpointValue {
+    fn from(value: String) -> Self {
+        ServiceEndpointValue::Uri(value)
+    }
+}
+
+impl From<&str> for ServiceEndpointValue {
+    fn from(value: &str) -> Self {
+        ServiceEndpointValue::Uri(value.to_string())
+    }
+}
+
+// `@context` and `controller` may be a single value.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum OneOrMany {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl From<OneOrMany> for Vec<String> {
+    fn from(value: OneOrMany) -> Self {
+        match value {
+            OneOrMany::One(v) => vec![v],
+            OneOrMany::Many(v) => v,
+        }
+    }
+}
+
+fn one_or_many<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<String>, D::Error> {
+    Ok(OneOrMany::deserialize(deserializer)?.into())
+}
+
+fn option_one_or_many<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<Vec<String>>, D::Error> {
+    Ok(Option::<OneOrMany>::deserialize(deserializer)?.map(Into::into))
+}
+
+impl DidDocument {
+    pub fn new(id: impl Into<String>) -> Self {
+        Self {
+            context: vec![DID_CONTEXT.to_string()],
+            id: id.into(),
+            verification_method: None,
+            authentication: None,
+            assertion_method: None,
+            key_agreement: None,
+            capability_invocation: None,
+            capability_delegation: None,
+            service: None,
+            also_known_as: None,
+            controller: None,
+            created: None,
+            deactivated: None,
+        }
+    }
+
+    // NOTE: Ids may be relative to the document, e.g. `#signingKey`.
+    fn is_same_id(&self, a: &str, b: &str) -> bool {
+        let absolute = |v: &str| match v.strip_prefix('#') {
+            Some(fragment) => format!("{}#{}", self.id, fragment),
+            None => v.to_string(),
+        };
+        absolute(a) == absolute(b)
+    }
+
+    fn relationship_mut(
+        &mut self,
+        kind: VerificationRelationshipKind,
+    ) -> &mut Option<Vec<VerificationRelationship>> {
+        match kind {
+            VerificationRelationshipKind::Authentication => &mut self.authentication,
+            VerificationRelationshipKind::AssertionMethod => &mut self.assertion_method,
+            VerificationRelationshipKind::KeyAgreement => &mut self.key_agreement,
+            VerificationRelationshipKind::CapabilityInvocation => &mut self.capability_invocation,
+            VerificationRelationshipKind::CapabilityDelegation => &mut self.capability_delegation,
+        }
+    }
+
+    pub fn relationship(
+        &self,
+        kind: VerificationRelationshipKind,
+    ) -> Option<&Vec<VerificationRelationship>> {
+        match kind {
+            VerificationRelationshipKind::Authentication => self.authentication.as_ref(),
+            VerificationRelationshipKind::AssertionMethod => self.assertion_method.as_ref(),
+            VerificationRelationshipKind::KeyAgreement => self.key_agreement.as_ref(),
+            VerificationRelationshipKind::CapabilityInvocation => {
+                self.capability_invocation.as_ref()
+            }
+            VerificationRelationshipKind::CapabilityDelegation => {
+                self.capability_delegation.as_ref()
+            }
+        }
+    }
+
+    // NOTE: Verification methods embedded in relationships are found as well.
+    pub fn find_verification_method(&self, id: &str) -> Option<&VerificationMethod> {
+        let embedded = [
+            &self.authentication,
+            &self.assertion_method,
+            &self.key_agreement,
+            &self.capability_invocation,
+            &self.capability_delegation,
+        ]
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter_map(|v| match v {
+            VerificationRelationship::Embedded(method) => Some(method.as_ref()),
+            VerificationRelationship::Reference(_) => None,
+        });
+        self.verification_method
+            .iter()
+            .flatten()
+            .chain(embedded)
+            .find(|v| self.is_same_id(&v.id, id))
+    }
+
+    // The verification method `id` only if it is authorized for `kind`.
+    pub fn find_verification_method_in(
+        &self,
+        kind: VerificationRelationshipKind,
+        id: &str,
+    ) -> Option<&VerificationMethod> {
+        self.verification_methods(kind)
+            .into_iter()
+            .find(|v| self.is_same_id(&v.id, id))
+    }
+
+    pub fn find_service(&self, id: &str) -> Option<&Service> {
+        self.service
+            .iter()
+            .flatten()
+            .find(|v| self.is_same_id(&v.id, id))
+    }
+
+    // Verification methods of `kind` in order, with references resolved.
+    // NOTE: References to missing verification methods are skipped.
+    pub fn verification_methods(
+        &self,
+        kind: VerificationRelationshipKind,
+    ) -> Vec<&VerificationMethod> {
+        self.relationship(kind)
+            .into_iter()
+            .flatten()
+            .filter_map(|v| match v {
+                VerificationRelationship::Reference(id) => self.find_verification_method(id),
+                VerificationRelationship::Embedded(method) => Some(method.as_ref()),
+            })
+            .collect()
+    }
+}
+
+impl From<ServiceEndpoint> for Service {
+    fn from(value: ServiceEndpoint) -> Self {
+        Service {
+            id: value.id,
+            r#type: value.r#type,
+            service_endpoint: value.service_endpoint,
+            description: value.description,
+        }
+    }
+}
+
+// Sidetree key purposes, including the ones of older nodes, e.g. "auth".
+// https://identity.foundation/sidetree/spec/#add-public-keys
+fn purpose_relationship(purpose: &str) -> Option<VerificationRelationshipKind> {
+    match purpose {
+        "authentication" | "auth" => Some(VerificationRelationshipKind::Authentication),
+        "assertionMethod" | "assertion" => Some(VerificationRelationshipKind::AssertionMethod),
+        "keyAgreement" | "agreement" => Some(VerificationRelationshipKind::KeyAgreement),
+        "capabilityInvocation" | "invocation" => {
+            Some(VerificationRelationshipKind::CapabilityInvocation)
+        }
+        "capabilityDelegation" | "delegation" => {
+            Some(VerificationRelationshipKind::CapabilityDelegation)
+        }
+        _ => None,
+    }
+}
+
+// NOTE: The relationships of a key are its purposes and whether the document lists it under
+//       authentication. Older nodes list every "auth" key there, X25519 ones included, but an
+//       X25519 key can only be used for key agreement, so it is always a key agreement key.
+impl From<SidetreeDidDocument> for DidDocument {
+    fn from(document: SidetreeDidDocument) -> Self {
+        let mut did_document = DidDocument::new(document.id);
+        let authentication: Vec<String> = document
+            .authentication
+            .unwrap_or_default()
+            .into_iter()
+            .map(|v| match v.contains('#') {
+                true => v,
+                false => format!("#{}", v),
+            })
+            .collect();
+        let mut methods = vec![];
+        for key in document.public_key.unwrap_or_default() {
+            let DidPublicKey {
+                id,
+                r#type,
+                public_key_jwk,
+                public_key_multibase,
+                public_key_base58,
+                purposes,
+                ..
+            } = key;
+            let method = VerificationMethod {
+                id,
+                r#type,
+                controller: did_document.id.clone(),
+                public_key_multibase,
+                public_key_jwk,
+                public_key_base58,
+                blockchain_account_id: None,
+            };
+            let mut kinds: Vec<_> = purposes
+                .iter()
+                .flatten()
+                .filter_map(|v| purpose_relationship(v))
+                .collect();
+            if authentication
+                .iter()
+                .any(|v| did_document.is_same_id(v, &method.id))
+            {
+                kinds.push(VerificationRelationshipKind::Authentication);
+            }
+            if method.public_key().is_ok_and(|jwk| jwk.crv() == "X25519") {
+                kinds = vec![VerificationRelationshipKind::KeyAgreement];
+            }
+            kinds.sort();
+            kinds.dedup();
+            for kind in kinds {
+                did_document
+                    .relationship_mut(kind)
+                    .get_or_insert_with(Vec::new)
+                    .push(VerificationRelationship::Reference(method.id.clone()));
+            }
+            methods.push(method);
+        }
+        did_document.verification_method = Some(methods);
+        did_document.service = document
+            .service
+            .map(|v| v.into_iter().map(Service::from).collect());
+        did_document
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand_core::OsRng;
+
+    use super::*;
+    use crate::did::did_repository::{get_encrypt_key, get_sign_key, get_verification_key};
+    use crate::did::sidetree::payload::DidPublicKey;
+    use crate::keyring::jwk::data_encoding::BASE64URL_NOPAD;
+    use crate::keyring::keypair::{KeyPair, KeyPairing, SignPublicKey};
+
+    #[test]
+    fn test_did_document_serde() {
+        let did_document: DidDocument = serde_json::from_str(
+            r##"{
+                "@context": "https://www.w3.org/ns/did/v1",
+                "id": "did:web:example.com",
+                "controller": "did:web:example.com",
+                "verificationMethod": [{
+                    "id": "did:web:example.com#key-1",
+                    "type": "Multikey",
+                    "controller": "did:web:example.com",
+                    "publicKeyMultibase": "z6MkijyunEqPi7hzgJirb4tQLjztCPbJeeZvXEySuzbY6MLv"
+                }],
+                "authentication": ["#key-1"],
+                "assertionMethod": [{
+                    "id": "did:web:example.com#key-2",
+                    "type": "Multikey",
+                    "controller": "did:web:example.com",
+                    "publicKeyMultibase": "z6MkoSFjacZb7R82htx8n1AkpgLQWR7CA6rigsc2VH9acLuF"
+                }]
+            }"##,
+        )
+        .unwrap();
+        assert_eq!(did_document.context, vec![DID_CONTEXT.to_string()]);
+        assert_eq!(
+            did_document.controller,
+            Some(vec!["did:web:example.com".to_string()])
+        );
+
+        let authentication =
+            did_document.verification_methods(VerificationRelationshipKind::Authentication);
+        assert_eq!(authentication.len(), 1);
+        assert_eq!(authentication[0].id, "did:web:example.com#key-1");
+        let assertion =
+            did_document.verification_methods(VerificationRelationshipKind::AssertionMethod);
+        assert_eq!(assertion[0].id, "did:web:example.com#key-2");
+        assert!(did_document
+            .verification_methods(VerificationRelationshipKind::KeyAgreement)
+            .is_empty());
+
+        let json = serde_json::to_value(&did_document).unwrap();
+        assert_eq!(json["authentication"][0], "#key-1");
+        assert_eq!(json["assertionMethod"][0]["type"], "Multikey");
+    }
+
+    #[test]
+    fn test_from_sidetree_did_document() {
+        let keyring = KeyPairing::create_keyring(OsRng);
+        let document = SidetreeDidDocument {
+            id: "did:nodex:test:abc".to_string(),
+            public_key: Some(vec![
+                DidPublicKey {
+                    id: "#signingKey".to_string(),
+                    controller: String::new(),
+                    r#type: "EcdsaSecp256k1VerificationKey2019".to_string(),
+                    public_key_jwk: Some(keyring.sign.get_public_key().try_into().unwrap()),
+                    public_key_multibase: None,
+                    public_key_base58: None,
+                    purposes: None,
+                },
+                DidPublicKey {
+                    id: "#encryptionKey".to_string(),
+                    controller: String::new(),
+                    r#type: "X25519KeyAgreementKey2019".to_string(),
+                    public_key_jwk: None,
+                    public_key_multibase: None,
+                    public_key_base58: Some(
+                        bs58::encode(keyring.encrypt.get_public_key().as_bytes()).into_string(),
+                    ),
+                    purposes: None,
+                },
+                DidPublicKey {
+                    id: "#assertionKey".to_string(),
+                    controller: String::new(),
+                    r#type: "EcdsaSecp256k1VerificationKey2019".to_string(),
+                    public_key_jwk: Some(keyring.update.get_public_key().try_into().unwrap()),
+                    public_key_multibase: None,
+                    public_key_base58: None,
+                    purposes: Some(vec!["assertionMethod".to_string()]),
+                },
+                DidPublicKey {
+                    id: "#recoveryKey".to_string(),
+                    controller: String::new(),
+                    r#type: "EcdsaSecp256k1VerificationKey2019".to_string(),
+                    public_key_jwk: Some(keyring.recovery.get_public_key().try_into().unwrap()),
+                    public_key_multibase: None,
+                    public_key_base58: None,
+                    purposes: Some(vec!["general".to_string()]),
+                },
+            ]),
+            service: Some(vec![serde_json::from_value(serde_json::json!({
+                "id": "#didcomm",
+                "type": "DIDCommMessaging",
+                "serviceEndpoint": {
+                    "uri": "https://example.com/didcomm",
+                    "routingKeys": ["did:example:123#key-1"]
+                }
+            }))
+            .unwrap()]),
+            authentication: Some(vec!["signingKey".to_string()]),
+        };
+        let did_document = DidDocument::from(document);
+        assert_eq!(
+            did_document
+                .find_verification_method("#signingKey")
+                .unwrap()
+                .controller,
+            "did:nodex:test:abc"
+        );
+        let ids = |kind| {
+            did_document
+                .verification_methods(kind)
+                .into_iter()
+                .map(|v| v.id.as_str())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            ids(VerificationRelationshipKind::Authentication),
+            ["#signingKey"]
+        );
+        assert_eq!(
+            ids(VerificationRelationshipKind::AssertionMethod),
+            ["#assertionKey"]
+        );
+        // NOTE: A key without relationships is only a verification method.
+        assert!(did_document
+            .find_verification_method("#recoveryKey")
+            .is_some());
+        let agreement =
+            did_document.verification_methods(VerificationRelationshipKind::KeyAgreement);
+        assert_eq!(agreement.len(), 1);
+        assert_eq!(agreement[0].id, "#encryptionKey");
+        assert!(did_document
+            .find_verification_method("did:nodex:test:abc#encryptionKey")
+            .is_some());
+        let service = did_document.find_service("#didcomm").unwrap();
+        assert_eq!(
+            service.service_endpoint,
+            ServiceEndpointValue::Map(
+                serde_json::from_value(serde_json::json!({
+                    "uri": "https://example.com/didcomm",
+                    "routingKeys": ["did:example:123#key-1"]
+                }))
+                .unwrap()
+            )
+        );
+    }
+
+    #[test]
+    fn test_public_key_formats() {
+        let keyring = KeyPairing::create_keyring(OsRng);
+        let sign = Multikey::from(keyring.sign.get_public_key());
+        let encrypt = keyring.encrypt.get_public_key();
+        let did_document: DidDocument = serde_json::from_value(serde_json::json!({
+            "id": "did:example:123",
+            "verificationMethod": [{
+                "id": "#key-1",
+                "type": "Multikey",
+                "controller": "did:example:123",
+                "publicKeyMultibase": sign.encode()
+            }, {
+                "id": "#key-2",
+                "type": "X25519KeyAgreementKey2019",
+                "controller": "did:example:123",
+                "publicKeyBase58": bs58::encode(encrypt.as_bytes()).into_string()
+            }, {
+                "id": "#key-3",
+                "type": "JsonWebKey2020",
+                "controller": "did:example:123",
+                "publicKeyJwk": {
+                    "kty": "EC",
+                    "crv": "secp256k1",
+                    "x": BASE64URL_NOPAD.encode(&sign.to_bytes())
+                }
+            }, {
+                "id": "#key-4",
+                "type": "Ed25519VerificationKey2018",
+                "controller": "did:example:123",
+                "publicKeyBase58": "invalid0"
+            }],
+            "authentication": ["#key-4", "#key-1", "#key-3"],
+            "keyAgreement": ["#key-2"]
+        }))
+        .unwrap();
+
+        assert_eq!(
+            get_sign_key(&did_document).unwrap(),
+            keyring.sign.get_public_key()
+        );
+        assert_eq!(get_encrypt_key(&did_document).unwrap(), encrypt);
+        assert!(matches!(
+            get_verification_key(&did_document, "did:example:123#key-3").unwrap(),
+            SignPublicKey::Secp256k1(_)
+        ));
+        assert!(matches!(
+            did_document
+                .find_verification_method("#key-4")
+                .unwrap()
+                .public_key(),
+            Err(PublicKeyDecodeError::Base58(_))
+        ));
+    }
+}
@@ -2,21 +2,31 @@ use std::convert::TryInto;
 
 use http::StatusCode;
 
+use super::did_document::{DidDocument, VerificationRelationshipKind};
+use super::did_url::DidUrl;
+use super::resolution::{ResolutionError, ResolutionErrorCode};
 use super::sidetree::{
     client::SidetreeHttpClient,
+    multihash,
     payload::{
-        did_create_payload, DidDocument, DidPatchDocument, DidResolutionResponse, ToPublicKey,
+        did_create_payload, did_deactivate_payload, did_long_form, did_recover_payload,
+        did_update_payload, get_did_suffix, get_short_form_did, parse_did, resolve_long_form_did,
+        DidAction, DidCreateResponse, DidLongFormError, DidPatchDocument, DidResolutionResponse,
+        PublicKeyPayload, ServiceEndpoint, SidetreeDidResolutionResponse, ToPublicKey,
     },
 };
 use crate::keyring::{
     jwk::Jwk,
-    keypair::{KeyPair, KeyPairing},
+    keypair::{K256KeyPair, KeyPair, KeyPairing, SignKeyPair, SignPublicKey},
+    multihash::HashAlgorithm,
 };
 
 #[derive(Debug, thiserror::Error)]
 pub enum CreateIdentifierError<StudioClientError: std::error::Error> {
     #[error("Failed to convert to JWK: {0}")]
     Jwk(#[from] crate::keyring::jwk::K256ToJwkError),
+    #[error("Failed to convert to JWK: {0}")]
+    SignKeyJwk(#[from] crate::keyring::jwk::SignKeyToJwkError),
     #[error("Failed to build operation payload: {0}")]
     PayloadBuildFailed(#[from] crate::did::sidetree::payload::DidCreatePayloadError),
     #[error("Failed to parse body: {0}")]
@@ -27,16 +37,75 @@ pub enum CreateIdentifierError<StudioClientError: std::error::Error> {
     SidetreeHttpClient(StudioClientError),
 }
 
+#[derive(Debug, thiserror::Error)]
+pub enum UpdateIdentifierError<StudioClientError: std::error::Error> {
+    #[error("Failed to convert to JWK: {0}")]
+    SignKeyJwk(#[from] crate::keyring::jwk::SignKeyToJwkError),
+    #[error("Failed to build operation payload: {0}")]
+    PayloadBuildFailed(#[from] crate::did::sidetree::payload::DidUpdatePayloadError),
+    #[error("Failed to update identifier. response: {0}")]
+    SidetreeRequestFailed(String),
+    #[error("Failed to send request: {0}")]
+    SidetreeHttpClient(StudioClientError),
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum RecoverIdentifierError<StudioClientError: std::error::Error> {
+    #[error("Failed to convert to JWK: {0}")]
+    SignKeyJwk(#[from] crate::keyring::jwk::SignKeyToJwkError),
+    #[error("Failed to build operation payload: {0}")]
+    PayloadBuildFailed(#[from] crate::did::sidetree::payload::DidRecoverPayloadError),
+    #[error("Failed to recover identifier. response: {0}")]
+    SidetreeRequestFailed(String),
+    #[error("Failed to resolve the current document: {0}")]
+    FindIdentifier(#[from] FindIdentifierError<StudioClientError>),
+    #[error("Failed to send request: {0}")]
+    SidetreeHttpClient(StudioClientError),
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum DeactivateIdentifierError<StudioClientError: std::error::Error> {
+    #[error("Failed to build operation payload: {0}")]
+    PayloadBuildFailed(#[from] crate::did::sidetree::payload::DidDeactivatePayloadError),
+    #[error("Failed to deactivate identifier. response: {0}")]
+    SidetreeRequestFailed(String),
+    #[error("Failed to send request: {0}")]
+    SidetreeHttpClient(StudioClientError),
+}
+
 #[derive(Debug, thiserror::Error)]
 pub enum FindIdentifierError<StudioClientError: std::error::Error> {
     #[error("Failed to send request to sidetree: {0}")]
     SidetreeRequestFailed(String),
     #[error("Failed to parse body: {0}")]
     BodyParse(#[from] serde_json::Error),
+    #[error("Failed to resolve long-form did: {0}")]
+    LongForm(#[from] DidLongFormError),
+    #[error("did is deactivated: {0}")]
+    Deactivated(String),
     #[error("Failed to send request: {0}")]
     SidetreeHttpClient(StudioClientError),
 }
 
+impl<E: std::error::Error> ResolutionError for FindIdentifierError<E> {
+    fn error_code(&self) -> ResolutionErrorCode {
+        match self {
+            FindIdentifierError::Deactivated(_) => ResolutionErrorCode::Deactivated,
+            _ => ResolutionErrorCode::InternalError,
+        }
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum LongFormIdentifierError {
+    #[error("Failed to convert to JWK: {0}")]
+    SignKeyJwk(#[from] crate::keyring::jwk::SignKeyToJwkError),
+    #[error("Failed to build operation payload: {0}")]
+    PayloadBuildFailed(#[from] crate::did::sidetree::payload::DidCreatePayloadError),
+    #[error("Failed to build long-form did: {0}")]
+    LongForm(#[from] DidLongFormError),
+}
+
 #[derive(Debug, thiserror::Error)]
 pub enum GetPublicKeyError {
     #[error("Failed to get public key: {0}")]
@@ -44,94 +113,295 @@ pub enum GetPublicKeyError {
     #[error("Failed to convert from JWK: {0}")]
     JwkToK256(#[from] crate::keyring::jwk::JwkToK256Error),
     #[error("Failed to convert from JWK: {0}")]
+    JwkToP256(#[from] crate::keyring::jwk::JwkToP256Error),
+    #[error("Failed to convert from JWK: {0}")]
     JwkToX25519(#[from] crate::keyring::jwk::JwkToX25519Error),
+    #[error("Failed to convert from JWK: {0}")]
+    JwkToEd25519(#[from] crate::keyring::jwk::JwkToEd25519Error),
+    #[error("Failed to decode public key: {0}")]
+    PublicKeyDecode(#[from] super::did_document::PublicKeyDecodeError),
 }
 
-fn get_key(key_type: &str, did_document: &DidDocument) -> Result<Jwk, GetPublicKeyError> {
-    let did = &did_document.id;
-    let public_key = did_document
-        .public_key
-        .clone()
-        .and_then(|pks| pks.into_iter().find(|pk| pk.id == key_type))
-        .ok_or(GetPublicKeyError::PublicKeyNotFound(did.to_string()))?;
-    Ok(public_key.public_key_jwk)
+// The first public key of `kinds` in order, whose curve is one of `crvs`.
+// NOTE: Keys in any format are converted to JWK, and undecodable ones are skipped.
+fn get_key(
+    did_document: &DidDocument,
+    kinds: &[VerificationRelationshipKind],
+    crvs: &[&str],
+) -> Result<Jwk, GetPublicKeyError> {
+    kinds
+        .iter()
+        .flat_map(|kind| did_document.verification_methods(*kind))
+        .filter_map(|v| v.public_key().ok())
+        .find(|jwk| crvs.contains(&jwk.crv()))
+        .ok_or(GetPublicKeyError::PublicKeyNotFound(
+            did_document.id.to_string(),
+        ))
 }
 
-pub fn get_sign_key(did_document: &DidDocument) -> Result<k256::PublicKey, GetPublicKeyError> {
-    let public_key = get_key("#signingKey", did_document)?;
-    Ok(public_key.try_into()?)
+const SIGN_KEY_KINDS: [VerificationRelationshipKind; 2] = [
+    VerificationRelationshipKind::AssertionMethod,
+    VerificationRelationshipKind::Authentication,
+];
+
+fn to_sign_key(public_key: Jwk) -> Result<SignPublicKey, GetPublicKeyError> {
+    match public_key.crv() {
+        "P-256" => Ok(SignPublicKey::Secp256r1(public_key.try_into()?)),
+        "Ed25519" => Ok(SignPublicKey::Ed25519(public_key.try_into()?)),
+        _ => Ok(SignPublicKey::Secp256k1(public_key.try_into()?)),
+    }
+}
+
+pub fn get_sign_key(did_document: &DidDocument) -> Result<SignPublicKey, GetPublicKeyError> {
+    to_sign_key(get_key(
+        did_document,
+        &SIGN_KEY_KINDS,
+        &["secp256k1", "P-256", "Ed25519"],
+    )?)
+}
+
+// The sign key referred to by `verification_method`, e.g. the one of a proof.
+// NOTE: It must be a DID URL of `did_document` authorized for assertion or authentication.
+pub fn get_verification_key(
+    did_document: &DidDocument,
+    verification_method: &str,
+) -> Result<SignPublicKey, GetPublicKeyError> {
+    let not_found = || GetPublicKeyError::PublicKeyNotFound(verification_method.to_string());
+    let url: DidUrl = verification_method.parse().map_err(|_| not_found())?;
+    if url.get_fragment().is_none() || url.get_did().to_string() != did_document.id {
+        return Err(not_found());
+    }
+    let method = SIGN_KEY_KINDS
+        .iter()
+        .find_map(|kind| did_document.find_verification_method_in(*kind, verification_method))
+        .ok_or_else(not_found)?;
+    to_sign_key(method.public_key()?)
+}
+
+// The fragment of the verification method of `sign_key` in `did_document`,
+// e.g. for the `verificationMethod` of a proof made with it.
+// NOTE: Each method names the sign key differently, e.g. did:peer numbers the keys.
+pub fn get_sign_key_id<'a>(
+    did_document: &'a DidDocument,
+    sign_key: &SignPublicKey,
+) -> Result<&'a str, GetPublicKeyError> {
+    SIGN_KEY_KINDS
+        .iter()
+        .flat_map(|kind| did_document.verification_methods(*kind))
+        .find(|v| {
+            v.public_key()
+                .ok()
+                .and_then(|v| to_sign_key(v).ok())
+                .is_some_and(|v| v == *sign_key)
+        })
+        .map(|v| v.id.rsplit_once('#').map_or(v.id.as_str(), |(_, v)| v))
+        .ok_or(GetPublicKeyError::PublicKeyNotFound(
+            did_document.id.to_string(),
+        ))
 }
 
 pub fn get_encrypt_key(
     did_document: &DidDocument,
 ) -> Result<x25519_dalek::PublicKey, GetPublicKeyError> {
-    let public_key = get_key("#encryptionKey", did_document)?;
+    let public_key = get_key(
+        did_document,
+        &[VerificationRelationshipKind::KeyAgreement],
+        &["X25519"],
+    )?;
     Ok(public_key.try_into()?)
 }
 
+fn to_public_keys(
+    keyring: &KeyPairing,
+) -> Result<Vec<PublicKeyPayload>, crate::keyring::jwk::SignKeyToJwkError> {
+    // https://w3c.github.io/did-spec-registries/#assertionmethod
+    // FIXME: This purpose property is strange...
+    //        I guess the sidetree protocol this impl uses is too old.
+    // https://identity.foundation/sidetree/spec/#add-public-keys
+    // vec!["assertionMethod".to_string()],
+    let sign_key_type = match keyring.sign {
+        SignKeyPair::Secp256k1(_) => "EcdsaSecp256k1VerificationKey2019",
+        SignKeyPair::Secp256r1(_) => "EcdsaSecp256r1VerificationKey2019",
+    };
+    let sign = keyring.sign.get_public_key().to_public_key(
+        sign_key_type.to_string(),
+        "signingKey".to_string(),
+        vec!["auth".to_string(), "general".to_string()],
+    )?;
+    // vec!["keyAgreement".to_string()]
+    let enc = keyring
+        .encrypt
+        .get_public_key()
+        .to_public_key(
+            "X25519KeyAgreementKey2019".to_string(),
+            "encryptionKey".to_string(),
+            vec!["auth".to_string(), "general".to_string()],
+        )
+        .unwrap();
+    Ok(vec![sign, enc])
+}
+
+fn create_document(
+    keyring: &KeyPairing,
+) -> Result<DidPatchDocument, crate::keyring::jwk::SignKeyToJwkError> {
+    Ok(DidPatchDocument {
+        public_keys: to_public_keys(keyring)?,
+        service_endpoints: vec![],
+    })
+}
+
+// The long-form of `did` created with `keyring`, usable before its creation is anchored.
+// NOTE: The create operation is hashed with the algorithm of the DID suffix.
+pub fn long_form_identifier(
+    did: &str,
+    keyring: &KeyPairing,
+) -> Result<String, LongFormIdentifierError> {
+    let hash_algorithm = parse_did(did)
+        .and_then(|(_, method_specific_id)| get_did_suffix(&method_specific_id))
+        .and_then(|suffix| multihash::encoded_algorithm(&suffix))
+        .unwrap_or_default();
+    let payload = did_create_payload(
+        create_document(keyring)?,
+        keyring.update.get_public_key(),
+        keyring.recovery.get_public_key(),
+        hash_algorithm,
+    )?;
+    Ok(did_long_form(did, &payload)?)
+}
+
 #[trait_variant::make(Send)]
-pub trait DidRepository: Sync {
+pub trait DidResolver: Sync {
+    type FindIdentifierError: std::error::Error + ResolutionError + Send + Sync;
+    async fn find_identifier(
+        &self,
+        did: &str,
+    ) -> Result<Option<DidResolutionResponse>, Self::FindIdentifierError>;
+}
+
+// NOTE: Only the Sidetree repository manages DIDs; the other methods are resolved only.
+#[trait_variant::make(Send)]
+pub trait DidRepository: DidResolver {
     type CreateIdentifierError: std::error::Error + Send + Sync;
-    type FindIdentifierError: std::error::Error + Send + Sync;
+    type UpdateIdentifierError: std::error::Error + Send + Sync;
+    type RecoverIdentifierError: std::error::Error + Send + Sync;
+    type DeactivateIdentifierError: std::error::Error + Send + Sync;
     async fn create_identifier(
         &self,
         keyring: KeyPairing,
     ) -> Result<DidResolutionResponse, Self::CreateIdentifierError>;
-    async fn find_identifier(
+    // Replaces the sign and encrypt keys with those of `keyring` and commits to its update key.
+    // `current_update` must be the key pair of the current update commitment.
+    async fn update_identifier(
         &self,
         did: &str,
-    ) -> Result<Option<DidResolutionResponse>, Self::FindIdentifierError>;
+        current_update: &K256KeyPair,
+        keyring: &KeyPairing,
+    ) -> Result<(), Self::UpdateIdentifierError>;
+    // Replaces the whole document with the keys of `keyring` and commits to its update and recovery keys.
+    // `current_recovery` must be the key pair of the current recovery commitment.
+    async fn recover_identifier(
+        &self,
+        did: &str,
+        current_recovery: &K256KeyPair,
+        keyring: &KeyPairing,
+    ) -> Result<(), Self::RecoverIdentifierError>;
+    // Adds `services`, replacing those with the same id, removes the services of `remove_ids`
+    // and commits to `next_update`.
+    // `current_update` must be the key pair of the current update commitment.
+    async fn update_services(
+        &self,
+        did: &str,
+        current_update: &K256KeyPair,
+        next_update: &K256KeyPair,
+        services: Vec<ServiceEndpoint>,
+        remove_ids: Vec<String>,
+    ) -> Result<(), Self::UpdateIdentifierError>;
+    async fn deactivate_identifier(
+        &self,
+        did: &str,
+        current_recovery: &K256KeyPair,
+    ) -> Result<(), Self::DeactivateIdentifierError>;
 }
 
 #[derive(Clone)]
 pub struct DidRepositoryImpl<C: SidetreeHttpClient> {
     client: C,
+    hash_algorithm: HashAlgorithm,
 }
 
 impl<C: SidetreeHttpClient> DidRepositoryImpl<C> {
     pub fn new(client: C) -> Self {
-        Self { client }
+        Self {
+            client,
+            hash_algorithm: HashAlgorithm::default(),
+        }
+    }
+
+    // `hashAlgorithmInMultihashCode` of the Sidetree node, for commitments and delta hashes.
+    pub fn hash_algorithm(mut self, algorithm: HashAlgorithm) -> Self {
+        self.hash_algorithm = algorithm;
+        self
     }
 }
 
-impl<C> DidRepository for DidRepositoryImpl<C>
+impl<C> DidRepositoryImpl<C>
 where
     C: SidetreeHttpClient + Send + Sync,
     C::Error: Send + Sync,
+{
+    async fn post_update(
+        &self,
+        patches: Vec<DidAction>,
+        did: &str,
+        current_update: &K256KeyPair,
+        next_update: k256::PublicKey,
+    ) -> Result<(), UpdateIdentifierError<C::Error>> {
+        let payload = did_update_payload(
+            patches,
+            did,
+            current_update.get_public_key(),
+            &current_update.get_secret_key(),
+            next_update,
+            self.hash_algorithm,
+        )?;
+
+        let response = self
+            .client
+            .post_update_identifier(&payload)
+            .await
+            .map_err(UpdateIdentifierError::SidetreeHttpClient)?;
+        if response.status_code.is_success() {
+            Ok(())
+        } else {
+            Err(UpdateIdentifierError::SidetreeRequestFailed(format!(
+                "{:?}",
+                response
+            )))
+        }
+    }
+}
+
+impl<C> DidRepository for DidRepositoryImpl<C>
+where
+    C: SidetreeHttpClient + Send + Sync,
+    C::Error: Send + Sync + 'static,
 {
     type CreateIdentifierError = CreateIdentifierError<C::Error>;
-    type FindIdentifierError = FindIdentifierError<C::Error>;
+    type UpdateIdentifierError = UpdateIdentifierError<C::Error>;
+    type RecoverIdentifierError = RecoverIdentifierError<C::Error>;
+    type DeactivateIdentifierError = DeactivateIdentifierError<C::Error>;
     async fn create_identifier(
         &self,
         keyring: KeyPairing,
     ) -> Result<DidResolutionResponse, CreateIdentifierError<C::Error>> {
-        // https://w3c.github.io/did-spec-registries/#assertionmethod
-        // FIXME: This purpose property is strange...
-        //        I guess the sidetree protocol this impl uses is too old.
-        // https://identity.foundation/sidetree/spec/#add-public-keys
-        // vec!["assertionMethod".to_string()],
-        let sign = keyring.sign.get_public_key().to_public_key(
-            "EcdsaSecp256k1VerificationKey2019".to_string(),
-            "signingKey".to_string(),
-            vec!["auth".to_string(), "general".to_string()],
-        )?;
-        // vec!["keyAgreement".to_string()]
-        let enc = keyring
-            .encrypt
-            .get_public_key()
-            .to_public_key(
-                "X25519KeyAgreementKey2019".to_string(),
-                "encryptionKey".to_string(),
-                vec!["auth".to_string(), "general".to_string()],
-            )
-            .unwrap();
         let update = keyring.update.get_public_key();
         let recovery = keyring.recovery.get_public_key();
-        let document = DidPatchDocument {
-            public_keys: vec![sign, enc],
-            service_endpoints: vec![],
-        };
-        let payload = did_create_payload(document, update, recovery)?;
+        let payload = did_create_payload(
+            create_document(&keyring)?,
+            update,
+            recovery,
+            self.hash_algorithm,
+        )?;
 
         let response = self
             .client
@@ -139,7 +409,13 @@ where
             .await
             .map_err(CreateIdentifierError::SidetreeHttpClient)?;
         if response.status_code.is_success() {
-            Ok(serde_json::from_str(&response.body)?)
+            let response: DidCreateResponse = serde_json::from_str(&response.body)?;
+            Ok(DidResolutionResponse {
+                context: response.context,
+                did_document: response.did_document.into(),
+                method_metadata: response.method_metadata,
+                did_document_metadata: None,
+            })
         } else {
             Err(CreateIdentifierError::SidetreeRequestFailed(format!(
                 "{:?}",
@@ -148,19 +424,152 @@ where
         }
     }
 
+    async fn update_identifier(
+        &self,
+        did: &str,
+        current_update: &K256KeyPair,
+        keyring: &KeyPairing,
+    ) -> Result<(), UpdateIdentifierError<C::Error>> {
+        // NOTE: Public keys with an existing id are replaced.
+        let patch = DidAction::AddPublicKeys {
+            public_keys: to_public_keys(keyring)?,
+        };
+        self.post_update(
+            vec![patch],
+            did,
+            current_update,
+            keyring.update.get_public_key(),
+        )
+        .await
+    }
+
+    async fn recover_identifier(
+        &self,
+        did: &str,
+        current_recovery: &K256KeyPair,
+        keyring: &KeyPairing,
+    ) -> Result<(), RecoverIdentifierError<C::Error>> {
+        // NOTE: The document is replaced as a whole, so the current services are carried over.
+        //       Their ids are relative in patches.
+        let service_endpoints = self
+            .find_identifier(did)
+            .await?
+            .and_then(|v| v.did_document.service)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|v| ServiceEndpoint {
+                id: v.id.rsplit('#').next().unwrap_or_default().to_string(),
+                r#type: v.r#type,
+                service_endpoint: v.service_endpoint,
+                description: v.description,
+            })
+            .collect();
+        let document = DidPatchDocument {
+            public_keys: to_public_keys(keyring)?,
+            service_endpoints,
+        };
+        let payload = did_recover_payload(
+            document,
+            did,
+            current_recovery.get_public_key(),
+            &current_recovery.get_secret_key(),
+            keyring.update.get_public_key(),
+            keyring.recovery.get_public_key(),
+            self.hash_algorithm,
+        )?;
+
+        let response = self
+            .client
+            .post_recover_identifier(&payload)
+            .await
+            .map_err(RecoverIdentifierError::SidetreeHttpClient)?;
+        if response.status_code.is_success() {
+            Ok(())
+        } else {
+            Err(RecoverIdentifierError::SidetreeRequestFailed(format!(
+                "{:?}",
+                response
+            )))
+        }
+    }
+
+    async fn update_services(
+        &self,
+        did: &str,
+        current_update: &K256KeyPair,
+        next_update: &K256KeyPair,
+        services: Vec<ServiceEndpoint>,
+        remove_ids: Vec<String>,
+    ) -> Result<(), UpdateIdentifierError<C::Error>> {
+        let mut patches = vec![];
+        if !remove_ids.is_empty() {
+            patches.push(DidAction::RemoveServices { ids: remove_ids });
+        }
+        if !services.is_empty() {
+            patches.push(DidAction::AddServices { services });
+        }
+        self.post_update(patches, did, current_update, next_update.get_public_key())
+            .await
+    }
+
+    async fn deactivate_identifier(
+        &self,
+        did: &str,
+        current_recovery: &K256KeyPair,
+    ) -> Result<(), DeactivateIdentifierError<C::Error>> {
+        let payload = did_deactivate_payload(
+            did,
+            current_recovery.get_public_key(),
+            &current_recovery.get_secret_key(),
+        )?;
+
+        let response = self
+            .client
+            .post_deactivate_identifier(&payload)
+            .await
+            .map_err(DeactivateIdentifierError::SidetreeHttpClient)?;
+        if response.status_code.is_success() {
+            Ok(())
+        } else {
+            Err(DeactivateIdentifierError::SidetreeRequestFailed(format!(
+                "{:?}",
+                response
+            )))
+        }
+    }
+}
+
+impl<C> DidResolver for DidRepositoryImpl<C>
+where
+    C: SidetreeHttpClient + Send + Sync,
+    C::Error: Send + Sync + 'static,
+{
+    type FindIdentifierError = FindIdentifierError<C::Error>;
     async fn find_identifier(
         &self,
         did: &str,
     ) -> Result<Option<DidResolutionResponse>, FindIdentifierError<C::Error>> {
+        let short_form_did = get_short_form_did(did);
         let response = self
             .client
-            .get_find_identifier(did)
+            .get_find_identifier(short_form_did)
             .await
             .map_err(FindIdentifierError::SidetreeHttpClient)?;
 
         match response.status_code {
-            StatusCode::OK => Ok(Some(serde_json::from_str(&response.body)?)),
-            StatusCode::NOT_FOUND => Ok(None),
+            StatusCode::OK => {
+                let response: SidetreeDidResolutionResponse = serde_json::from_str(&response.body)?;
+                let mut response = DidResolutionResponse::from(response);
+                // NOTE: Once anchored, a long-form DID resolves to the published document.
+                if short_form_did != did {
+                    response.did_document.id = did.to_string();
+                }
+                Ok(Some(response))
+            }
+            // NOTE: A long-form DID not anchored yet is resolved from its create operation.
+            //       Other statuses, e.g. deactivated, are not.
+            StatusCode::NOT_FOUND => Ok(resolve_long_form_did(did)?),
+            StatusCode::GONE => Err(FindIdentifierError::Deactivated(did.to_string())),
             _ => Err(FindIdentifierError::SidetreeRequestFailed(format!(
                 "{:?}",
                 response
@@ -175,7 +584,7 @@ pub mod mocks {
 
     use super::*;
     use crate::{
-        did::sidetree::payload::{DidDocument, DidPublicKey, MethodMetadata},
+        did::sidetree::payload::{DidPublicKey, MethodMetadata, SidetreeDidDocument},
         keyring::keypair::KeyPairing,
     };
 
@@ -199,15 +608,8 @@ pub mod mocks {
     #[derive(Debug, thiserror::Error)]
     pub enum DummyError {}
 
-    impl DidRepository for MockDidRepository {
-        type CreateIdentifierError = CreateIdentifierError<DummyError>;
+    impl DidResolver for MockDidRepository {
         type FindIdentifierError = FindIdentifierError<DummyError>;
-        async fn create_identifier(
-            &self,
-            _keyring: KeyPairing,
-        ) -> Result<DidResolutionResponse, Self::CreateIdentifierError> {
-            unimplemented!()
-        }
         async fn find_identifier(
             &self,
             did: &str,
@@ -221,13 +623,21 @@ pub mod mocks {
                                 id: "#signingKey".to_string(),
                                 controller: String::new(),
                                 r#type: "EcdsaSecp256k1VerificationKey2019".to_string(),
-                                public_key_jwk: keyring.sign.get_public_key().try_into().unwrap(),
+                                public_key_jwk: Some(
+                                    keyring.sign.get_public_key().try_into().unwrap(),
+                                ),
+                                public_key_multibase: None,
+                                public_key_base58: None,
+                                purposes: None,
                             },
                             DidPublicKey {
                                 id: "#encryptionKey".to_string(),
                                 controller: String::new(),
                                 r#type: "X25519KeyAgreementKey2019".to_string(),
-                                public_key_jwk: keyring.encrypt.get_public_key().into(),
+                                public_key_jwk: Some(keyring.encrypt.get_public_key().into()),
+                                public_key_multibase: None,
+                                public_key_base58: None,
+                                purposes: None,
                             },
                         ]
                     })
@@ -235,17 +645,20 @@ pub mod mocks {
 
                 let response = DidResolutionResponse {
                     context: "https://www.w3.org/ns/did-resolution/v1".to_string(),
-                    did_document: DidDocument {
+                    did_document: SidetreeDidDocument {
                         id: did.to_string(),
                         public_key: Some(public_keys),
                         service: None,
                         authentication: Some(vec!["signingKey".to_string()]),
-                    },
+                    }
+                    .into(),
                     method_metadata: MethodMetadata {
                         published: true,
                         recovery_commitment: None,
                         update_commitment: None,
+                        ttl: None,
                     },
+                    did_document_metadata: None,
                 };
                 Ok(Some(response))
             } else {
@@ -257,32 +670,28 @@ pub mod mocks {
     #[derive(Clone, Copy)]
     pub struct NoPublicKeyDidRepository;
 
-    impl DidRepository for NoPublicKeyDidRepository {
-        type CreateIdentifierError = CreateIdentifierError<DummyError>;
+    impl DidResolver for NoPublicKeyDidRepository {
         type FindIdentifierError = FindIdentifierError<DummyError>;
-        async fn create_identifier(
-            &self,
-            _keyring: KeyPairing,
-        ) -> Result<DidResolutionResponse, Self::CreateIdentifierError> {
-            unimplemented!()
-        }
         async fn find_identifier(
             &self,
             did: &str,
         ) -> Result<Option<DidResolutionResponse>, Self::FindIdentifierError> {
             Ok(Some(DidResolutionResponse {
                 context: "https://www.w3.org/ns/did-resolution/v1".to_string(),
-                did_document: DidDocument {
+                did_document: SidetreeDidDocument {
                     id: did.to_string(),
                     public_key: None,
                     service: None,
                     authentication: None,
-                },
+                }
+                .into(),
                 method_metadata: MethodMetadata {
                     published: true,
                     recovery_commitment: None,
                     update_commitment: None,
+                    ttl: None,
                 },
+                did_document_metadata: None,
             }))
         }
     }
@@ -290,33 +699,219 @@ pub mod mocks {
     #[derive(Clone, Copy)]
     pub struct IllegalPublicKeyLengthDidRepository;
 
-    impl DidRepository for IllegalPublicKeyLengthDidRepository {
-        type CreateIdentifierError = CreateIdentifierError<DummyError>;
+    impl DidResolver for IllegalPublicKeyLengthDidRepository {
         type FindIdentifierError = FindIdentifierError<DummyError>;
-        async fn create_identifier(
-            &self,
-            _keyring: KeyPairing,
-        ) -> Result<DidResolutionResponse, Self::CreateIdentifierError> {
-            unimplemented!()
-        }
         async fn find_identifier(
             &self,
             did: &str,
         ) -> Result<Option<DidResolutionResponse>, Self::FindIdentifierError> {
             Ok(Some(DidResolutionResponse {
                 context: "https://www.w3.org/ns/did-resolution/v1".to_string(),
-                did_document: DidDocument {
+                did_document: SidetreeDidDocument {
                     id: did.to_string(),
                     public_key: Some(vec![]),
                     service: None,
                     authentication: None,
-                },
+                }
+                .into(),
                 method_metadata: MethodMetadata {
                     published: true,
                     recovery_commitment: None,
                     update_commitment: None,
+                    ttl: None,
                 },
+                did_document_metadata: None,
             }))
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use data_encoding::BASE64URL_NOPAD;
+    use rand_core::OsRng;
+    use serde_json::{json, Value};
+
+    use super::*;
+    use crate::did::sidetree::{client::SidetreeHttpClientResponse, payload::verify_commitment};
+
+    // Records the operations posted, as a Sidetree node accepting all of them.
+    #[derive(Default)]
+    struct RecordingSidetreeClient {
+        bodies: Mutex<Vec<String>>,
+        resolution: Option<Value>,
+    }
+
+    impl RecordingSidetreeClient {
+        fn accept(&self, body: &str) -> Result<SidetreeHttpClientResponse, std::io::Error> {
+            self.bodies.lock().unwrap().push(body.to_string());
+            Ok(SidetreeHttpClientResponse::new(
+                StatusCode::OK,
+                "{}".to_string(),
+            ))
+        }
+    }
+
+    impl SidetreeHttpClient for RecordingSidetreeClient {
+        type Error = std::io::Error;
+        async fn post_create_identifier(
+            &self,
+            body: &str,
+        ) -> Result<SidetreeHttpClientResponse, Self::Error> {
+            self.accept(body)
+        }
+        async fn post_update_identifier(
+            &self,
+            body: &str,
+        ) -> Result<SidetreeHttpClientResponse, Self::Error> {
+            self.accept(body)
+        }
+        async fn post_recover_identifier(
+            &self,
+            body: &str,
+        ) -> Result<SidetreeHttpClientResponse, Self::Error> {
+            self.accept(body)
+        }
+        async fn post_deactivate_identifier(
+            &self,
+            body: &str,
+        ) -> Result<SidetreeHttpClientResponse, Self::Error> {
+            self.accept(body)
+        }
+        async fn get_find_identifier(
+            &self,
+            _did: &str,
+        ) -> Result<SidetreeHttpClientResponse, Self::Error> {
+            Ok(match &self.resolution {
+                Some(v) => SidetreeHttpClientResponse::new(StatusCode::OK, v.to_string()),
+                None => SidetreeHttpClientResponse::new(StatusCode::NOT_FOUND, "".to_string()),
+            })
+        }
+    }
+
+    const DID: &str = "did:nodex:test:EiBprXreMiba4loyl3psXm0RsECdtlCiQIjM8G9BtdQplA";
+
+    fn decode_delta(body: &str) -> Value {
+        let body: Value = serde_json::from_str(body).unwrap();
+        let delta = BASE64URL_NOPAD
+            .decode(body["delta"].as_str().unwrap().as_bytes())
+            .unwrap();
+        serde_json::from_slice(&delta).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_update_services_payload() {
+        let current = KeyPairing::create_keyring(OsRng);
+        let next = KeyPairing::create_keyring(OsRng);
+        let repository = DidRepositoryImpl::new(RecordingSidetreeClient::default());
+        let service = ServiceEndpoint {
+            id: "didcomm".to_string(),
+            r#type: "DIDCommMessaging".to_string(),
+            service_endpoint: "https://example.com/didcomm".into(),
+            description: None,
+        };
+
+        repository
+            .update_services(
+                DID,
+                &current.update,
+                &next.update,
+                vec![service],
+                vec!["old".to_string()],
+            )
+            .await
+            .unwrap();
+
+        let bodies = repository.client.bodies.lock().unwrap();
+        let [body] = bodies.as_slice() else {
+            panic!("{} operations are posted", bodies.len());
+        };
+        let body: Value = serde_json::from_str(body).unwrap();
+        assert_eq!(body["type"], "update");
+        assert_eq!(
+            body["did_suffix"],
+            "EiBprXreMiba4loyl3psXm0RsECdtlCiQIjM8G9BtdQplA"
+        );
+        let delta = decode_delta(&body.to_string());
+        assert_eq!(
+            delta["patches"],
+            json!([
+                {"action": "remove-services", "ids": ["old"]},
+                {
+                    "action": "add-services",
+                    "services": [{
+                        "id": "didcomm",
+                        "type": "DIDCommMessaging",
+                        "serviceEndpoint": "https://example.com/didcomm",
+                        "description": null,
+                    }],
+                },
+            ])
+        );
+        // NOTE: The next update key is committed, and the current one is revealed in signed_data.
+        let commitment = delta["update_commitment"].as_str().unwrap();
+        assert!(verify_commitment(commitment, next.update.get_public_key()));
+        assert!(!verify_commitment(
+            commitment,
+            current.update.get_public_key()
+        ));
+        let signed_data = body["signed_data"].as_str().unwrap();
+        let payload = signed_data.split('.').nth(1).unwrap();
+        let payload: Value =
+            serde_json::from_slice(&BASE64URL_NOPAD.decode(payload.as_bytes()).unwrap()).unwrap();
+        assert_eq!(
+            payload["update_key"],
+            serde_json::to_value(Jwk::try_from(current.update.get_public_key()).unwrap()).unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_recover_identifier_keeps_services() {
+        let current = KeyPairing::create_keyring(OsRng);
+        let next = KeyPairing::create_keyring(OsRng);
+        let repository = DidRepositoryImpl::new(RecordingSidetreeClient {
+            resolution: Some(json!({
+                "@context": "https://w3id.org/did-resolution/v1",
+                "didDocument": {
+                    "id": DID,
+                    "publicKey": [],
+                    "service": [{
+                        "id": "#didcomm",
+                        "type": "DIDCommMessaging",
+                        "serviceEndpoint": "https://example.com/didcomm",
+                        "description": null,
+                    }],
+                },
+                "methodMetadata": {"published": true},
+            })),
+            ..Default::default()
+        });
+
+        repository
+            .recover_identifier(DID, &current.recovery, &next)
+            .await
+            .unwrap();
+
+        let bodies = repository.client.bodies.lock().unwrap();
+        let delta = decode_delta(&bodies[0]);
+        assert_eq!(delta["patches"][0]["action"], "replace");
+        assert_eq!(
+            delta["patches"][0]["document"]["service_endpoints"],
+            json!([{
+                "id": "didcomm",
+                "type": "DIDCommMessaging",
+                "serviceEndpoint": "https://example.com/didcomm",
+                "description": null,
+            }])
+        );
+        assert_eq!(
+            delta["patches"][0]["document"]["public_keys"]
+                .as_array()
+                .unwrap()
+                .len(),
+            to_public_keys(&next).unwrap().len()
+        );
+    }
+}
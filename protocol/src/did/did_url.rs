@@ -0,0 +1,308 @@
+// DID URLs and dereferencing them into resources of DID documents.
+// https://www.w3.org/TR/did-core/#did-url-syntax
+// https://www.w3.org/TR/did-core/#did-url-dereferencing
+
+use std::str::FromStr;
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use super::did_document::{DidDocument, Service, VerificationMethod};
+use super::did_repository::DidResolver;
+use crate::did_webvh::domain::did::{Did, DidError};
+
+// pchar / "/" / "?" of RFC 3986.
+static URL_CHARS: &str = r"^([a-zA-Z0-9\-._~!$&'()*+,;=:@/?]|%[0-9A-Fa-f]{2})*$";
+
+#[derive(Clone, Debug, PartialEq, thiserror::Error)]
+pub enum DidUrlError {
+    #[error(transparent)]
+    Regex(#[from] regex::Error),
+    #[error("Failed to parse did: {0}")]
+    Did(#[from] DidError),
+    #[error("Failed to parse path")]
+    InvalidPath,
+    #[error("Failed to parse query")]
+    InvalidQuery,
+    #[error("Failed to parse fragment")]
+    InvalidFragment,
+}
+
+// did:example:123/path?service=files#key-1
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct DidUrl {
+    did: Did,
+    // NOTE: Starts with '/' if any.
+    path: Option<String>,
+    query: Option<String>,
+    fragment: Option<String>,
+}
+
+impl DidUrl {
+    pub fn new(did: Did) -> Self {
+        Self {
+            did,
+            path: None,
+            query: None,
+            fragment: None,
+        }
+    }
+
+    pub fn with_fragment(self, fragment: &str) -> Result<Self, DidUrlError> {
+        if !is_url_chars(fragment)? {
+            return Err(DidUrlError::InvalidFragment);
+        }
+        Ok(Self {
+            fragment: Some(fragment.to_string()),
+            ..self
+        })
+    }
+
+    pub fn get_did(&self) -> &Did {
+        &self.did
+    }
+
+    pub fn get_path(&self) -> Option<&str> {
+        self.path.as_deref()
+    }
+
+    pub fn get_query(&self) -> Option<&str> {
+        self.query.as_deref()
+    }
+
+    pub fn get_fragment(&self) -> Option<&str> {
+        self.fragment.as_deref()
+    }
+
+    // The value of `name` in the query, e.g. `service` of `?service=files`.
+    pub fn get_query_param(&self, name: &str) -> Option<&str> {
+        self.query.as_deref()?.split('&').find_map(|v| {
+            let (key, value) = v.split_once('=')?;
+            (key == name).then_some(value)
+        })
+    }
+
+    // Dereferences this DID URL in `did_document`, which is the resolved document of its DID.
+    // NOTE: Paths are not defined by any DID method we support, so they are never found.
+    pub fn dereference_in(&self, did_document: &DidDocument) -> Option<DereferencedResource> {
+        if did_document.id != self.did.to_string() || self.path.is_some() {
+            return None;
+        }
+        if let Some(service) = self.get_query_param("service") {
+            let service = did_document.find_service(&format!("#{}", service))?;
+            return Some(DereferencedResource::Service(service.clone()));
+        }
+        let fragment = match &self.fragment {
+            Some(v) => format!("#{}", v),
+            None => return Some(DereferencedResource::DidDocument(did_document.clone())),
+        };
+        if let Some(method) = did_document.find_verification_method(&fragment) {
+            return Some(DereferencedResource::VerificationMethod(method.clone()));
+        }
+        did_document
+            .find_service(&fragment)
+            .map(|v| DereferencedResource::Service(v.clone()))
+    }
+}
+
+fn is_url_chars(s: &str) -> Result<bool, regex::Error> {
+    Ok(regex::Regex::new(URL_CHARS)?.is_match(s))
+}
+
+impl std::fmt::Display for DidUrl {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.did)?;
+        if let Some(path) = &self.path {
+            write!(f, "{}", path)?;
+        }
+        if let Some(query) = &self.query {
+            write!(f, "?{}", query)?;
+        }
+        if let Some(fragment) = &self.fragment {
+            write!(f, "#{}", fragment)?;
+        }
+        Ok(())
+    }
+}
+
+impl From<Did> for DidUrl {
+    fn from(did: Did) -> Self {
+        Self::new(did)
+    }
+}
+
+impl FromStr for DidUrl {
+    type Err = DidUrlError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (s, fragment) = match s.split_once('#') {
+            Some((s, fragment)) => (s, Some(fragment)),
+            None => (s, None),
+        };
+        let (s, query) = match s.split_once('?') {
+            Some((s, query)) => (s, Some(query)),
+            None => (s, None),
+        };
+        let (did, path) = match s.find('/') {
+            Some(i) => (&s[..i], Some(&s[i..])),
+            None => (s, None),
+        };
+        if !is_url_chars(path.unwrap_or_default())? {
+            return Err(DidUrlError::InvalidPath);
+        }
+        if !is_url_chars(query.unwrap_or_default())? {
+            return Err(DidUrlError::InvalidQuery);
+        }
+        if !is_url_chars(fragment.unwrap_or_default())? {
+            return Err(DidUrlError::InvalidFragment);
+        }
+        Ok(Self {
+            did: did.parse()?,
+            path: path.map(str::to_string),
+            query: query.map(str::to_string),
+            fragment: fragment.map(str::to_string),
+        })
+    }
+}
+
+impl<'de> Deserialize<'de> for DidUrl {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        s.parse()
+            .map_err(|_| serde::de::Error::custom("Invalid Did Url Format"))
+    }
+}
+
+impl Serialize for DidUrl {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.collect_str(self)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum DereferencedResource {
+    DidDocument(DidDocument),
+    VerificationMethod(VerificationMethod),
+    Service(Service),
+}
+
+// Resolves the DID of `url` with `repository` and dereferences `url` in its document.
+pub async fn dereference<R: DidResolver>(
+    repository: &R,
+    url: &DidUrl,
+) -> Result<Option<DereferencedResource>, R::FindIdentifierError> {
+    let response = repository.find_identifier(&url.did.to_string()).await?;
+    Ok(response.and_then(|v| url.dereference_in(&v.did_document)))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use rand_core::OsRng;
+
+    use super::*;
+    use crate::did::did_repository::mocks::MockDidRepository;
+    use crate::did::test_utils::create_random_did;
+    use crate::keyring::keypair::KeyPairing;
+
+    #[test]
+    fn test_did_url() {
+        let url: DidUrl = "did:example:123/path/to?service=files&x=1#key-1"
+            .parse()
+            .unwrap();
+        assert_eq!(url.get_did().to_string(), "did:example:123");
+        assert_eq!(url.get_path(), Some("/path/to"));
+        assert_eq!(url.get_query(), Some("service=files&x=1"));
+        assert_eq!(url.get_query_param("service"), Some("files"));
+        assert_eq!(url.get_query_param("y"), None);
+        assert_eq!(url.get_fragment(), Some("key-1"));
+        assert_eq!(
+            url.to_string(),
+            "did:example:123/path/to?service=files&x=1#key-1"
+        );
+
+        let url = DidUrl::new("did:example:123".parse().unwrap())
+            .with_fragment("signingKey")
+            .unwrap();
+        assert_eq!(url.to_string(), "did:example:123#signingKey");
+        let json = serde_json::to_string(&url).unwrap();
+        assert_eq!(json, r#""did:example:123#signingKey""#);
+        assert_eq!(serde_json::from_str::<DidUrl>(&json).unwrap(), url);
+    }
+
+    #[test]
+    fn test_did_url_error() {
+        assert!(matches!(
+            "example:123#key-1".parse::<DidUrl>(),
+            Err(DidUrlError::Did(_))
+        ));
+        assert_eq!(
+            "did:example:123#key 1".parse::<DidUrl>().unwrap_err(),
+            DidUrlError::InvalidFragment
+        );
+        assert_eq!(
+            "did:example:123#a#b".parse::<DidUrl>().unwrap_err(),
+            DidUrlError::InvalidFragment
+        );
+        assert_eq!(
+            "did:example:123?a=%".parse::<DidUrl>().unwrap_err(),
+            DidUrlError::InvalidQuery
+        );
+    }
+
+    #[tokio::test]
+    async fn test_dereference() {
+        let did = create_random_did();
+        let keyring = KeyPairing::create_keyring(OsRng);
+        let mock = MockDidRepository::from_single(BTreeMap::from([(did.clone(), keyring)]));
+
+        let url: DidUrl = format!("{}#signingKey", did).parse().unwrap();
+        match dereference(&mock, &url).await.unwrap() {
+            Some(DereferencedResource::VerificationMethod(method)) => {
+                assert_eq!(method.id, "#signingKey");
+                assert_eq!(method.public_key_jwk.unwrap().crv(), "secp256k1");
+            }
+            v => panic!("unexpected resource: {:?}", v),
+        }
+
+        let url: DidUrl = did.parse().unwrap();
+        assert!(matches!(
+            dereference(&mock, &url).await.unwrap(),
+            Some(DereferencedResource::DidDocument(_))
+        ));
+
+        let url: DidUrl = format!("{}#unknownKey", did).parse().unwrap();
+        assert!(dereference(&mock, &url).await.unwrap().is_none());
+        let url: DidUrl = create_random_did().parse().unwrap();
+        assert!(dereference(&mock, &url).await.unwrap().is_none());
+    }
+
+    #[test]
+    fn test_dereference_service() {
+        let mut did_document = DidDocument::new("did:example:123");
+        did_document.service = Some(vec![Service {
+            id: "did:example:123#files".to_string(),
+            r#type: "LinkedDomains".to_string(),
+            service_endpoint: "https://example.com".into(),
+            description: None,
+        }]);
+
+        for url in ["did:example:123#files", "did:example:123?service=files"] {
+            let url: DidUrl = url.parse().unwrap();
+            match url.dereference_in(&did_document) {
+                Some(DereferencedResource::Service(service)) => {
+                    assert_eq!(service.service_endpoint, "https://example.com".into())
+                }
+                v => panic!("unexpected resource: {:?}", v),
+            }
+        }
+        let url: DidUrl = "did:example:123/files".parse().unwrap();
+        assert!(url.dereference_in(&did_document).is_none());
+    }
+}
@@ -0,0 +1,190 @@
+// A DidResolver resolving DIDs with another one when the primary one fails,
+// e.g. pinned documents behind the network resolver.
+
+use super::did_repository::{DidRepository, DidResolver};
+use super::resolution::{ResolutionError, ResolutionErrorCode};
+use super::sidetree::payload::{DidResolutionResponse, ServiceEndpoint};
+use crate::keyring::keypair::{K256KeyPair, KeyPairing};
+
+#[derive(Debug, thiserror::Error)]
+pub enum FallbackFindIdentifierError<PrimaryError, FallbackError>
+where
+    PrimaryError: std::error::Error,
+    FallbackError: std::error::Error,
+{
+    #[error(transparent)]
+    Primary(PrimaryError),
+    #[error("Failed to find identifier in fallback: {0}")]
+    Fallback(FallbackError),
+}
+
+impl<P, F> ResolutionError for FallbackFindIdentifierError<P, F>
+where
+    P: std::error::Error + ResolutionError,
+    F: std::error::Error + ResolutionError,
+{
+    fn error_code(&self) -> ResolutionErrorCode {
+        match self {
+            FallbackFindIdentifierError::Primary(e) => e.error_code(),
+            FallbackFindIdentifierError::Fallback(e) => e.error_code(),
+        }
+    }
+}
+
+// NOTE: A DID the primary one does not find is not looked up in the fallback,
+//       since e.g. a deactivated DID must not be resolved from a stale copy.
+//       Neither is one the primary one knows to be invalid, e.g. deactivated.
+//       The other operations go to the primary one.
+#[derive(Clone)]
+pub struct FallbackDidRepository<P: DidResolver, F: DidResolver> {
+    primary: P,
+    fallback: F,
+}
+
+impl<P: DidResolver, F: DidResolver> FallbackDidRepository<P, F> {
+    pub fn new(primary: P, fallback: F) -> Self {
+        Self { primary, fallback }
+    }
+
+    pub fn fallback(&self) -> &F {
+        &self.fallback
+    }
+}
+
+impl<P, F> DidResolver for FallbackDidRepository<P, F>
+where
+    P: DidResolver + Send,
+    F: DidResolver + Send,
+{
+    type FindIdentifierError =
+        FallbackFindIdentifierError<P::FindIdentifierError, F::FindIdentifierError>;
+
+    async fn find_identifier(
+        &self,
+        did: &str,
+    ) -> Result<Option<DidResolutionResponse>, Self::FindIdentifierError> {
+        let e = match self.primary.find_identifier(did).await {
+            Ok(response) => return Ok(response),
+            Err(e) if e.error_code() != ResolutionErrorCode::InternalError => {
+                return Err(FallbackFindIdentifierError::Primary(e))
+            }
+            Err(e) => e,
+        };
+        match self.fallback.find_identifier(did).await {
+            Ok(Some(response)) => Ok(Some(response)),
+            Ok(None) => Err(FallbackFindIdentifierError::Primary(e)),
+            Err(e) => Err(FallbackFindIdentifierError::Fallback(e)),
+        }
+    }
+}
+
+impl<P, F> DidRepository for FallbackDidRepository<P, F>
+where
+    P: DidRepository + Send,
+    F: DidResolver + Send,
+{
+    type CreateIdentifierError = P::CreateIdentifierError;
+    type UpdateIdentifierError = P::UpdateIdentifierError;
+    type RecoverIdentifierError = P::RecoverIdentifierError;
+    type DeactivateIdentifierError = P::DeactivateIdentifierError;
+
+    async fn create_identifier(
+        &self,
+        keyring: KeyPairing,
+    ) -> Result<DidResolutionResponse, Self::CreateIdentifierError> {
+        self.primary.create_identifier(keyring).await
+    }
+
+    async fn update_identifier(
+        &self,
+        did: &str,
+        current_update: &K256KeyPair,
+        keyring: &KeyPairing,
+    ) -> Result<(), Self::UpdateIdentifierError> {
+        self.primary
+            .update_identifier(did, current_update, keyring)
+            .await
+    }
+
+    async fn recover_identifier(
+        &self,
+        did: &str,
+        current_recovery: &K256KeyPair,
+        keyring: &KeyPairing,
+    ) -> Result<(), Self::RecoverIdentifierError> {
+        self.primary
+            .recover_identifier(did, current_recovery, keyring)
+            .await
+    }
+
+    async fn update_services(
+        &self,
+        did: &str,
+        current_update: &K256KeyPair,
+        next_update: &K256KeyPair,
+        services: Vec<ServiceEndpoint>,
+        remove_ids: Vec<String>,
+    ) -> Result<(), Self::UpdateIdentifierError> {
+        self.primary
+            .update_services(did, current_update, next_update, services, remove_ids)
+            .await
+    }
+
+    async fn deactivate_identifier(
+        &self,
+        did: &str,
+        current_recovery: &K256KeyPair,
+    ) -> Result<(), Self::DeactivateIdentifierError> {
+        self.primary
+            .deactivate_identifier(did, current_recovery)
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+    use std::fs;
+
+    use rand_core::OsRng;
+
+    use super::*;
+    use crate::did::did_repository::mocks::MockDidRepository;
+    use crate::did::file_store::FileDidRepository;
+    use crate::did::test_utils::create_random_did;
+
+    #[tokio::test]
+    async fn test_find_identifier() {
+        let did = create_random_did();
+        let keyring = KeyPairing::create_keyring(OsRng);
+        let mock = MockDidRepository::from_single(BTreeMap::from([(did.clone(), keyring)]));
+        let response = mock.find_identifier(&did).await.unwrap().unwrap();
+
+        let dir = std::env::temp_dir().join(create_random_did().replace(':', "_"));
+        let primary = FileDidRepository::new(dir.join("primary"));
+        let fallback = FileDidRepository::new(dir.join("fallback"));
+        fallback.pin(&response).unwrap();
+        let repository = FallbackDidRepository::new(primary, fallback);
+
+        // NOTE: Not found by the primary one.
+        assert!(repository.find_identifier(&did).await.unwrap().is_none());
+
+        // NOTE: A broken document makes the primary one fail.
+        fs::create_dir_all(dir.join("primary")).unwrap();
+        fs::write(
+            dir.join("primary")
+                .join(format!("{}.json", did.replace(':', "%3A"))),
+            "{}",
+        )
+        .unwrap();
+        let found = repository.find_identifier(&did).await.unwrap().unwrap();
+        assert_eq!(found.did_document.id, did);
+
+        repository.fallback().unpin(&did).unwrap();
+        assert!(matches!(
+            repository.find_identifier(&did).await,
+            Err(FallbackFindIdentifierError::Primary(_))
+        ));
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
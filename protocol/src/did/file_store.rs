@@ -0,0 +1,390 @@
+// A DidResolver of DID documents pinned in a local directory, e.g. for air-gapped devices.
+// Each file holds a resolution response or a bare DID document.
+
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use thiserror::Error;
+
+use super::did_document::DidDocument;
+use super::did_repository::{get_sign_key, DidResolver, GetPublicKeyError};
+use super::resolution::ResolutionError;
+use super::sidetree::payload::{DidResolutionResponse, MethodMetadata};
+use crate::keyring::jwk::{Jwk, JwkThumbprintError, SignKeyToJwkError};
+use crate::keyring::keypair::KeyPairing;
+use crate::verifiable_credentials::credential_signer::{
+    CredentialSigner, CredentialSignerSignError, CredentialSignerSuite, CredentialSignerVerifyError,
+};
+use crate::verifiable_credentials::types::VerifiableCredentials;
+
+#[derive(Debug, Error)]
+pub enum FileFindIdentifierError {
+    #[error("Failed to read pinned did document: {0}")]
+    Io(#[from] io::Error),
+    #[error("Failed to parse pinned did document: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("pinned did document does not match: {0}")]
+    DidMismatch(String),
+}
+
+impl ResolutionError for FileFindIdentifierError {}
+
+#[derive(Debug, Error)]
+pub enum ImportDidDocumentError {
+    #[error("Failed to parse bundle: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("bundle is not issued by its did: {0}")]
+    IssuerMismatch(String),
+    #[error("did public key not found: {0}")]
+    PublicKey(#[from] GetPublicKeyError),
+    #[error("Failed to convert to JWK: {0}")]
+    Jwk(#[from] SignKeyToJwkError),
+    #[error("Failed to compute JWK thumbprint: {0}")]
+    Thumbprint(#[from] JwkThumbprintError),
+    #[error("sign key is not trusted: {0}")]
+    UntrustedKey(String),
+    #[error("Failed to verify bundle: {0}")]
+    Verify(#[from] CredentialSignerVerifyError),
+    #[error("Failed to write did document: {0}")]
+    Io(#[from] io::Error),
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum PinnedDocument {
+    Resolution(Box<DidResolutionResponse>),
+    Document(Box<DidDocument>),
+}
+
+impl From<PinnedDocument> for DidResolutionResponse {
+    fn from(value: PinnedDocument) -> Self {
+        match value {
+            PinnedDocument::Resolution(v) => *v,
+            PinnedDocument::Document(did_document) => DidResolutionResponse {
+                context: "https://w3id.org/did-resolution/v1".to_string(),
+                did_document: *did_document,
+                method_metadata: MethodMetadata {
+                    published: true,
+                    recovery_commitment: None,
+                    update_commitment: None,
+                    ttl: None,
+                },
+                did_document_metadata: None,
+            },
+        }
+    }
+}
+
+// A bundle of `response` to be imported by other devices, signed with the sign key of its DID.
+pub fn export_did_document(
+    response: &DidResolutionResponse,
+    keyring: &KeyPairing,
+    issuance_date: DateTime<Utc>,
+) -> Result<VerifiableCredentials, CredentialSignerSignError> {
+    let did = &response.did_document.id;
+    let model =
+        VerifiableCredentials::new(did.clone(), serde_json::to_value(response)?, issuance_date);
+    CredentialSigner::sign(
+        model,
+        CredentialSignerSuite {
+            did,
+            key_id: "signingKey",
+            context: &keyring.sign,
+        },
+    )
+}
+
+// did:nodex:test:abc -> did%3Anodex%3Atest%3Aabc.json
+// NOTE: Everything but unreserved characters is percent encoded, so a DID never escapes the directory.
+fn file_name(did: &str) -> String {
+    let mut name = String::with_capacity(did.len() + 5);
+    for b in did.bytes() {
+        if b.is_ascii_alphanumeric() || matches!(b, b'.' | b'-' | b'_') {
+            name.push(b as char);
+        } else {
+            name.push_str(&format!("%{:02X}", b));
+        }
+    }
+    name.push_str(".json");
+    name
+}
+
+// NOTE: Write to a temporary file unique to each write and rename it, so that concurrent
+//       writes never mix and a crash never leaves a file half-written.
+//       The temporary file is only readable by the owner, or has the permissions of the file
+//       it replaces, since e.g. the agent config holds key pairs.
+pub fn write_atomically(path: &Path, contents: &[u8]) -> io::Result<()> {
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(format!(
+        ".{}.{}.tmp",
+        std::process::id(),
+        COUNTER.fetch_add(1, Ordering::Relaxed)
+    ));
+    let tmp = PathBuf::from(tmp);
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let write = || -> io::Result<()> {
+        let mut file = options.open(&tmp)?;
+        #[cfg(unix)]
+        if let Ok(metadata) = fs::metadata(path) {
+            file.set_permissions(metadata.permissions())?;
+        }
+        file.write_all(contents)?;
+        file.sync_all()?;
+        fs::rename(&tmp, path)
+    };
+    if let Err(e) = write() {
+        let _ = fs::remove_file(&tmp);
+        return Err(e);
+    }
+    // NOTE: Sync the directory as well, so that the rename survives a power loss.
+    #[cfg(unix)]
+    {
+        let dir = path
+            .parent()
+            .filter(|v| !v.as_os_str().is_empty())
+            .unwrap_or(Path::new("."));
+        fs::File::open(dir)?.sync_all()?;
+    }
+    Ok(())
+}
+
+#[derive(Clone)]
+pub struct FileDidRepository {
+    dir: PathBuf,
+    trusted_keys: Vec<String>,
+}
+
+impl FileDidRepository {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            trusted_keys: vec![],
+        }
+    }
+
+    // RFC 7638 thumbprints of the sign keys whose bundles are imported.
+    pub fn trusted_keys(mut self, trusted_keys: Vec<String>) -> Self {
+        self.trusted_keys = trusted_keys;
+        self
+    }
+
+    fn path(&self, did: &str) -> PathBuf {
+        self.dir.join(file_name(did))
+    }
+
+    pub fn get(&self, did: &str) -> Result<Option<DidResolutionResponse>, FileFindIdentifierError> {
+        let json = match fs::read_to_string(self.path(did)) {
+            Ok(v) => v,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+        let response: DidResolutionResponse = serde_json::from_str::<PinnedDocument>(&json)?.into();
+        if response.did_document.id != did {
+            return Err(FileFindIdentifierError::DidMismatch(
+                response.did_document.id,
+            ));
+        }
+        Ok(Some(response))
+    }
+
+    // Pins `response` without checks, e.g. our own DID just resolved.
+    pub fn pin(&self, response: &DidResolutionResponse) -> io::Result<()> {
+        fs::create_dir_all(&self.dir)?;
+        let path = self.path(&response.did_document.id);
+        write_atomically(&path, &serde_json::to_vec_pretty(response)?)
+    }
+
+    pub fn unpin(&self, did: &str) -> io::Result<()> {
+        match fs::remove_file(self.path(did)) {
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(()),
+            v => v,
+        }
+    }
+
+    // Pins the document of a bundle made by `export_did_document`.
+    // NOTE: The signature only proves that the exporter holds the sign key of the document,
+    //       so the key itself has to be one of the trusted keys.
+    pub fn import(&self, bundle: &str) -> Result<DidResolutionResponse, ImportDidDocumentError> {
+        let bundle: VerifiableCredentials = serde_json::from_str(bundle)?;
+        let response: DidResolutionResponse =
+            serde_json::from_value(bundle.credential_subject.container.clone())?;
+        if bundle.issuer.id != response.did_document.id {
+            return Err(ImportDidDocumentError::IssuerMismatch(bundle.issuer.id));
+        }
+        let public_key = get_sign_key(&response.did_document)?;
+        let thumbprint = Jwk::try_from(public_key)?.thumbprint()?;
+        if !self.trusted_keys.contains(&thumbprint) {
+            return Err(ImportDidDocumentError::UntrustedKey(thumbprint));
+        }
+        CredentialSigner::verify(bundle, &public_key)?;
+        self.pin(&response)?;
+        Ok(response)
+    }
+}
+
+impl DidResolver for FileDidRepository {
+    type FindIdentifierError = FileFindIdentifierError;
+
+    async fn find_identifier(
+        &self,
+        did: &str,
+    ) -> Result<Option<DidResolutionResponse>, FileFindIdentifierError> {
+        self.get(did)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use rand_core::OsRng;
+
+    use super::*;
+    use crate::did::did_repository::mocks::MockDidRepository;
+    use crate::did::test_utils::create_random_did;
+    use crate::keyring::keypair::KeyPair;
+
+    fn temp_dir() -> PathBuf {
+        std::env::temp_dir().join(create_random_did().replace(':', "_"))
+    }
+
+    fn thumbprint(keyring: &KeyPairing) -> String {
+        Jwk::try_from(keyring.sign.get_public_key())
+            .unwrap()
+            .thumbprint()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_write_atomically() {
+        let dir = temp_dir();
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("config.json");
+
+        write_atomically(&path, b"{}").unwrap();
+        assert_eq!(fs::read(&path).unwrap(), b"{}");
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = |path: &Path| fs::metadata(path).unwrap().permissions().mode() & 0o777;
+            assert_eq!(mode(&path), 0o600);
+            // NOTE: The permissions of an existing file are kept.
+            fs::set_permissions(&path, fs::Permissions::from_mode(0o640)).unwrap();
+            write_atomically(&path, b"{\"did\":null}").unwrap();
+            assert_eq!(mode(&path), 0o640);
+        }
+        assert_eq!(fs::read(&path).unwrap(), b"{\"did\":null}");
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 1);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_file_name() {
+        assert_eq!(
+            file_name("did:nodex:test:abc"),
+            "did%3Anodex%3Atest%3Aabc.json"
+        );
+        assert_eq!(file_name("../x"), "..%2Fx.json");
+    }
+
+    #[tokio::test]
+    async fn test_pin_concurrently() {
+        let did = create_random_did();
+        let keyring = KeyPairing::create_keyring(OsRng);
+        let mock = MockDidRepository::from_single(BTreeMap::from([(did.clone(), keyring)]));
+        let response = mock.find_identifier(&did).await.unwrap().unwrap();
+        let dir = temp_dir();
+        let repository = FileDidRepository::new(&dir);
+
+        std::thread::scope(|scope| {
+            for _ in 0..8 {
+                scope.spawn(|| repository.pin(&response).unwrap());
+            }
+        });
+        // NOTE: Only the pinned document is left, without any temporary file.
+        assert!(repository.get(&did).unwrap().is_some());
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 1);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_import_and_find_identifier() {
+        let did = create_random_did();
+        let keyring = KeyPairing::create_keyring(OsRng);
+        let mock = MockDidRepository::from_single(BTreeMap::from([(did.clone(), keyring.clone())]));
+        let response = mock.find_identifier(&did).await.unwrap().unwrap();
+        let repository =
+            FileDidRepository::new(temp_dir()).trusted_keys(vec![thumbprint(&keyring)]);
+
+        assert!(repository.find_identifier(&did).await.unwrap().is_none());
+
+        let bundle = export_did_document(&response, &keyring, Utc::now()).unwrap();
+        let bundle = serde_json::to_string(&bundle).unwrap();
+        repository.import(&bundle).unwrap();
+        let found = repository.find_identifier(&did).await.unwrap().unwrap();
+        assert_eq!(found.did_document.id, did);
+        assert_eq!(
+            get_sign_key(&found.did_document).unwrap(),
+            get_sign_key(&response.did_document).unwrap()
+        );
+
+        // NOTE: A bare DID document can be pinned as well.
+        let other = create_random_did();
+        let mut document = response.did_document.clone();
+        document.id = other.clone();
+        fs::write(
+            repository.path(&other),
+            serde_json::to_string(&document).unwrap(),
+        )
+        .unwrap();
+        assert!(repository.find_identifier(&other).await.unwrap().is_some());
+
+        repository.unpin(&did).unwrap();
+        assert!(repository.find_identifier(&did).await.unwrap().is_none());
+        fs::remove_dir_all(&repository.dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_import_rejects_invalid_bundle() {
+        let did = create_random_did();
+        let keyring = KeyPairing::create_keyring(OsRng);
+        let mock = MockDidRepository::from_single(BTreeMap::from([(did.clone(), keyring.clone())]));
+        let response = mock.find_identifier(&did).await.unwrap().unwrap();
+        let repository =
+            FileDidRepository::new(temp_dir()).trusted_keys(vec![thumbprint(&keyring)]);
+
+        // NOTE: A self-signed bundle of an untrusted key is rejected.
+        let untrusted = FileDidRepository::new(temp_dir());
+        let bundle = export_did_document(&response, &keyring, Utc::now()).unwrap();
+        assert!(matches!(
+            untrusted.import(&serde_json::to_string(&bundle).unwrap()),
+            Err(ImportDidDocumentError::UntrustedKey(v)) if v == thumbprint(&keyring)
+        ));
+
+        let other = KeyPairing::create_keyring(OsRng);
+        let bundle = export_did_document(&response, &other, Utc::now()).unwrap();
+        assert!(matches!(
+            repository.import(&serde_json::to_string(&bundle).unwrap()),
+            Err(ImportDidDocumentError::Verify(_))
+        ));
+
+        let mut bundle = bundle;
+        bundle.issuer.id = create_random_did();
+        assert!(matches!(
+            repository.import(&serde_json::to_string(&bundle).unwrap()),
+            Err(ImportDidDocumentError::IssuerMismatch(_))
+        ));
+        assert!(repository.find_identifier(&did).await.unwrap().is_none());
+    }
+}
@@ -1,4 +1,11 @@
+pub mod cached;
+pub mod did_document;
 pub mod did_repository;
+pub mod did_url;
+pub mod fallback;
+pub mod file_store;
+pub mod multi_method;
+pub mod resolution;
 pub mod sidetree;
 
 #[cfg(test)]
@@ -0,0 +1,393 @@
+// A DidRepository resolving DIDs of several methods, dispatched on the method name.
+
+use serde::{Deserialize, Serialize};
+
+use super::did_repository::{DidRepository, DidResolver};
+use super::resolution::{ResolutionError, ResolutionErrorCode};
+use super::sidetree::payload::{DidResolutionResponse, ServiceEndpoint};
+use crate::did_key::did_repository::{DidKeyError, DidKeyRepository, PublicKeyFormat};
+use crate::did_peer::did_repository::{DidPeerError, DidPeerRepository};
+use crate::did_web::client::DidWebHttpClient;
+use crate::did_web::did_repository::{DidWebFindIdentifierError, DidWebRepository};
+use crate::did_webvh::domain::did::{Did, DidError};
+use crate::did_webvh::service::client::DidWebvhHttpClient;
+use crate::did_webvh::service::resolver::{DidWebvhResolver, ResolveIdentifierError};
+use crate::keyring::keypair::{K256KeyPair, KeyPairing};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DidMethod {
+    // Sidetree, i.e. did:nodex.
+    Nodex,
+    Webvh,
+    Web,
+    Key,
+    Peer,
+}
+
+impl DidMethod {
+    pub const ALL: [DidMethod; 5] = [
+        DidMethod::Nodex,
+        DidMethod::Webvh,
+        DidMethod::Web,
+        DidMethod::Key,
+        DidMethod::Peer,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            DidMethod::Nodex => "nodex",
+            DidMethod::Webvh => "webvh",
+            DidMethod::Web => "web",
+            DidMethod::Key => "key",
+            DidMethod::Peer => "peer",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|v| v.name() == name)
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum MultiMethodFindIdentifierError<SidetreeError, WebvhError, WebError>
+where
+    SidetreeError: std::error::Error,
+    WebvhError: std::error::Error,
+    WebError: std::error::Error,
+{
+    #[error("Failed to parse did: {0}")]
+    DidParse(#[from] DidError),
+    #[error("did method is not supported: {0}")]
+    UnsupportedMethod(String),
+    #[error("Failed to resolve did:nodex: {0}")]
+    Sidetree(SidetreeError),
+    #[error("Failed to resolve did:webvh: {0}")]
+    Webvh(ResolveIdentifierError<WebvhError>),
+    #[error("Failed to resolve did:web: {0}")]
+    Web(DidWebFindIdentifierError<WebError>),
+    #[error("Failed to resolve did:key: {0}")]
+    Key(DidKeyError),
+    #[error("Failed to resolve did:peer: {0}")]
+    Peer(DidPeerError),
+}
+
+impl<S, V, W> ResolutionError for MultiMethodFindIdentifierError<S, V, W>
+where
+    S: std::error::Error + ResolutionError,
+    V: std::error::Error,
+    W: std::error::Error,
+{
+    fn error_code(&self) -> ResolutionErrorCode {
+        match self {
+            MultiMethodFindIdentifierError::DidParse(_) => ResolutionErrorCode::InvalidDid,
+            MultiMethodFindIdentifierError::UnsupportedMethod(_) => {
+                ResolutionErrorCode::MethodNotSupported
+            }
+            MultiMethodFindIdentifierError::Sidetree(e) => e.error_code(),
+            MultiMethodFindIdentifierError::Webvh(e) => e.error_code(),
+            MultiMethodFindIdentifierError::Web(e) => e.error_code(),
+            MultiMethodFindIdentifierError::Key(e) => e.error_code(),
+            MultiMethodFindIdentifierError::Peer(e) => e.error_code(),
+        }
+    }
+}
+
+// NOTE: Only find_identifier is dispatched. The other operations manage our own DID,
+//       so they go to the Sidetree backend.
+#[derive(Clone)]
+pub struct MultiMethodDidRepository<S, V, W>
+where
+    S: DidResolver,
+    V: DidWebvhHttpClient,
+    W: DidWebHttpClient,
+{
+    sidetree: S,
+    webvh: DidWebvhResolver<V>,
+    web: DidWebRepository<W>,
+    key: DidKeyRepository,
+    peer: DidPeerRepository,
+    methods: Vec<DidMethod>,
+}
+
+impl<S, V, W> MultiMethodDidRepository<S, V, W>
+where
+    S: DidResolver,
+    V: DidWebvhHttpClient,
+    W: DidWebHttpClient,
+{
+    // Every method is enabled by default.
+    pub fn new(sidetree: S, webvh: DidWebvhResolver<V>, web: DidWebRepository<W>) -> Self {
+        Self {
+            sidetree,
+            webvh,
+            web,
+            // NOTE: get_sign_key and get_encrypt_key read publicKeyJwk.
+            key: DidKeyRepository::new().public_key_format(PublicKeyFormat::JsonWebKey2020),
+            peer: DidPeerRepository::new().public_key_format(PublicKeyFormat::JsonWebKey2020),
+            methods: DidMethod::ALL.to_vec(),
+        }
+    }
+
+    pub fn methods(mut self, methods: impl IntoIterator<Item = DidMethod>) -> Self {
+        self.methods = methods.into_iter().collect();
+        self
+    }
+
+    fn method(&self, name: &str) -> Option<DidMethod> {
+        self.methods.iter().find(|v| v.name() == name).copied()
+    }
+}
+
+impl<S, V, W> DidResolver for MultiMethodDidRepository<S, V, W>
+where
+    S: DidResolver + Send,
+    V: DidWebvhHttpClient + Send + Sync,
+    V::Error: Send + Sync,
+    W: DidWebHttpClient + Send + Sync,
+    W::Error: Send + Sync,
+{
+    type FindIdentifierError =
+        MultiMethodFindIdentifierError<S::FindIdentifierError, V::Error, W::Error>;
+
+    async fn find_identifier(
+        &self,
+        did: &str,
+    ) -> Result<Option<DidResolutionResponse>, Self::FindIdentifierError> {
+        let parsed: Did = did.parse()?;
+        let method = parsed.get_method();
+        match self.method(method) {
+            Some(DidMethod::Nodex) => self
+                .sidetree
+                .find_identifier(did)
+                .await
+                .map_err(MultiMethodFindIdentifierError::Sidetree),
+            Some(DidMethod::Webvh) => self
+                .webvh
+                .resolve_identifier(did)
+                .await
+                .map(|v| v.map(DidResolutionResponse::from))
+                .map_err(MultiMethodFindIdentifierError::Webvh),
+            Some(DidMethod::Web) => self
+                .web
+                .find_identifier(did)
+                .await
+                .map_err(MultiMethodFindIdentifierError::Web),
+            Some(DidMethod::Key) => self
+                .key
+                .find_identifier(did)
+                .await
+                .map_err(MultiMethodFindIdentifierError::Key),
+            Some(DidMethod::Peer) => self
+                .peer
+                .find_identifier(did)
+                .await
+                .map_err(MultiMethodFindIdentifierError::Peer),
+            None => Err(MultiMethodFindIdentifierError::UnsupportedMethod(
+                method.to_string(),
+            )),
+        }
+    }
+}
+
+impl<S, V, W> DidRepository for MultiMethodDidRepository<S, V, W>
+where
+    S: DidRepository + Send,
+    V: DidWebvhHttpClient + Send + Sync,
+    V::Error: Send + Sync,
+    W: DidWebHttpClient + Send + Sync,
+    W::Error: Send + Sync,
+{
+    type CreateIdentifierError = S::CreateIdentifierError;
+    type UpdateIdentifierError = S::UpdateIdentifierError;
+    type RecoverIdentifierError = S::RecoverIdentifierError;
+    type DeactivateIdentifierError = S::DeactivateIdentifierError;
+
+    async fn create_identifier(
+        &self,
+        keyring: KeyPairing,
+    ) -> Result<DidResolutionResponse, Self::CreateIdentifierError> {
+        self.sidetree.create_identifier(keyring).await
+    }
+
+    async fn update_identifier(
+        &self,
+        did: &str,
+        current_update: &K256KeyPair,
+        keyring: &KeyPairing,
+    ) -> Result<(), Self::UpdateIdentifierError> {
+        self.sidetree
+            .update_identifier(did, current_update, keyring)
+            .await
+    }
+
+    async fn recover_identifier(
+        &self,
+        did: &str,
+        current_recovery: &K256KeyPair,
+        keyring: &KeyPairing,
+    ) -> Result<(), Self::RecoverIdentifierError> {
+        self.sidetree
+            .recover_identifier(did, current_recovery, keyring)
+            .await
+    }
+
+    async fn update_services(
+        &self,
+        did: &str,
+        current_update: &K256KeyPair,
+        next_update: &K256KeyPair,
+        services: Vec<ServiceEndpoint>,
+        remove_ids: Vec<String>,
+    ) -> Result<(), Self::UpdateIdentifierError> {
+        self.sidetree
+            .update_services(did, current_update, next_update, services, remove_ids)
+            .await
+    }
+
+    async fn deactivate_identifier(
+        &self,
+        did: &str,
+        current_recovery: &K256KeyPair,
+    ) -> Result<(), Self::DeactivateIdentifierError> {
+        self.sidetree
+            .deactivate_identifier(did, current_recovery)
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{BTreeMap, HashMap};
+
+    use ed25519_dalek::SigningKey;
+    use http::StatusCode;
+    use rand_core::OsRng;
+
+    use super::*;
+    use crate::did::did_repository::mocks::MockDidRepository;
+    use crate::did::resolution::resolve_identifier;
+    use crate::did::test_utils::create_random_did;
+    use crate::did_peer::did_repository::did_peer;
+    use crate::did_web::client::DidWebHttpClientResponse;
+    use crate::did_webvh::domain::did_log::tests::genesis;
+    use crate::did_webvh::service::client::DidWebvhHttpClientResponse;
+
+    #[derive(Debug, thiserror::Error)]
+    #[error("dummy error")]
+    struct DummyError;
+
+    #[derive(Clone)]
+    struct MockHttpClient {
+        files: HashMap<String, String>,
+    }
+
+    impl MockHttpClient {
+        fn get(&self, url: &str) -> (StatusCode, String) {
+            match self.files.get(url) {
+                Some(body) => (StatusCode::OK, body.clone()),
+                None => (StatusCode::NOT_FOUND, String::new()),
+            }
+        }
+    }
+
+    impl DidWebvhHttpClient for MockHttpClient {
+        type Error = DummyError;
+        async fn get_did_log(&self, url: &str) -> Result<DidWebvhHttpClientResponse, DummyError> {
+            let (status_code, body) = self.get(url);
+            Ok(DidWebvhHttpClientResponse::new(status_code, body))
+        }
+        async fn get_did_witness(
+            &self,
+            url: &str,
+        ) -> Result<DidWebvhHttpClientResponse, DummyError> {
+            let (status_code, body) = self.get(url);
+            Ok(DidWebvhHttpClientResponse::new(status_code, body))
+        }
+    }
+
+    impl DidWebHttpClient for MockHttpClient {
+        type Error = DummyError;
+        async fn get_did_document(
+            &self,
+            url: &str,
+        ) -> Result<DidWebHttpClientResponse, DummyError> {
+            let (status_code, body) = self.get(url);
+            Ok(DidWebHttpClientResponse::new(status_code, body))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_find_identifier() {
+        let sidetree_did = create_random_did();
+        let keyring = KeyPairing::create_keyring(OsRng);
+        let sidetree =
+            MockDidRepository::from_single(BTreeMap::from([(sidetree_did.clone(), keyring)]));
+
+        let key = SigningKey::generate(&mut OsRng);
+        let (webvh_did, entry) = genesis(&key, None);
+        let web_did = "did:web:example.com:alice";
+        let client = MockHttpClient {
+            files: HashMap::from([
+                (
+                    webvh_did.did_log_url(),
+                    serde_json::to_string(&entry).unwrap(),
+                ),
+                (
+                    "https://example.com/alice/did.json".to_string(),
+                    serde_json::json!({
+                        "@context": ["https://www.w3.org/ns/did/v1"],
+                        "id": web_did,
+                    })
+                    .to_string(),
+                ),
+            ]),
+        };
+        let webvh_did = webvh_did.get_did().to_string();
+
+        let repository = MultiMethodDidRepository::new(
+            sidetree,
+            DidWebvhResolver::new(client.clone()),
+            DidWebRepository::new(client),
+        );
+        let key_did = "did:key:z6MkhaXgBZDvotDkL5257faiztiGiC2QtKLGpbnnEGta2doK";
+        let peer_did = did_peer(&KeyPairing::create_keyring(OsRng), &[]).unwrap();
+        for did in [&sidetree_did, &webvh_did, web_did, key_did, &peer_did] {
+            let response = repository.find_identifier(did).await.unwrap().unwrap();
+            assert_eq!(&response.did_document.id, did);
+        }
+        assert!(matches!(
+            repository.find_identifier("did:example:123").await,
+            Err(MultiMethodFindIdentifierError::UnsupportedMethod(method)) if method == "example"
+        ));
+        assert!(matches!(
+            repository.find_identifier("not a did").await,
+            Err(MultiMethodFindIdentifierError::DidParse(_))
+        ));
+
+        let repository = repository.methods([DidMethod::Nodex]);
+        assert!(repository
+            .find_identifier(&sidetree_did)
+            .await
+            .unwrap()
+            .is_some());
+        assert!(matches!(
+            repository.find_identifier(&webvh_did).await,
+            Err(MultiMethodFindIdentifierError::UnsupportedMethod(method)) if method == "webvh"
+        ));
+        assert_eq!(
+            resolve_identifier(&repository, &webvh_did)
+                .await
+                .error_code(),
+            Some(ResolutionErrorCode::MethodNotSupported)
+        );
+    }
+
+    #[test]
+    fn test_did_method_serde() {
+        let methods: Vec<DidMethod> =
+            serde_json::from_str(r#"["nodex", "webvh", "web", "key", "peer"]"#).unwrap();
+        assert_eq!(methods, DidMethod::ALL.to_vec());
+        assert!(serde_json::from_str::<DidMethod>(r#""ion""#).is_err());
+    }
+}
@@ -0,0 +1,175 @@
+// DID resolution results with metadata, telling e.g. a deactivated DID from a network failure.
+// https://w3c-ccg.github.io/did-resolution/#did-resolution-result
+
+use serde::{Deserialize, Serialize};
+
+use super::did_document::DidDocument;
+use super::did_repository::DidResolver;
+use super::sidetree::payload::{DidResolutionResponse, MethodMetadata};
+use crate::did_webvh::domain::did::Did;
+
+pub const DID_RESOLUTION_CONTEXT: &str = "https://w3id.org/did-resolution/v1";
+pub const DID_LD_JSON: &str = "application/did+ld+json";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ResolutionErrorCode {
+    InvalidDid,
+    NotFound,
+    MethodNotSupported,
+    Deactivated,
+    InternalError,
+}
+
+// Classifies errors of find_identifier. Anything but a known failure is an internal error.
+pub trait ResolutionError {
+    fn error_code(&self) -> ResolutionErrorCode {
+        ResolutionErrorCode::InternalError
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DidResolutionMetadata {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content_type: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<ResolutionErrorCode>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error_message: Option<String>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DidDocumentMetadata {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub created: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub updated: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub deactivated: Option<bool>,
+    // NOTE: Sidetree specific, e.g. whether the DID is anchored.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub method: Option<MethodMetadata>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DidResolutionResult {
+    #[serde(rename = "@context")]
+    pub context: String,
+    pub did_document: Option<DidDocument>,
+    pub did_resolution_metadata: DidResolutionMetadata,
+    pub did_document_metadata: DidDocumentMetadata,
+}
+
+impl DidResolutionResult {
+    pub fn error(code: ResolutionErrorCode, message: impl Into<String>) -> Self {
+        Self {
+            context: DID_RESOLUTION_CONTEXT.to_string(),
+            did_document: None,
+            did_resolution_metadata: DidResolutionMetadata {
+                content_type: None,
+                error: Some(code),
+                error_message: Some(message.into()),
+            },
+            did_document_metadata: DidDocumentMetadata::default(),
+        }
+    }
+
+    pub fn error_code(&self) -> Option<ResolutionErrorCode> {
+        self.did_resolution_metadata.error
+    }
+}
+
+// NOTE: The document of a deactivated DID is kept along with the error.
+impl From<DidResolutionResponse> for DidResolutionResult {
+    fn from(response: DidResolutionResponse) -> Self {
+        let deactivated = response.is_deactivated();
+        let mut metadata = response.did_document_metadata.unwrap_or_default();
+        metadata.method = Some(response.method_metadata);
+        if deactivated {
+            metadata.deactivated = Some(true);
+        }
+        Self {
+            context: DID_RESOLUTION_CONTEXT.to_string(),
+            did_document: Some(response.did_document),
+            did_resolution_metadata: DidResolutionMetadata {
+                content_type: Some(DID_LD_JSON.to_string()),
+                error: deactivated.then_some(ResolutionErrorCode::Deactivated),
+                error_message: None,
+            },
+            did_document_metadata: metadata,
+        }
+    }
+}
+
+// Resolves `did` with `repository`, turning every failure into an error code.
+pub async fn resolve_identifier<R: DidResolver>(repository: &R, did: &str) -> DidResolutionResult {
+    if let Err(e) = did.parse::<Did>() {
+        return DidResolutionResult::error(ResolutionErrorCode::InvalidDid, e.to_string());
+    }
+    match repository.find_identifier(did).await {
+        Ok(Some(response)) => response.into(),
+        Ok(None) => DidResolutionResult::error(
+            ResolutionErrorCode::NotFound,
+            format!("did not found: {}", did),
+        ),
+        Err(e) => DidResolutionResult::error(e.error_code(), e.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use rand_core::OsRng;
+
+    use super::*;
+    use crate::did::did_repository::mocks::MockDidRepository;
+    use crate::did::test_utils::create_random_did;
+    use crate::keyring::keypair::KeyPairing;
+
+    #[tokio::test]
+    async fn test_resolve_identifier() {
+        let did = create_random_did();
+        let keyring = KeyPairing::create_keyring(OsRng);
+        let mock = MockDidRepository::from_single(BTreeMap::from([(did.clone(), keyring)]));
+
+        let result = resolve_identifier(&mock, &did).await;
+        assert_eq!(result.error_code(), None);
+        assert_eq!(result.did_document.unwrap().id, did);
+        assert_eq!(
+            result.did_resolution_metadata.content_type.as_deref(),
+            Some(DID_LD_JSON)
+        );
+        assert!(result.did_document_metadata.method.unwrap().published);
+
+        let result = resolve_identifier(&mock, &create_random_did()).await;
+        assert_eq!(result.error_code(), Some(ResolutionErrorCode::NotFound));
+        assert!(result.did_document.is_none());
+
+        let result = resolve_identifier(&mock, "did:nodex").await;
+        assert_eq!(result.error_code(), Some(ResolutionErrorCode::InvalidDid));
+    }
+
+    #[tokio::test]
+    async fn test_resolve_deactivated_identifier() {
+        let did = create_random_did();
+        let keyring = KeyPairing::create_keyring(OsRng);
+        let mock = MockDidRepository::from_single(BTreeMap::from([(did.clone(), keyring)]));
+        let mut response = mock.find_identifier(&did).await.unwrap().unwrap();
+        response.did_document.deactivated = Some(true);
+
+        let result = DidResolutionResult::from(response);
+        assert_eq!(result.error_code(), Some(ResolutionErrorCode::Deactivated));
+        assert_eq!(result.did_document_metadata.deactivated, Some(true));
+        assert!(result.did_document.is_some());
+
+        let json = serde_json::to_value(&result).unwrap();
+        assert_eq!(json["didResolutionMetadata"]["error"], "deactivated");
+        assert_eq!(json["didDocumentMetadata"]["deactivated"], true);
+    }
+}
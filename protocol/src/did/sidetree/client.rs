@@ -19,6 +19,18 @@ pub trait SidetreeHttpClient {
         &self,
         body: &str,
     ) -> Result<SidetreeHttpClientResponse, Self::Error>;
+    async fn post_update_identifier(
+        &self,
+        body: &str,
+    ) -> Result<SidetreeHttpClientResponse, Self::Error>;
+    async fn post_recover_identifier(
+        &self,
+        body: &str,
+    ) -> Result<SidetreeHttpClientResponse, Self::Error>;
+    async fn post_deactivate_identifier(
+        &self,
+        body: &str,
+    ) -> Result<SidetreeHttpClientResponse, Self::Error>;
     async fn get_find_identifier(
         &self,
         did: &str,
@@ -1,30 +1,39 @@
-use std::convert::TryInto;
-
 use data_encoding::BASE64URL_NOPAD;
-use sha2::{Digest, Sha256};
 
-const MULTIHASH_SHA256_CODE: u8 = 0x12; // 0x12 = 18
+use crate::keyring::multihash::{self, HashAlgorithm};
 
-// [NOTE]: SHA2-256 ONLY
-pub fn hash(message: &[u8]) -> Vec<u8> {
-    let mut prefix = Vec::from([MULTIHASH_SHA256_CODE]);
-    let mut hashed = Sha256::digest(message).to_vec();
-    prefix.push(hashed.len().try_into().unwrap());
-    prefix.append(&mut hashed);
-    prefix
+// NOTE: `algorithm` is `hashAlgorithmInMultihashCode` of the Sidetree protocol parameters.
+pub fn hash(algorithm: HashAlgorithm, message: &[u8]) -> Vec<u8> {
+    algorithm.multihash(message)
 }
 
-pub fn double_hash_encode(message: &[u8]) -> String {
-    let mes = Sha256::digest(message).to_vec();
-    let mes = hash(&mes);
+// The commitment of a reveal value, hashed twice with the same algorithm.
+pub fn double_hash_encode(algorithm: HashAlgorithm, message: &[u8]) -> String {
+    let mes = algorithm.digest(message);
+    let mes = hash(algorithm, &mes);
     BASE64URL_NOPAD.encode(&mes)
 }
 
-pub fn hash_encode(message: &[u8]) -> String {
-    let mes = hash(message);
+pub fn hash_encode(algorithm: HashAlgorithm, message: &[u8]) -> String {
+    let mes = hash(algorithm, message);
     BASE64URL_NOPAD.encode(&mes)
 }
 
+// Whether `encoded` is `hash_encode` of `message` with any supported algorithm.
+pub fn verify_encoded(encoded: &str, message: &[u8]) -> bool {
+    BASE64URL_NOPAD
+        .decode(encoded.as_bytes())
+        .is_ok_and(|v| multihash::verify(&v, message))
+}
+
+// The algorithm of `hash_encode` result, e.g. of a DID suffix.
+pub fn encoded_algorithm(encoded: &str) -> Option<HashAlgorithm> {
+    let decoded = BASE64URL_NOPAD.decode(encoded.as_bytes()).ok()?;
+    multihash::decode(&decoded)
+        .ok()
+        .map(|(algorithm, _)| algorithm)
+}
+
 #[cfg(test)]
 mod tests {
 
@@ -36,7 +45,7 @@ mod tests {
 
     #[test]
     fn test_hash() {
-        let result = hash(message().as_bytes());
+        let result = hash(HashAlgorithm::Sha256, message().as_bytes());
         assert_eq!(
             result,
             vec![
@@ -49,7 +58,7 @@ mod tests {
 
     #[test]
     fn test_double_hash_then_encode() {
-        let result = double_hash_encode(message().as_bytes());
+        let result = double_hash_encode(HashAlgorithm::Sha256, message().as_bytes());
         assert_eq!(
             result,
             String::from("EiAEX1W46vVid7IjJyFY5ibjmyrgepTjW0rYrw-wo4xLCw")
@@ -58,10 +67,22 @@ mod tests {
 
     #[test]
     fn test_hash_then_encode() {
-        let result = hash_encode(message().as_bytes());
+        let result = hash_encode(HashAlgorithm::Sha256, message().as_bytes());
         assert_eq!(
             result,
             String::from("EiBfRiXU9h7bUngHRV9I-L4njnHoSqlNIxEf-rO2MJOnEw")
         );
     }
+
+    #[test]
+    fn test_verify_encoded() {
+        for algorithm in HashAlgorithm::ALL {
+            let encoded = hash_encode(algorithm, message().as_bytes());
+            assert!(verify_encoded(&encoded, message().as_bytes()));
+            assert!(!verify_encoded(&encoded, b"other"));
+            assert_eq!(encoded_algorithm(&encoded), Some(algorithm));
+        }
+        assert!(!verify_encoded("invalid", message().as_bytes()));
+        assert_eq!(encoded_algorithm("invalid"), None);
+    }
 }
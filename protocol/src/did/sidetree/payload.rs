@@ -6,7 +6,13 @@ use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
 use crate::{
-    did::sidetree::multihash, keyring::jwk::Jwk, verifiable_credentials::jws::JwsEncodeError,
+    did::{
+        did_document::{DidDocument, ServiceEndpointValue},
+        resolution::DidDocumentMetadata,
+        sidetree::multihash,
+    },
+    keyring::{jwk::Jwk, multihash::HashAlgorithm},
+    verifiable_credentials::jws::JwsEncodeError,
 };
 
 // TODO: Migrate Sidetree Version
@@ -20,7 +26,7 @@ pub struct ServiceEndpoint {
     pub r#type: String,
 
     #[serde(rename = "serviceEndpoint")]
-    pub service_endpoint: String,
+    pub service_endpoint: ServiceEndpointValue,
 
     #[serde(rename = "description")]
     pub description: Option<String>,
@@ -37,12 +43,22 @@ pub struct DidPublicKey {
     #[serde(rename = "type")]
     pub r#type: String,
 
-    #[serde(rename = "publicKeyJwk")]
-    pub public_key_jwk: Jwk,
+    #[serde(rename = "publicKeyJwk", skip_serializing_if = "Option::is_none")]
+    pub public_key_jwk: Option<Jwk>,
+
+    #[serde(rename = "publicKeyMultibase", skip_serializing_if = "Option::is_none")]
+    pub public_key_multibase: Option<String>,
+
+    #[serde(rename = "publicKeyBase58", skip_serializing_if = "Option::is_none")]
+    pub public_key_base58: Option<String>,
+
+    #[serde(rename = "purposes", default, skip_serializing_if = "Option::is_none")]
+    pub purposes: Option<Vec<String>>,
 }
 
+// The DID document of a Sidetree node, converted into `did_document::DidDocument`.
 #[derive(Clone, Debug, Serialize, Deserialize)]
-pub struct DidDocument {
+pub struct SidetreeDidDocument {
     // TODO: impl parser for mixed type
     // #[serde(rename = "@context")]
     // context: String,
@@ -123,6 +139,11 @@ pub enum DidAction {
         #[serde(rename = "public_keys")]
         public_keys: Vec<PublicKeyPayload>,
     },
+    // NOTE: Services with an existing id are replaced.
+    #[serde(rename = "add-services")]
+    AddServices { services: Vec<ServiceEndpoint> },
+    #[serde(rename = "remove-services")]
+    RemoveServices { ids: Vec<String> },
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -137,7 +158,7 @@ struct DidSuffixObject {
     recovery_commitment: String,
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct MethodMetadata {
     #[serde(rename = "published")]
     pub published: bool,
@@ -147,6 +168,10 @@ pub struct MethodMetadata {
 
     #[serde(rename = "updateCommitment")]
     pub update_commitment: Option<String>,
+
+    // Seconds the resolution may be cached for, e.g. did:webvh `ttl`.
+    #[serde(rename = "ttl", default, skip_serializing_if = "Option::is_none")]
+    pub ttl: Option<u32>,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -159,6 +184,49 @@ pub struct DidResolutionResponse {
 
     #[serde(rename = "methodMetadata")]
     pub method_metadata: MethodMetadata,
+
+    #[serde(
+        rename = "didDocumentMetadata",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub did_document_metadata: Option<DidDocumentMetadata>,
+}
+
+impl DidResolutionResponse {
+    // NOTE: Deactivated Sidetree DIDs are not resolved at all, but did:webvh ones
+    //       are resolved to their last state.
+    pub fn is_deactivated(&self) -> bool {
+        self.did_document.deactivated == Some(true)
+            || self
+                .did_document_metadata
+                .as_ref()
+                .is_some_and(|v| v.deactivated == Some(true))
+    }
+}
+
+// The resolution response of a Sidetree node.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SidetreeDidResolutionResponse {
+    #[serde(rename = "@context")]
+    pub context: String,
+
+    #[serde(rename = "didDocument")]
+    pub did_document: SidetreeDidDocument,
+
+    #[serde(rename = "methodMetadata")]
+    pub method_metadata: MethodMetadata,
+}
+
+impl From<SidetreeDidResolutionResponse> for DidResolutionResponse {
+    fn from(response: SidetreeDidResolutionResponse) -> Self {
+        DidResolutionResponse {
+            context: response.context,
+            did_document: response.did_document.into(),
+            method_metadata: response.method_metadata,
+            did_document_metadata: None,
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -176,6 +244,21 @@ enum DidPayload {
         #[serde(rename = "signed_data")]
         signed_data: String,
     },
+    #[serde(rename = "recover")]
+    Recover {
+        delta: String,
+        #[serde(rename = "did_suffix")]
+        did_suffix: String,
+        #[serde(rename = "signed_data")]
+        signed_data: String,
+    },
+    #[serde(rename = "deactivate")]
+    Deactivate {
+        #[serde(rename = "did_suffix")]
+        did_suffix: String,
+        #[serde(rename = "signed_data")]
+        signed_data: String,
+    },
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -184,7 +267,7 @@ pub struct DidCreateResponse {
     pub context: String,
 
     #[serde(rename = "didDocument")]
-    pub did_document: DidDocument,
+    pub did_document: SidetreeDidDocument,
 
     #[serde(rename = "methodMetadata")]
     pub method_metadata: MethodMetadata,
@@ -207,17 +290,34 @@ where
 }
 
 #[inline]
-fn commitment_scheme(value: &Jwk) -> Result<String, serde_json::Error> {
-    Ok(multihash::double_hash_encode(&canon(value)?))
+fn commitment_scheme(
+    value: &Jwk,
+    hash_algorithm: HashAlgorithm,
+) -> Result<String, serde_json::Error> {
+    Ok(multihash::double_hash_encode(
+        hash_algorithm,
+        &canon(value)?,
+    ))
+}
+
+// Whether `commitment`, e.g. the update commitment of a DID, commits to `key`.
+pub fn verify_commitment(commitment: &str, key: k256::PublicKey) -> bool {
+    let (Some(hash_algorithm), Ok(key)) =
+        (multihash::encoded_algorithm(commitment), Jwk::try_from(key))
+    else {
+        return false;
+    };
+    commitment_scheme(&key, hash_algorithm).is_ok_and(|v| v == commitment)
 }
 
 pub fn did_create_payload(
     replace_payload: DidPatchDocument,
     update_key: k256::PublicKey,
     recovery_key: k256::PublicKey,
+    hash_algorithm: HashAlgorithm,
 ) -> Result<String, DidCreatePayloadError> {
-    let update_commitment = commitment_scheme(&update_key.try_into()?)?;
-    let recovery_commitment = commitment_scheme(&recovery_key.try_into()?)?;
+    let update_commitment = commitment_scheme(&update_key.try_into()?, hash_algorithm)?;
+    let recovery_commitment = commitment_scheme(&recovery_key.try_into()?, hash_algorithm)?;
     let patch = DidAction::Replace {
         document: replace_payload,
     };
@@ -226,7 +326,7 @@ pub fn did_create_payload(
         update_commitment,
     };
     let delta = canon(&delta)?;
-    let delta_hash = multihash::hash_encode(&delta);
+    let delta_hash = multihash::hash_encode(hash_algorithm, &delta);
 
     let suffix = DidSuffixObject {
         delta_hash,
@@ -253,18 +353,140 @@ pub fn parse_did(did: &str) -> Option<(String, String)> {
     }
 }
 
+// NOTE: The method specific id may be prefixed with the network, e.g. `test:<suffix>`,
+//       and followed by the create operation of a long-form DID.
 pub fn get_did_suffix(method_specific_id: &str) -> Option<String> {
-    let ret: Vec<&str> = method_specific_id.splitn(2, ':').collect();
-    if ret.len() == 2 || ret.len() == 1 {
-        Some(ret[0].to_string())
-    } else {
-        None
+    split_long_form(method_specific_id)
+        .map_or(method_specific_id, |(short, _)| short)
+        .rsplit(':')
+        .next()
+        .filter(|v| !v.is_empty())
+        .map(|v| v.to_string())
+}
+
+#[derive(Debug, Error)]
+pub enum DidLongFormError {
+    #[error("Failed to decode: {0}")]
+    Decode(#[from] data_encoding::DecodeError),
+    #[error(transparent)]
+    SerdeJsonError(#[from] serde_json::Error),
+    #[error("not a create operation")]
+    NotCreate,
+    #[error("did suffix does not match the create operation")]
+    SuffixMismatch,
+    #[error("delta does not match the create operation")]
+    DeltaMismatch,
+}
+
+// Splits `<short form>:<base64url(create payload)>` into the short form and the create operation.
+fn split_long_form(did: &str) -> Option<(&str, DidPayload)> {
+    let (short, encoded) = did.rsplit_once(':')?;
+    let payload = BASE64URL_NOPAD.decode(encoded.as_bytes()).ok()?;
+    match serde_json::from_slice(&payload).ok()? {
+        payload @ DidPayload::Create { .. } => Some((short, payload)),
+        _ => None,
     }
 }
 
+// `did` itself unless it is a long-form DID.
+pub fn get_short_form_did(did: &str) -> &str {
+    split_long_form(did).map_or(did, |(short, _)| short)
+}
+
+// The suffix data of a create operation, whose hash must be the suffix of `did`.
+// NOTE: The hash algorithm is the one of the suffix, so any supported one is accepted.
+fn verify_create_suffix(did: &str, suffix_data: &str) -> Result<DidSuffixObject, DidLongFormError> {
+    let suffix_data = BASE64URL_NOPAD.decode(suffix_data.as_bytes())?;
+    let (_, method_specific_id) = parse_did(did).ok_or(DidLongFormError::SuffixMismatch)?;
+    match get_did_suffix(&method_specific_id) {
+        Some(suffix) if multihash::verify_encoded(&suffix, &suffix_data) => {
+            Ok(serde_json::from_slice(&suffix_data)?)
+        }
+        _ => Err(DidLongFormError::SuffixMismatch),
+    }
+}
+
+// The long-form of `did`, i.e. `<did>:<base64url(create payload)>`, which is resolvable
+// from itself before the create operation is anchored.
+pub fn did_long_form(did: &str, create_payload: &str) -> Result<String, DidLongFormError> {
+    let DidPayload::Create { suffix_data, .. } = serde_json::from_str(create_payload)? else {
+        return Err(DidLongFormError::NotCreate);
+    };
+    verify_create_suffix(did, &suffix_data)?;
+    Ok(format!(
+        "{}:{}",
+        did,
+        BASE64URL_NOPAD.encode(create_payload.as_bytes())
+    ))
+}
+
+// Resolves a long-form DID from its create operation. Returns None for a short-form DID.
+pub fn resolve_long_form_did(did: &str) -> Result<Option<DidResolutionResponse>, DidLongFormError> {
+    let Some((short, DidPayload::Create { delta, suffix_data })) = split_long_form(did) else {
+        return Ok(None);
+    };
+    let suffix_data = verify_create_suffix(short, &suffix_data)?;
+    let delta = BASE64URL_NOPAD.decode(delta.as_bytes())?;
+    if !multihash::verify_encoded(&suffix_data.delta_hash, &delta) {
+        return Err(DidLongFormError::DeltaMismatch);
+    }
+    let delta: DidDeltaObject = serde_json::from_slice(&delta)?;
+
+    let mut public_keys: Vec<PublicKeyPayload> = vec![];
+    let mut service_endpoints: Vec<ServiceEndpoint> = vec![];
+    for patch in delta.patches {
+        match patch {
+            DidAction::Replace { document } => {
+                public_keys = document.public_keys;
+                service_endpoints = document.service_endpoints;
+            }
+            DidAction::AddPublicKeys { public_keys: added } => {
+                public_keys.retain(|v| added.iter().all(|w| w.id != v.id));
+                public_keys.extend(added);
+            }
+            DidAction::AddServices { services } => {
+                service_endpoints.retain(|v| services.iter().all(|w| w.id != v.id));
+                service_endpoints.extend(services);
+            }
+            DidAction::RemoveServices { ids } => {
+                service_endpoints.retain(|v| !ids.contains(&v.id));
+            }
+        }
+    }
+    let public_key = public_keys
+        .into_iter()
+        .map(|v| DidPublicKey {
+            id: format!("#{}", v.id),
+            controller: String::new(),
+            r#type: v.r#type,
+            public_key_jwk: Some(v.jwk),
+            public_key_multibase: None,
+            public_key_base58: None,
+            purposes: Some(v.purpose),
+        })
+        .collect();
+
+    Ok(Some(DidResolutionResponse {
+        context: "https://www.w3.org/ns/did-resolution/v1".to_string(),
+        did_document: SidetreeDidDocument {
+            id: did.to_string(),
+            public_key: Some(public_key),
+            service: Some(service_endpoints),
+            authentication: None,
+        }
+        .into(),
+        method_metadata: MethodMetadata {
+            published: false,
+            recovery_commitment: Some(suffix_data.recovery_commitment),
+            update_commitment: Some(delta.update_commitment),
+            ttl: None,
+        },
+        did_document_metadata: None,
+    }))
+}
+
 fn sign(
-    delta_hash: String,
-    old_public_key: Jwk,
+    object: serde_json::Value,
     old_secret_key: &k256::SecretKey,
 ) -> Result<String, JwsEncodeError> {
     // NOTE: header
@@ -272,7 +494,6 @@ fn sign(
     let header = serde_jcs::to_string(&header)?;
     let header = BASE64URL_NOPAD.encode(header.as_bytes());
     // NOTE: payload
-    let object = serde_json::json!({"delta_hash": delta_hash, "update_key": old_public_key});
     let payload = BASE64URL_NOPAD.encode(object.to_string().as_bytes());
     // NOTE: message
     let message = [header.clone(), payload.clone()].join(".");
@@ -298,22 +519,22 @@ pub enum DidUpdatePayloadError {
     Sign(#[from] JwsEncodeError),
 }
 
-// TODO: Not yet tested because sidetree is broken.
 pub fn did_update_payload(
     update_payload: Vec<DidAction>,
     my_did: &str,
     old_update: k256::PublicKey,
     old_update_secret: &k256::SecretKey,
     new_update: k256::PublicKey,
+    hash_algorithm: HashAlgorithm,
 ) -> Result<String, DidUpdatePayloadError> {
     let old_update: Jwk = old_update.try_into()?;
-    let new_update = commitment_scheme(&new_update.try_into()?)?;
+    let new_update = commitment_scheme(&new_update.try_into()?, hash_algorithm)?;
     let delta = DidDeltaObject {
         patches: update_payload,
         update_commitment: new_update,
     };
     let delta = canon(&delta)?;
-    let delta_hash = multihash::hash_encode(&delta);
+    let delta_hash = multihash::hash_encode(hash_algorithm, &delta);
     let encoded_delta = BASE64URL_NOPAD.encode(&delta);
     let (_, suff) = parse_did(my_did).ok_or(DidUpdatePayloadError::DidParse)?;
     let suff = get_did_suffix(&suff).ok_or(DidUpdatePayloadError::DidParse)?;
@@ -322,7 +543,95 @@ pub fn did_update_payload(
         delta: encoded_delta,
         did_suffix: suff,
         // reveal_value: multihash::hash_encode(&canon(&old_update)?),
-        signed_data: sign(delta_hash, old_update, old_update_secret)?,
+        signed_data: sign(
+            serde_json::json!({"delta_hash": delta_hash, "update_key": old_update}),
+            old_update_secret,
+        )?,
+    };
+    Ok(serde_jcs::to_string(&payload)?)
+}
+
+#[derive(Debug, Error)]
+pub enum DidRecoverPayloadError {
+    #[error(transparent)]
+    SerdeJsonError(#[from] serde_json::Error),
+    #[error("Failed to convert to JWK: {0}")]
+    Jwk(#[from] crate::keyring::jwk::K256ToJwkError),
+    #[error("Failed to parse did")]
+    DidParse,
+    #[error("Failed to sign: {0}")]
+    Sign(#[from] JwsEncodeError),
+}
+
+// Replaces the whole document and both commitments. Signed with the current recovery key.
+pub fn did_recover_payload(
+    replace_payload: DidPatchDocument,
+    my_did: &str,
+    old_recovery: k256::PublicKey,
+    old_recovery_secret: &k256::SecretKey,
+    new_update: k256::PublicKey,
+    new_recovery: k256::PublicKey,
+    hash_algorithm: HashAlgorithm,
+) -> Result<String, DidRecoverPayloadError> {
+    let old_recovery: Jwk = old_recovery.try_into()?;
+    let update_commitment = commitment_scheme(&new_update.try_into()?, hash_algorithm)?;
+    let recovery_commitment = commitment_scheme(&new_recovery.try_into()?, hash_algorithm)?;
+    let patch = DidAction::Replace {
+        document: replace_payload,
+    };
+    let delta = DidDeltaObject {
+        patches: vec![patch],
+        update_commitment,
+    };
+    let delta = canon(&delta)?;
+    let delta_hash = multihash::hash_encode(hash_algorithm, &delta);
+    let encoded_delta = BASE64URL_NOPAD.encode(&delta);
+    let (_, suff) = parse_did(my_did).ok_or(DidRecoverPayloadError::DidParse)?;
+    let suff = get_did_suffix(&suff).ok_or(DidRecoverPayloadError::DidParse)?;
+
+    let payload = DidPayload::Recover {
+        delta: encoded_delta,
+        did_suffix: suff,
+        signed_data: sign(
+            serde_json::json!({
+                "delta_hash": delta_hash,
+                "recovery_key": old_recovery,
+                "recovery_commitment": recovery_commitment,
+            }),
+            old_recovery_secret,
+        )?,
+    };
+    Ok(serde_jcs::to_string(&payload)?)
+}
+
+#[derive(Debug, Error)]
+pub enum DidDeactivatePayloadError {
+    #[error(transparent)]
+    SerdeJsonError(#[from] serde_json::Error),
+    #[error("Failed to convert to JWK: {0}")]
+    Jwk(#[from] crate::keyring::jwk::K256ToJwkError),
+    #[error("Failed to parse did")]
+    DidParse,
+    #[error("Failed to sign: {0}")]
+    Sign(#[from] JwsEncodeError),
+}
+
+// NOTE: Deactivation is permanent, the DID can not be recovered afterwards.
+pub fn did_deactivate_payload(
+    my_did: &str,
+    old_recovery: k256::PublicKey,
+    old_recovery_secret: &k256::SecretKey,
+) -> Result<String, DidDeactivatePayloadError> {
+    let old_recovery: Jwk = old_recovery.try_into()?;
+    let (_, suff) = parse_did(my_did).ok_or(DidDeactivatePayloadError::DidParse)?;
+    let suff = get_did_suffix(&suff).ok_or(DidDeactivatePayloadError::DidParse)?;
+
+    let payload = DidPayload::Deactivate {
+        signed_data: sign(
+            serde_json::json!({"did_suffix": suff, "recovery_key": old_recovery}),
+            old_recovery_secret,
+        )?,
+        did_suffix: suff,
     };
     Ok(serde_jcs::to_string(&payload)?)
 }
@@ -350,6 +659,257 @@ pub mod tests {
             service_endpoints: vec![],
         };
 
-        let _result = did_create_payload(document, update, recovery).unwrap();
+        let _result =
+            did_create_payload(document, update, recovery, HashAlgorithm::default()).unwrap();
+    }
+
+    #[test]
+    pub fn test_did_update_payload() {
+        let old = keyring::keypair::KeyPairing::create_keyring(OsRng);
+        let new = keyring::keypair::KeyPairing::create_keyring(OsRng);
+        let public = new
+            .sign
+            .get_public_key()
+            .to_public_key(
+                "".to_string(),
+                "signingKey".to_string(),
+                vec!["".to_string()],
+            )
+            .unwrap();
+        let patch = DidAction::AddPublicKeys {
+            public_keys: vec![public],
+        };
+
+        let result = did_update_payload(
+            vec![patch],
+            "did:nodex:test:EiBprXreMiba4loyl3psXm0RsECdtlCiQIjM8G9BtdQplA",
+            old.update.get_public_key(),
+            &old.update.get_secret_key(),
+            new.update.get_public_key(),
+            HashAlgorithm::default(),
+        )
+        .unwrap();
+
+        let DidPayload::Update {
+            delta,
+            did_suffix,
+            signed_data,
+        } = serde_json::from_str(&result).unwrap()
+        else {
+            panic!("not an update operation");
+        };
+        assert_eq!(did_suffix, "EiBprXreMiba4loyl3psXm0RsECdtlCiQIjM8G9BtdQplA");
+
+        let delta = BASE64URL_NOPAD.decode(delta.as_bytes()).unwrap();
+        let delta: DidDeltaObject = serde_json::from_slice(&delta).unwrap();
+        let new_update: Jwk = new.update.get_public_key().try_into().unwrap();
+        assert_eq!(
+            delta.update_commitment,
+            commitment_scheme(&new_update, HashAlgorithm::default()).unwrap()
+        );
+        assert!(verify_commitment(
+            &delta.update_commitment,
+            new.update.get_public_key()
+        ));
+        assert!(!verify_commitment(
+            &delta.update_commitment,
+            old.update.get_public_key()
+        ));
+
+        let payload = decode_signed_data(&signed_data);
+        let old_update: Jwk = old.update.get_public_key().try_into().unwrap();
+        assert_eq!(
+            payload["update_key"],
+            serde_json::to_value(&old_update).unwrap()
+        );
+
+        assert!(did_update_payload(
+            vec![],
+            "invalid",
+            old.update.get_public_key(),
+            &old.update.get_secret_key(),
+            new.update.get_public_key(),
+            HashAlgorithm::default(),
+        )
+        .is_err());
+    }
+
+    #[test]
+    pub fn test_did_action_services() {
+        let add = DidAction::AddServices {
+            services: vec![ServiceEndpoint {
+                id: "didcomm".to_string(),
+                r#type: "DIDCommMessaging".to_string(),
+                service_endpoint: "https://example.com/didcomm".into(),
+                description: None,
+            }],
+        };
+        let remove = DidAction::RemoveServices {
+            ids: vec!["didcomm".to_string()],
+        };
+        assert_eq!(
+            serde_json::to_value(&add).unwrap(),
+            serde_json::json!({
+                "action": "add-services",
+                "services": [{
+                    "id": "didcomm",
+                    "type": "DIDCommMessaging",
+                    "serviceEndpoint": "https://example.com/didcomm",
+                    "description": null,
+                }],
+            })
+        );
+        assert_eq!(
+            serde_json::to_value(&remove).unwrap(),
+            serde_json::json!({"action": "remove-services", "ids": ["didcomm"]})
+        );
+    }
+
+    fn decode_signed_data(signed_data: &str) -> serde_json::Value {
+        let signed_data: Vec<&str> = signed_data.split('.').collect();
+        assert_eq!(signed_data.len(), 3);
+        let payload = BASE64URL_NOPAD.decode(signed_data[1].as_bytes()).unwrap();
+        serde_json::from_slice(&payload).unwrap()
+    }
+
+    #[test]
+    pub fn test_did_recover_payload() {
+        let old = keyring::keypair::KeyPairing::create_keyring(OsRng);
+        let new = keyring::keypair::KeyPairing::create_keyring(OsRng);
+        let document = DidPatchDocument {
+            public_keys: vec![],
+            service_endpoints: vec![],
+        };
+
+        let result = did_recover_payload(
+            document,
+            "did:nodex:test:EiBprXreMiba4loyl3psXm0RsECdtlCiQIjM8G9BtdQplA",
+            old.recovery.get_public_key(),
+            &old.recovery.get_secret_key(),
+            new.update.get_public_key(),
+            new.recovery.get_public_key(),
+            HashAlgorithm::default(),
+        )
+        .unwrap();
+
+        let DidPayload::Recover {
+            delta,
+            did_suffix,
+            signed_data,
+        } = serde_json::from_str(&result).unwrap()
+        else {
+            panic!("not a recover operation");
+        };
+        assert_eq!(did_suffix, "EiBprXreMiba4loyl3psXm0RsECdtlCiQIjM8G9BtdQplA");
+
+        let delta = BASE64URL_NOPAD.decode(delta.as_bytes()).unwrap();
+        let delta: DidDeltaObject = serde_json::from_slice(&delta).unwrap();
+        let new_update: Jwk = new.update.get_public_key().try_into().unwrap();
+        assert_eq!(
+            delta.update_commitment,
+            commitment_scheme(&new_update, HashAlgorithm::default()).unwrap()
+        );
+
+        let payload = decode_signed_data(&signed_data);
+        let old_recovery: Jwk = old.recovery.get_public_key().try_into().unwrap();
+        let new_recovery: Jwk = new.recovery.get_public_key().try_into().unwrap();
+        assert_eq!(
+            payload["recovery_key"],
+            serde_json::to_value(&old_recovery).unwrap()
+        );
+        assert_eq!(
+            payload["recovery_commitment"],
+            commitment_scheme(&new_recovery, HashAlgorithm::default()).unwrap()
+        );
+    }
+
+    #[test]
+    pub fn test_did_deactivate_payload() {
+        let keyring = keyring::keypair::KeyPairing::create_keyring(OsRng);
+
+        let result = did_deactivate_payload(
+            "did:nodex:test:EiBprXreMiba4loyl3psXm0RsECdtlCiQIjM8G9BtdQplA",
+            keyring.recovery.get_public_key(),
+            &keyring.recovery.get_secret_key(),
+        )
+        .unwrap();
+
+        let DidPayload::Deactivate {
+            did_suffix,
+            signed_data,
+        } = serde_json::from_str(&result).unwrap()
+        else {
+            panic!("not a deactivate operation");
+        };
+        let payload = decode_signed_data(&signed_data);
+        assert_eq!(payload["did_suffix"], did_suffix);
+    }
+
+    #[test]
+    pub fn test_did_long_form() {
+        let keyring = keyring::keypair::KeyPairing::create_keyring(OsRng);
+        let public = keyring
+            .sign
+            .get_public_key()
+            .to_public_key(
+                "".to_string(),
+                "signingKey".to_string(),
+                vec!["auth".to_string(), "general".to_string()],
+            )
+            .unwrap();
+        // NOTE: The hash algorithm is told by the suffix, so any supported one resolves.
+        for hash_algorithm in HashAlgorithm::ALL {
+            let document = DidPatchDocument {
+                public_keys: vec![public.clone()],
+                service_endpoints: vec![],
+            };
+            let payload = did_create_payload(
+                document,
+                keyring.update.get_public_key(),
+                keyring.recovery.get_public_key(),
+                hash_algorithm,
+            )
+            .unwrap();
+            let DidPayload::Create { suffix_data, .. } = serde_json::from_str(&payload).unwrap()
+            else {
+                panic!("not a create operation");
+            };
+            let suffix = multihash::hash_encode(
+                hash_algorithm,
+                &BASE64URL_NOPAD.decode(suffix_data.as_bytes()).unwrap(),
+            );
+            let did = format!("did:nodex:test:{}", suffix);
+
+            let long_form = did_long_form(&did, &payload).unwrap();
+            assert!(long_form.starts_with(&format!("{}:", did)));
+            assert_eq!(get_short_form_did(&long_form), did);
+            assert_eq!(get_short_form_did(&did), did);
+            let (_, method_specific_id) = parse_did(&long_form).unwrap();
+            assert_eq!(get_did_suffix(&method_specific_id), Some(suffix));
+
+            let response = resolve_long_form_did(&long_form).unwrap().unwrap();
+            assert_eq!(response.did_document.id, long_form);
+            assert!(!response.method_metadata.published);
+            let methods = response.did_document.verification_method.unwrap();
+            assert_eq!(methods.len(), 1);
+            assert_eq!(methods[0].id, "#signingKey");
+            assert_eq!(
+                response.did_document.authentication,
+                Some(vec!["#signingKey".to_string().into()])
+            );
+            assert!(resolve_long_form_did(&did).unwrap().is_none());
+
+            // NOTE: The create operation must belong to the suffix.
+            let other = "did:nodex:test:EiBprXreMiba4loyl3psXm0RsECdtlCiQIjM8G9BtdQplA";
+            assert!(matches!(
+                did_long_form(other, &payload),
+                Err(DidLongFormError::SuffixMismatch)
+            ));
+            let forged = format!("{}:{}", other, BASE64URL_NOPAD.encode(payload.as_bytes()));
+            assert!(matches!(
+                resolve_long_form_did(&forged),
+                Err(DidLongFormError::SuffixMismatch)
+            ));
+        }
     }
 }
@@ -0,0 +1,221 @@
+// did:key generation and resolution through the DidResolver contract.
+// https://w3c-ccg.github.io/did-method-key/
+
+use crate::did::did_document::{DidDocument, VerificationMethod, VerificationRelationship};
+use crate::did::did_repository::DidResolver;
+use crate::did::resolution::{ResolutionError, ResolutionErrorCode};
+use crate::did::sidetree::payload::{DidResolutionResponse, MethodMetadata};
+use crate::did_webvh::domain::did::{Did, DidError};
+use crate::keyring::multikey::{Multikey, MultikeyError, MultikeyToJwkError};
+
+#[derive(Debug, thiserror::Error)]
+pub enum DidKeyError {
+    #[error("Failed to parse did: {0}")]
+    DidParse(#[from] DidError),
+    #[error("Failed to decode multikey: {0}")]
+    Multikey(#[from] MultikeyError),
+    #[error("Failed to convert to JWK: {0}")]
+    Jwk(#[from] MultikeyToJwkError),
+}
+
+impl ResolutionError for DidKeyError {
+    fn error_code(&self) -> ResolutionErrorCode {
+        match self {
+            DidKeyError::DidParse(_) | DidKeyError::Multikey(_) => ResolutionErrorCode::InvalidDid,
+            DidKeyError::Jwk(_) => ResolutionErrorCode::InternalError,
+        }
+    }
+}
+
+// The representation of verification methods in expanded documents.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PublicKeyFormat {
+    #[default]
+    Multikey,
+    JsonWebKey2020,
+}
+
+impl PublicKeyFormat {
+    pub(crate) fn context(&self) -> &'static str {
+        match self {
+            PublicKeyFormat::Multikey => "https://w3id.org/security/multikey/v1",
+            PublicKeyFormat::JsonWebKey2020 => "https://w3id.org/security/suites/jws-2020/v1",
+        }
+    }
+}
+
+pub fn did_key(public_key: &Multikey) -> String {
+    format!("did:key:{}", public_key.encode())
+}
+
+pub(crate) fn to_verification_method(
+    id: String,
+    controller: &str,
+    key: &Multikey,
+    format: PublicKeyFormat,
+) -> Result<VerificationMethod, MultikeyToJwkError> {
+    let (r#type, public_key_multibase, public_key_jwk) = match format {
+        PublicKeyFormat::Multikey => ("Multikey", Some(key.encode()), None),
+        PublicKeyFormat::JsonWebKey2020 => ("JsonWebKey2020", None, Some(key.to_jwk()?)),
+    };
+    Ok(VerificationMethod {
+        id,
+        r#type: r#type.to_string(),
+        controller: controller.to_string(),
+        public_key_multibase,
+        public_key_jwk,
+        public_key_base58: None,
+        blockchain_account_id: None,
+    })
+}
+
+// Expands did:key:<multikey> into its DID document.
+// NOTE: An X25519 key is only for key agreement. An Ed25519 key gets the derived X25519 key
+//       for key agreement as well.
+pub fn expand_did_key(did: &str, format: PublicKeyFormat) -> Result<DidDocument, DidKeyError> {
+    let parsed: Did = did.parse()?;
+    if parsed.get_method() != "key" {
+        return Err(DidError::InvalidMethodName.into());
+    }
+    let key = Multikey::decode(parsed.get_method_specific_id())?;
+    let verification_method = |key: &Multikey| {
+        to_verification_method(format!("{}#{}", did, key.encode()), did, key, format)
+    };
+    let reference =
+        |method: &VerificationMethod| Some(vec![VerificationRelationship::from(method.id.clone())]);
+
+    let mut did_document = DidDocument::new(did);
+    did_document.context.push(format.context().to_string());
+    let signing = verification_method(&key)?;
+    if let Multikey::X25519(_) = key {
+        did_document.key_agreement = reference(&signing);
+        did_document.verification_method = Some(vec![signing]);
+        return Ok(did_document);
+    }
+    did_document.authentication = reference(&signing);
+    did_document.assertion_method = reference(&signing);
+    did_document.capability_invocation = reference(&signing);
+    did_document.capability_delegation = reference(&signing);
+    let mut methods = vec![signing];
+    if let Some(x25519) = key.to_x25519() {
+        let encryption = verification_method(&Multikey::X25519(x25519))?;
+        did_document.key_agreement = reference(&encryption);
+        methods.push(encryption);
+    }
+    did_document.verification_method = Some(methods);
+    Ok(did_document)
+}
+
+#[derive(Clone, Default)]
+pub struct DidKeyRepository {
+    format: PublicKeyFormat,
+}
+
+impl DidKeyRepository {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn public_key_format(mut self, format: PublicKeyFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    fn resolve(&self, did: &str) -> Result<DidResolutionResponse, DidKeyError> {
+        Ok(DidResolutionResponse {
+            context: "https://w3id.org/did-resolution/v1".to_string(),
+            did_document: expand_did_key(did, self.format)?,
+            method_metadata: MethodMetadata {
+                published: true,
+                recovery_commitment: None,
+                update_commitment: None,
+                ttl: None,
+            },
+            did_document_metadata: None,
+        })
+    }
+}
+
+impl DidResolver for DidKeyRepository {
+    type FindIdentifierError = DidKeyError;
+
+    async fn find_identifier(
+        &self,
+        did: &str,
+    ) -> Result<Option<DidResolutionResponse>, DidKeyError> {
+        self.resolve(did).map(Some)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand_core::OsRng;
+
+    use super::*;
+    use crate::did::did_document::VerificationRelationshipKind;
+    use crate::did::did_repository::{get_encrypt_key, get_sign_key};
+    use crate::keyring::keypair::{KeyPair, KeyPairing, SignPublicKey};
+
+    const ED25519_DID: &str = "did:key:z6MkhaXgBZDvotDkL5257faiztiGiC2QtKLGpbnnEGta2doK";
+
+    #[test]
+    fn test_expand_did_key() {
+        let did_document = expand_did_key(ED25519_DID, PublicKeyFormat::Multikey).unwrap();
+        let authentication =
+            did_document.verification_methods(VerificationRelationshipKind::Authentication);
+        assert_eq!(
+            authentication[0].id,
+            format!(
+                "{}#z6MkhaXgBZDvotDkL5257faiztiGiC2QtKLGpbnnEGta2doK",
+                ED25519_DID
+            )
+        );
+        assert_eq!(authentication[0].r#type, "Multikey");
+        assert!(matches!(
+            get_sign_key(&did_document).unwrap(),
+            SignPublicKey::Ed25519(_)
+        ));
+        let agreement =
+            did_document.verification_methods(VerificationRelationshipKind::KeyAgreement);
+        assert_eq!(
+            agreement[0].public_key_multibase.as_deref(),
+            Some("z6LSj72tK8brWgZja8NLRwPigth2T9QRiG1uH9oKZuKjdh9p")
+        );
+        assert_eq!(
+            did_document.context[1],
+            "https://w3id.org/security/multikey/v1"
+        );
+
+        let did = "did:key:z6LSj72tK8brWgZja8NLRwPigth2T9QRiG1uH9oKZuKjdh9p";
+        let did_document = expand_did_key(did, PublicKeyFormat::JsonWebKey2020).unwrap();
+        assert!(did_document.authentication.is_none());
+        assert!(get_encrypt_key(&did_document).is_ok());
+
+        assert!(matches!(
+            expand_did_key("did:web:example.com", PublicKeyFormat::Multikey),
+            Err(DidKeyError::DidParse(_))
+        ));
+        assert!(matches!(
+            expand_did_key(
+                "did:key:zQmdEjpG2gwEWZAx8YjBrw7mF1iuCqgrMh8S63M7PaC1Ldr",
+                PublicKeyFormat::Multikey
+            ),
+            Err(DidKeyError::Multikey(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_find_identifier() {
+        let keyring = KeyPairing::create_keyring(OsRng);
+        let repository = DidKeyRepository::new().public_key_format(PublicKeyFormat::JsonWebKey2020);
+        let did = did_key(&keyring.sign.get_public_key().into());
+        assert!(did.starts_with("did:key:zQ3s"));
+
+        let found = repository.find_identifier(&did).await.unwrap().unwrap();
+        assert_eq!(found.did_document.id, did);
+        assert_eq!(
+            get_sign_key(&found.did_document).unwrap(),
+            keyring.sign.get_public_key()
+        );
+    }
+}
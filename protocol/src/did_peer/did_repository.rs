@@ -0,0 +1,351 @@
+// did:peer (numalgo 2) generation and resolution through the DidResolver contract.
+// A did:peer DID carries its keys and services, so it is resolved without any network.
+// https://identity.foundation/peer-did-method-spec/#method-2-multiple-inception-key-without-doc
+
+use data_encoding::BASE64URL_NOPAD;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map};
+
+use crate::did::did_document::{
+    DidDocument, Service, ServiceEndpointValue, VerificationRelationship,
+};
+use crate::did::did_repository::DidResolver;
+use crate::did::resolution::{ResolutionError, ResolutionErrorCode};
+use crate::did::sidetree::payload::{DidResolutionResponse, MethodMetadata, ServiceEndpoint};
+use crate::did_key::did_repository::{to_verification_method, PublicKeyFormat};
+use crate::did_webvh::domain::did::{Did, DidError};
+use crate::keyring::keypair::{KeyPair, KeyPairing};
+use crate::keyring::multikey::{Multikey, MultikeyError, MultikeyToJwkError};
+
+// NOTE: The sign key comes first in our DIDs, and keys are numbered in order.
+pub const SIGN_KEY_ID: &str = "key-1";
+
+#[derive(Debug, thiserror::Error)]
+pub enum DidPeerError {
+    #[error("Failed to parse did: {0}")]
+    DidParse(#[from] DidError),
+    #[error("numalgo is not supported: {0}")]
+    UnsupportedNumalgo(String),
+    #[error("invalid element: {0}")]
+    InvalidElement(String),
+    #[error("Failed to decode multikey: {0}")]
+    Multikey(#[from] MultikeyError),
+    #[error("Failed to convert to JWK: {0}")]
+    Jwk(#[from] MultikeyToJwkError),
+    #[error("Failed to decode service: {0}")]
+    ServiceDecode(#[from] data_encoding::DecodeError),
+    #[error("failed serialize/deserialize service: {0}")]
+    ServiceJson(#[from] serde_json::Error),
+}
+
+impl ResolutionError for DidPeerError {
+    fn error_code(&self) -> ResolutionErrorCode {
+        match self {
+            DidPeerError::UnsupportedNumalgo(_) => ResolutionErrorCode::MethodNotSupported,
+            DidPeerError::Jwk(_) => ResolutionErrorCode::InternalError,
+            _ => ResolutionErrorCode::InvalidDid,
+        }
+    }
+}
+
+// Services are encoded with abbreviated keys, e.g. {"t":"dm","s":{"uri":"...","a":[...]}}.
+#[derive(Serialize, Deserialize)]
+struct AbbreviatedService {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    id: Option<String>,
+    t: String,
+    s: ServiceEndpointValue,
+}
+
+const DIDCOMM_MESSAGING: (&str, &str) = ("DIDCommMessaging", "dm");
+const ENDPOINT_ABBREVIATIONS: [(&str, &str); 2] = [("accept", "a"), ("routingKeys", "r")];
+
+fn abbreviate(key: &str) -> Option<&'static str> {
+    ENDPOINT_ABBREVIATIONS
+        .iter()
+        .find_map(|(full, abbreviated)| (*full == key).then_some(*abbreviated))
+}
+
+fn expand(key: &str) -> Option<&'static str> {
+    ENDPOINT_ABBREVIATIONS
+        .iter()
+        .find_map(|(full, abbreviated)| (*abbreviated == key).then_some(*full))
+}
+
+// Renames the keys of endpoint maps with `rename`, which returns None for the keys to keep.
+fn rename_endpoint_keys(
+    endpoint: ServiceEndpointValue,
+    rename: fn(&str) -> Option<&'static str>,
+) -> ServiceEndpointValue {
+    match endpoint {
+        ServiceEndpointValue::Uri(uri) => ServiceEndpointValue::Uri(uri),
+        ServiceEndpointValue::Map(map) => ServiceEndpointValue::Map(
+            map.into_iter()
+                .map(|(k, v)| (rename(&k).map(str::to_string).unwrap_or(k), v))
+                .collect(),
+        ),
+        ServiceEndpointValue::Set(set) => ServiceEndpointValue::Set(
+            set.into_iter()
+                .map(|v| rename_endpoint_keys(v, rename))
+                .collect(),
+        ),
+    }
+}
+
+fn encode_service(service: &ServiceEndpoint) -> Result<String, DidPeerError> {
+    let id = service.id.trim_start_matches('#');
+    let t = if service.r#type == DIDCOMM_MESSAGING.0 {
+        DIDCOMM_MESSAGING.1
+    } else {
+        &service.r#type
+    };
+    // NOTE: A URI is encoded as {"uri":"..."}, as most implementations do.
+    let endpoint = match &service.service_endpoint {
+        ServiceEndpointValue::Uri(uri) => {
+            ServiceEndpointValue::Map(Map::from_iter([("uri".to_string(), json!(uri))]))
+        }
+        endpoint => endpoint.clone(),
+    };
+    let abbreviated = AbbreviatedService {
+        id: (!id.is_empty()).then(|| format!("#{}", id)),
+        t: t.to_string(),
+        s: rename_endpoint_keys(endpoint, abbreviate),
+    };
+    Ok(BASE64URL_NOPAD.encode(&serde_json::to_vec(&abbreviated)?))
+}
+
+// NOTE: Services without id are `#service`, `#service-1`, ... in order.
+fn decode_service(encoded: &str, index: usize) -> Result<Service, DidPeerError> {
+    let service: AbbreviatedService =
+        serde_json::from_slice(&BASE64URL_NOPAD.decode(encoded.as_bytes())?)?;
+    let id = service.id.unwrap_or_else(|| match index {
+        0 => "#service".to_string(),
+        i => format!("#service-{}", i),
+    });
+    let r#type = if service.t == DIDCOMM_MESSAGING.1 {
+        DIDCOMM_MESSAGING.0.to_string()
+    } else {
+        service.t
+    };
+    let service_endpoint = rename_endpoint_keys(service.s, expand);
+    Ok(Service {
+        id,
+        r#type,
+        service_endpoint,
+        description: None,
+    })
+}
+
+// did:peer:2 of the sign and encrypt keys of `keyring`, with `services`.
+// NOTE: The update and recovery keys are not used, since a did:peer DID is never updated.
+pub fn did_peer(
+    keyring: &KeyPairing,
+    services: &[ServiceEndpoint],
+) -> Result<String, DidPeerError> {
+    let sign = Multikey::from(keyring.sign.get_public_key());
+    let encrypt = Multikey::X25519(keyring.encrypt.get_public_key());
+    let mut did = format!("did:peer:2.V{}.E{}", sign.encode(), encrypt.encode());
+    for service in services {
+        did.push_str(".S");
+        did.push_str(&encode_service(service)?);
+    }
+    Ok(did)
+}
+
+// Expands did:peer:2.<purpose><multikey>...S<service> into its DID document.
+pub fn expand_did_peer(did: &str, format: PublicKeyFormat) -> Result<DidDocument, DidPeerError> {
+    let parsed: Did = did.parse()?;
+    if parsed.get_method() != "peer" {
+        return Err(DidError::InvalidMethodName.into());
+    }
+    let elements = parsed
+        .get_method_specific_id()
+        .strip_prefix("2.")
+        .ok_or_else(|| DidPeerError::UnsupportedNumalgo(did.to_string()))?;
+
+    let mut did_document = DidDocument::new(did);
+    did_document.context.push(format.context().to_string());
+    let mut methods = vec![];
+    let mut services = vec![];
+    for element in elements.split('.') {
+        let invalid_element = || DidPeerError::InvalidElement(element.to_string());
+        let mut chars = element.chars();
+        let purpose = chars.next().ok_or_else(invalid_element)?;
+        let value = chars.as_str();
+        if purpose == 'S' {
+            services.push(decode_service(value, services.len())?);
+            continue;
+        }
+        let relationship = match purpose {
+            'A' => &mut did_document.assertion_method,
+            'E' => &mut did_document.key_agreement,
+            'V' => &mut did_document.authentication,
+            'I' => &mut did_document.capability_invocation,
+            'D' => &mut did_document.capability_delegation,
+            _ => return Err(invalid_element()),
+        };
+        let id = format!("#key-{}", methods.len() + 1);
+        relationship
+            .get_or_insert_with(Vec::new)
+            .push(VerificationRelationship::from(id.clone()));
+        methods.push(to_verification_method(
+            id,
+            did,
+            &Multikey::decode(value)?,
+            format,
+        )?);
+    }
+    did_document.verification_method = (!methods.is_empty()).then_some(methods);
+    did_document.service = (!services.is_empty()).then_some(services);
+    Ok(did_document)
+}
+
+#[derive(Clone, Default)]
+pub struct DidPeerRepository {
+    format: PublicKeyFormat,
+}
+
+impl DidPeerRepository {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn public_key_format(mut self, format: PublicKeyFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    // NOTE: Resolution is local, so it does not need to be async.
+    pub fn resolve(&self, did: &str) -> Result<DidResolutionResponse, DidPeerError> {
+        Ok(DidResolutionResponse {
+            context: "https://w3id.org/did-resolution/v1".to_string(),
+            did_document: expand_did_peer(did, self.format)?,
+            method_metadata: MethodMetadata {
+                published: true,
+                recovery_commitment: None,
+                update_commitment: None,
+                ttl: None,
+            },
+            did_document_metadata: None,
+        })
+    }
+}
+
+impl DidResolver for DidPeerRepository {
+    type FindIdentifierError = DidPeerError;
+
+    async fn find_identifier(
+        &self,
+        did: &str,
+    ) -> Result<Option<DidResolutionResponse>, DidPeerError> {
+        self.resolve(did).map(Some)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand_core::OsRng;
+
+    use super::*;
+    use crate::did::did_document::VerificationRelationshipKind;
+    use crate::did::did_repository::{get_encrypt_key, get_sign_key, get_verification_key};
+
+    #[test]
+    fn test_expand_did_peer() {
+        let did = "did:peer:2.Vz6Mkj3PUd1WjvaDhNZhhhXQdz5UnZXmS7ehtx8bsPpD47kKc.Ez6LSg8zQom395jKLrGiBNruB9MM6V8PWuf2FpEy4uRFiqQBR.SeyJ0IjoiZG0iLCJzIjp7InVyaSI6Imh0dHA6Ly9leGFtcGxlLmNvbS9kaWRjb21tIiwiYSI6WyJkaWRjb21tL3YyIl0sInIiOlsiZGlkOmV4YW1wbGU6MTIzNDU2Nzg5YWJjZGVmZ2hpI2tleS0xIl19fQ";
+        let did_document = expand_did_peer(did, PublicKeyFormat::Multikey).unwrap();
+        let authentication =
+            did_document.verification_methods(VerificationRelationshipKind::Authentication);
+        assert_eq!(authentication[0].id, "#key-1");
+        assert_eq!(
+            authentication[0].public_key_multibase.as_deref(),
+            Some("z6Mkj3PUd1WjvaDhNZhhhXQdz5UnZXmS7ehtx8bsPpD47kKc")
+        );
+        let agreement =
+            did_document.verification_methods(VerificationRelationshipKind::KeyAgreement);
+        assert_eq!(agreement[0].id, "#key-2");
+        let service = did_document.find_service("#service").unwrap();
+        assert_eq!(service.r#type, "DIDCommMessaging");
+        assert_eq!(
+            serde_json::to_value(&service.service_endpoint).unwrap(),
+            json!({
+                "uri": "http://example.com/didcomm",
+                "accept": ["didcomm/v2"],
+                "routingKeys": ["did:example:123456789abcdefghi#key-1"]
+            })
+        );
+
+        assert!(matches!(
+            expand_did_peer(
+                "did:peer:0z6Mkj3PUd1WjvaDhNZhhhXQdz5UnZXmS7ehtx8bsPpD47kKc",
+                PublicKeyFormat::Multikey
+            ),
+            Err(DidPeerError::UnsupportedNumalgo(_))
+        ));
+        assert!(matches!(
+            expand_did_peer(
+                "did:peer:2.Xz6Mkj3PUd1WjvaDhNZhhhXQdz5UnZXmS7ehtx8bsPpD47kKc",
+                PublicKeyFormat::Multikey
+            ),
+            Err(DidPeerError::InvalidElement(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_create_and_find_identifier() {
+        let keyring = KeyPairing::create_keyring(OsRng);
+        let endpoint = json!({
+            "uri": "https://example.com/mediator",
+            "accept": ["didcomm/v2"],
+            "routingKeys": ["did:example:123#key-1"]
+        });
+        let services = [
+            ServiceEndpoint {
+                id: "didcomm".to_string(),
+                r#type: "DIDCommMessaging".to_string(),
+                service_endpoint: "https://example.com/didcomm".into(),
+                description: None,
+            },
+            ServiceEndpoint {
+                id: "mediator".to_string(),
+                r#type: "DIDCommMessaging".to_string(),
+                service_endpoint: serde_json::from_value(endpoint.clone()).unwrap(),
+                description: None,
+            },
+        ];
+        let did = did_peer(&keyring, &services).unwrap();
+        assert!(did.starts_with("did:peer:2.Vz"));
+
+        let repository =
+            DidPeerRepository::new().public_key_format(PublicKeyFormat::JsonWebKey2020);
+        let did_document = repository
+            .find_identifier(&did)
+            .await
+            .unwrap()
+            .unwrap()
+            .did_document;
+        assert_eq!(did_document.id, did);
+        assert_eq!(
+            get_sign_key(&did_document).unwrap(),
+            keyring.sign.get_public_key()
+        );
+        assert_eq!(
+            get_verification_key(&did_document, &format!("{}#{}", did, SIGN_KEY_ID)).unwrap(),
+            keyring.sign.get_public_key()
+        );
+        assert_eq!(
+            get_encrypt_key(&did_document).unwrap(),
+            keyring.encrypt.get_public_key()
+        );
+        let service = did_document.find_service("#didcomm").unwrap();
+        assert_eq!(
+            serde_json::to_value(&service.service_endpoint).unwrap(),
+            json!({"uri": "https://example.com/didcomm"})
+        );
+        let service = did_document.find_service("#mediator").unwrap();
+        assert_eq!(
+            serde_json::to_value(&service.service_endpoint).unwrap(),
+            endpoint
+        );
+    }
+}
@@ -0,0 +1,19 @@
+use http::StatusCode;
+
+#[derive(Clone, Debug)]
+pub struct DidWebHttpClientResponse {
+    pub(crate) status_code: StatusCode,
+    pub(crate) body: String,
+}
+
+impl DidWebHttpClientResponse {
+    pub fn new(status_code: StatusCode, body: String) -> Self {
+        Self { status_code, body }
+    }
+}
+
+#[trait_variant::make(Send)]
+pub trait DidWebHttpClient {
+    type Error: std::error::Error;
+    async fn get_did_document(&self, url: &str) -> Result<DidWebHttpClientResponse, Self::Error>;
+}
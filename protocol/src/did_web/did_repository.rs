@@ -0,0 +1,268 @@
+// did:web resolution through the DidResolver contract.
+// https://w3c-ccg.github.io/did-method-web/
+
+use http::StatusCode;
+
+use super::client::DidWebHttpClient;
+use crate::did::did_document::DidDocument;
+use crate::did::did_repository::DidResolver;
+use crate::did::resolution::{ResolutionError, ResolutionErrorCode};
+use crate::did::sidetree::payload::{DidResolutionResponse, MethodMetadata};
+use crate::did_webvh::domain::did::{Did, DidError};
+
+#[derive(Debug, thiserror::Error)]
+pub enum DidWebFindIdentifierError<HttpClientError: std::error::Error> {
+    #[error("Failed to parse did: {0}")]
+    DidParse(#[from] DidError),
+    #[error("Failed to get did document. response: {0}")]
+    RequestFailed(String),
+    #[error("Failed to parse body: {0}")]
+    BodyParse(#[from] serde_json::Error),
+    #[error("did document id does not match: {0}")]
+    DidMismatch(String),
+    #[error("Failed to send request: {0}")]
+    HttpClient(HttpClientError),
+}
+
+impl<E: std::error::Error> ResolutionError for DidWebFindIdentifierError<E> {
+    fn error_code(&self) -> ResolutionErrorCode {
+        match self {
+            DidWebFindIdentifierError::DidParse(_) => ResolutionErrorCode::InvalidDid,
+            _ => ResolutionErrorCode::InternalError,
+        }
+    }
+}
+
+// example.com%3a8443 -> example.com:8443
+fn percent_decode(s: &str) -> Result<String, DidError> {
+    let mut bytes = Vec::with_capacity(s.len());
+    let mut rest = s.as_bytes();
+    while let Some((&b, tail)) = rest.split_first() {
+        if b == b'%' {
+            let hex = tail
+                .get(..2)
+                .filter(|v| v.iter().all(u8::is_ascii_hexdigit))
+                .and_then(|v| std::str::from_utf8(v).ok())
+                .and_then(|v| u8::from_str_radix(v, 16).ok())
+                .ok_or(DidError::InvalidMethodSpecificId)?;
+            bytes.push(hex);
+            rest = &tail[2..];
+        } else {
+            bytes.push(b);
+            rest = tail;
+        }
+    }
+    String::from_utf8(bytes).map_err(|_| DidError::InvalidMethodSpecificId)
+}
+
+// did:web:example.com%3A8443:user:alice -> https://example.com:8443/user/alice/did.json
+// NOTE: A DID without path is hosted under /.well-known.
+//       Decoded parts must not change the URL structure, e.g. by `..` or an encoded `/`.
+pub fn did_web_url(did: &Did) -> Result<String, DidError> {
+    if did.get_method() != "web" {
+        return Err(DidError::InvalidMethodName);
+    }
+    let mut parts = did.get_method_specific_id().split(':');
+    let domain = percent_decode(parts.next().unwrap_or_default())?;
+    if domain.is_empty() || domain.contains(['/', '\\', '?', '#', '@']) {
+        return Err(DidError::InvalidMethodSpecificId);
+    }
+    let path = parts
+        .map(|v| {
+            let v = percent_decode(v)?;
+            if v.is_empty() || v == "." || v == ".." || v.contains(['/', '\\', '?', '#']) {
+                return Err(DidError::InvalidMethodSpecificId);
+            }
+            Ok(v)
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    if path.is_empty() {
+        Ok(format!("https://{}/.well-known/did.json", domain))
+    } else {
+        Ok(format!("https://{}/{}/did.json", domain, path.join("/")))
+    }
+}
+
+#[derive(Clone)]
+pub struct DidWebRepository<C: DidWebHttpClient> {
+    client: C,
+}
+
+impl<C: DidWebHttpClient> DidWebRepository<C> {
+    pub fn new(client: C) -> Self {
+        Self { client }
+    }
+}
+
+impl<C> DidResolver for DidWebRepository<C>
+where
+    C: DidWebHttpClient + Send + Sync,
+    C::Error: Send + Sync,
+{
+    type FindIdentifierError = DidWebFindIdentifierError<C::Error>;
+
+    async fn find_identifier(
+        &self,
+        did: &str,
+    ) -> Result<Option<DidResolutionResponse>, DidWebFindIdentifierError<C::Error>> {
+        let did: Did = did.parse()?;
+        let response = self
+            .client
+            .get_did_document(&did_web_url(&did)?)
+            .await
+            .map_err(DidWebFindIdentifierError::HttpClient)?;
+
+        match response.status_code {
+            StatusCode::OK => {
+                let document: DidDocument = serde_json::from_str(&response.body)?;
+                if document.id != did.to_string() {
+                    return Err(DidWebFindIdentifierError::DidMismatch(document.id));
+                }
+                Ok(Some(DidResolutionResponse {
+                    context: "https://w3id.org/did-resolution/v1".to_string(),
+                    did_document: document,
+                    method_metadata: MethodMetadata {
+                        published: true,
+                        recovery_commitment: None,
+                        update_commitment: None,
+                        ttl: None,
+                    },
+                    did_document_metadata: None,
+                }))
+            }
+            StatusCode::NOT_FOUND => Ok(None),
+            _ => Err(DidWebFindIdentifierError::RequestFailed(format!(
+                "{:?}",
+                response
+            ))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand_core::OsRng;
+    use serde_json::json;
+
+    use super::*;
+    use crate::did::did_repository::{get_encrypt_key, get_sign_key};
+    use crate::did_web::client::DidWebHttpClientResponse;
+    use crate::keyring::jwk::Jwk;
+    use crate::keyring::keypair::{KeyPair, KeyPairing};
+
+    #[derive(Debug, thiserror::Error)]
+    #[error("dummy error")]
+    struct DummyError;
+
+    struct MockHttpClient {
+        url: String,
+        body: String,
+    }
+
+    impl DidWebHttpClient for MockHttpClient {
+        type Error = DummyError;
+        async fn get_did_document(
+            &self,
+            url: &str,
+        ) -> Result<DidWebHttpClientResponse, DummyError> {
+            if url == self.url {
+                Ok(DidWebHttpClientResponse::new(
+                    StatusCode::OK,
+                    self.body.clone(),
+                ))
+            } else {
+                Ok(DidWebHttpClientResponse::new(
+                    StatusCode::NOT_FOUND,
+                    String::new(),
+                ))
+            }
+        }
+    }
+
+    #[test]
+    fn test_did_web_url() {
+        let url = |did: &str| did_web_url(&did.parse().unwrap());
+        assert_eq!(
+            url("did:web:example.com").unwrap(),
+            "https://example.com/.well-known/did.json"
+        );
+        assert_eq!(
+            url("did:web:example.com%3A8443:user:alice").unwrap(),
+            "https://example.com:8443/user/alice/did.json"
+        );
+        assert_eq!(
+            url("did:web:example.com%3a8443:%7Ealice").unwrap(),
+            "https://example.com:8443/~alice/did.json"
+        );
+        for did in [
+            "did:web:example.com:..:secret",
+            "did:web:example.com:%2E%2E:secret",
+            "did:web:example.com:user%2Falice",
+            "did:web:example.com%2Fevil",
+        ] {
+            assert_eq!(url(did).unwrap_err(), DidError::InvalidMethodSpecificId);
+        }
+        assert_eq!(
+            url("did:webvh:example.com").unwrap_err(),
+            DidError::InvalidMethodName
+        );
+    }
+
+    #[tokio::test]
+    async fn test_find_identifier() {
+        let keyring = KeyPairing::create_keyring(OsRng);
+        let sign: Jwk = keyring.sign.get_public_key().try_into().unwrap();
+        let encrypt: Jwk = keyring.encrypt.get_public_key().into();
+        let did = "did:web:example.com:services:backend";
+        let document = json!({
+            "@context": ["https://www.w3.org/ns/did/v1"],
+            "id": did,
+            "verificationMethod": [
+                {
+                    "id": format!("{}#signingKey", did),
+                    "type": "JsonWebKey2020",
+                    "controller": did,
+                    "publicKeyJwk": sign,
+                },
+                {
+                    "id": format!("{}#encryptionKey", did),
+                    "type": "JsonWebKey2020",
+                    "controller": did,
+                    "publicKeyJwk": encrypt,
+                },
+            ],
+            "authentication": [format!("{}#signingKey", did)],
+            "keyAgreement": [format!("{}#encryptionKey", did)],
+        });
+        let repository = DidWebRepository::new(MockHttpClient {
+            url: "https://example.com/services/backend/did.json".to_string(),
+            body: document.to_string(),
+        });
+
+        let response = repository.find_identifier(did).await.unwrap().unwrap();
+        assert_eq!(response.did_document.id, did);
+        assert_eq!(
+            get_sign_key(&response.did_document).unwrap(),
+            keyring.sign.get_public_key()
+        );
+        assert_eq!(
+            get_encrypt_key(&response.did_document).unwrap(),
+            keyring.encrypt.get_public_key()
+        );
+
+        assert!(repository
+            .find_identifier("did:web:example.com")
+            .await
+            .unwrap()
+            .is_none());
+
+        let repository = DidWebRepository::new(MockHttpClient {
+            url: "https://example.com/.well-known/did.json".to_string(),
+            body: document.to_string(),
+        });
+        assert!(matches!(
+            repository.find_identifier("did:web:example.com").await,
+            Err(DidWebFindIdentifierError::DidMismatch(_))
+        ));
+    }
+}
@@ -1,33 +1,34 @@
 use bs58;
-use multihash::Multihash;
-use sha2::{Digest, Sha256};
 
-const SHA256: u64 = 0x12;
+use crate::did_webvh::domain::did_log_entry::WEBVH_DID_METHOD;
+use crate::keyring::multihash::{self, HashAlgorithm};
 
 #[derive(Debug)]
 pub enum CryptoError {
     FailedToGenerateHash,
 }
 
-pub fn generate_multihash_with_base58_encode(data: &[u8]) -> Result<String, CryptoError> {
-    let mut hasher = Sha256::new();
-    hasher.update(data);
-    let hash = hasher.finalize();
-    let wrapped_hash =
-        Multihash::<32>::wrap(SHA256, &hash).map_err(|_| CryptoError::FailedToGenerateHash)?;
-    Ok(bs58::encode(wrapped_hash.to_bytes()).into_string())
+// NOTE: did:webvh has no hashAlgorithm parameter. The spec version in the `method`
+//       parameter fixes the algorithm of the SCID, entry hashes and nextKeyHashes,
+//       and did:webvh:0.5, the only version supported, is SHA-256 only.
+const WEBVH_HASH_ALGORITHM: HashAlgorithm = HashAlgorithm::Sha256;
+
+pub fn method_hash_algorithm(method: &str) -> Option<HashAlgorithm> {
+    (method == WEBVH_DID_METHOD).then_some(WEBVH_HASH_ALGORITHM)
+}
+
+pub fn generate_multihash_with_base58_encode(
+    algorithm: HashAlgorithm,
+    data: &[u8],
+) -> Result<String, CryptoError> {
+    Ok(bs58::encode(algorithm.multihash(data)).into_string())
 }
 
+// Whether `hash` is a base58 multihash of the did:webvh hash algorithm.
 pub fn validate_hash(hash: &str) -> bool {
     match bs58::decode(hash).into_vec() {
-        Ok(decoded) => match Multihash::<32>::from_bytes(&decoded) {
-            Ok(multihash) => multihash.code() == SHA256,
-            Err(_) => false,
-        },
-        Err(e) => {
-            println!("Error decoding hash: {:?}", e);
-            false
-        }
+        Ok(decoded) => multihash::decode(&decoded).is_ok_and(|(v, _)| v == WEBVH_HASH_ALGORITHM),
+        Err(_) => false,
     }
 }
 
@@ -38,10 +39,22 @@ mod tests {
     #[test]
     fn test_generate_multihash_with_base58encode() {
         let data = "z6MkoSFjacZb7R82htx8n1AkpgLQWR7CA6rigsc2VH9acLuF";
-        let hash = generate_multihash_with_base58_encode(data.as_bytes()).unwrap();
+        let algorithm = method_hash_algorithm(WEBVH_DID_METHOD).unwrap();
+        let hash = generate_multihash_with_base58_encode(algorithm, data.as_bytes()).unwrap();
         assert_eq!(hash, "QmdEjpG2gwEWZAx8YjBrw7mF1iuCqgrMh8S63M7PaC1Ldr");
     }
 
+    #[test]
+    fn test_method_hash_algorithm() {
+        assert_eq!(
+            method_hash_algorithm("did:webvh:0.5"),
+            Some(HashAlgorithm::Sha256)
+        );
+        assert_eq!(method_hash_algorithm("did:webvh:0.4"), None);
+        assert_eq!(method_hash_algorithm("did:webvh:9.9"), None);
+        assert_eq!(method_hash_algorithm("did:tdw:0.4"), None);
+    }
+
     #[test]
     fn test_validate_hash() {
         let encoded_hash = "QmdEjpG2gwEWZAx8YjBrw7mF1iuCqgrMh8S63M7PaC1Ldr";
@@ -52,5 +65,8 @@ mod tests {
 
         let invalid_hash = "scid";
         assert!(!validate_hash(invalid_hash));
+
+        let hash = HashAlgorithm::Blake2b256.multihash(b"scid");
+        assert!(!validate_hash(&bs58::encode(hash).into_string()));
     }
 }
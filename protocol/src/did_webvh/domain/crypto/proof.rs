@@ -0,0 +1,25 @@
+// Data Integrity proofs of did:webvh log entries, made with the eddsa-jcs-2022 suite.
+
+use ed25519_dalek::SigningKey;
+use serde::Serialize;
+
+use crate::did_webvh::domain::did_log_entry::Proof;
+use crate::verifiable_credentials::eddsa_jcs_2022::{
+    create_proof_value, resolve_verification_method, verify_proof_value, EddsaJcs2022Error,
+};
+
+// Fills in `proof.proof_value`. `document` must not contain the proof itself.
+pub fn create_proof<T: Serialize>(
+    document: &T,
+    mut proof: Proof,
+    secret_key: &SigningKey,
+) -> Result<Proof, EddsaJcs2022Error> {
+    proof.proof_value = create_proof_value(document, &proof, secret_key)?;
+    Ok(proof)
+}
+
+// `document` must not contain the proof itself.
+pub fn verify_proof<T: Serialize>(document: &T, proof: &Proof) -> Result<(), EddsaJcs2022Error> {
+    let public_key = resolve_verification_method(&proof.verification_method)?;
+    verify_proof_value(document, proof, &proof.proof_value, &public_key)
+}
@@ -205,6 +205,24 @@ impl DidWebvh {
         }
         format!("https://{}", parts.join("/"))
     }
+
+    // NOTE: A DID without path is hosted under /.well-known.
+    fn file_url(&self, file_name: &str) -> String {
+        if self.uri.contains(':') {
+            format!("{}/{}", self.did_to_https(), file_name)
+        } else {
+            format!("{}/.well-known/{}", self.did_to_https(), file_name)
+        }
+    }
+
+    pub fn did_log_url(&self) -> String {
+        self.file_url("did.jsonl")
+    }
+
+    // did-witness.json is hosted next to did.jsonl.
+    pub fn did_witness_url(&self) -> String {
+        self.file_url("did-witness.json")
+    }
 }
 
 impl TryFrom<Did> for DidWebvh {
@@ -321,6 +339,14 @@ mod tests {
         );
         assert_eq!(did.get_uri(), "example.com:path:to:resource");
         assert_eq!(did.did_to_https(), "https://example.com/path/to/resource");
+        assert_eq!(
+            did.did_log_url(),
+            "https://example.com/path/to/resource/did.jsonl"
+        );
+        assert_eq!(
+            did.did_witness_url(),
+            "https://example.com/path/to/resource/did-witness.json"
+        );
 
         let did = "did:webvh:QmdEjpG2gwEWZAx8YjBrw7mF1iuCqgrMh8S63M7PaC1Ldr:example.com%3A8000:path:to:resource"
             .parse::<DidWebvh>()
@@ -335,6 +361,19 @@ mod tests {
             "https://example.com:8000/path/to/resource"
         );
 
+        let did = DidWebvh::new(
+            "QmdEjpG2gwEWZAx8YjBrw7mF1iuCqgrMh8S63M7PaC1Ldr",
+            "example.com%3A8000",
+        )?;
+        assert_eq!(
+            did.did_log_url(),
+            "https://example.com:8000/.well-known/did.jsonl"
+        );
+        assert_eq!(
+            did.did_witness_url(),
+            "https://example.com:8000/.well-known/did-witness.json"
+        );
+
         let did = Did::new(
             "webvh",
             "QmdEjpG2gwEWZAx8YjBrw7mF1iuCqgrMh8S63M7PaC1Ldr:example.com:path:to:resource",
@@ -1,92 +1,11 @@
-use super::did::Did;
-use serde::{Deserialize, Serialize};
-
-#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
-#[serde(rename_all = "camelCase")]
-pub struct DidDocument {
-    #[serde(rename = "@context")]
-    pub context: Vec<String>,
-    pub id: Did,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub deactivated: Option<bool>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub verification_method: Option<Vec<VerificationMethod>>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub authentication: Option<Vec<String>>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub assertion_method: Option<Vec<String>>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub key_agreement: Option<Vec<String>>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub capability_invocation: Option<Vec<String>>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub capability_delegation: Option<Vec<String>>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub service: Option<Vec<Service>>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub also_known_as: Option<Vec<String>>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub controller: Option<Vec<Did>>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub created: Option<String>,
-}
-
-#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
-#[serde(rename_all = "camelCase")]
-pub struct VerificationMethod {
-    pub id: String,
-    #[serde(rename = "type")]
-    pub r#type: String,
-    pub controller: Did,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub public_key_multibase: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub public_key_jwk: Option<PublicKeyJwk>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub blockchain_account_id: Option<String>,
-}
-
-#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
-#[serde(rename_all = "camelCase")]
-pub struct PublicKeyJwk {
-    pub kty: String,
-    pub crv: String,
-    pub x: String,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub y: Option<String>,
-}
-
-#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
-#[serde(rename_all = "camelCase")]
-pub struct Service {
-    pub id: String,
-    #[serde(rename = "type")]
-    pub r#type: String,
-    pub service_endpoint: String,
-}
-
-impl DidDocument {
-    pub fn new(id: Did) -> Self {
-        Self {
-            context: vec!["https://www.w3.org/ns/did/v1".to_string()],
-            id,
-            verification_method: None,
-            authentication: None,
-            assertion_method: None,
-            key_agreement: None,
-            capability_invocation: None,
-            capability_delegation: None,
-            service: None,
-            also_known_as: None,
-            controller: None,
-            created: None,
-            deactivated: None,
-        }
-    }
-}
+// NOTE: did:webvh states are plain DID Core documents.
+pub use crate::did::did_document::{
+    DidDocument, Service, VerificationMethod, VerificationRelationship,
+};
 
 #[cfg(test)]
 mod tests {
+    use super::super::did::Did;
     use super::*;
 
     const JSON: &str = r#"{
@@ -97,13 +16,13 @@ mod tests {
     #[test]
     fn test_did_document_new() {
         let did = Did::new("web", "example.com").unwrap();
-        let did_document = DidDocument::new(did);
-        assert_eq!(did_document.id.to_string(), "did:web:example.com");
+        let did_document = DidDocument::new(did.to_string());
+        assert_eq!(did_document.id, "did:web:example.com");
     }
 
     #[test]
     fn test_did_document_serde() {
         let did_document: DidDocument = serde_json::from_str(JSON).unwrap();
-        assert_eq!(did_document.id.to_string(), "did:web:example.com");
+        assert_eq!(did_document.id, "did:web:example.com");
     }
 }
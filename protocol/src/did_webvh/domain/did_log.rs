@@ -0,0 +1,619 @@
+// Verification of a did:webvh log (did.jsonl).
+// https://identity.foundation/didwebvh/v0.5/#read-resolve
+
+use chrono::{DateTime, FixedOffset};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use validator::Validate;
+
+use super::crypto::hash::{generate_multihash_with_base58_encode, method_hash_algorithm};
+use super::crypto::proof::verify_proof;
+use super::did::DidWebvh;
+use super::did_document::DidDocument;
+use super::did_log_entry::{DidLogEntry, DidLogEntryError, Parameters, WitnessConfig};
+use crate::did::resolution::DidDocumentMetadata;
+use crate::did::sidetree::payload::{DidResolutionResponse, MethodMetadata};
+use crate::verifiable_credentials::eddsa_jcs_2022::{
+    verification_method_to_multikey, EddsaJcs2022Error,
+};
+
+#[derive(Debug, Error)]
+pub enum DidLogVerificationError {
+    #[error("DID log is empty")]
+    Empty,
+    #[error("failed to parse log entry at line {0}: {1}")]
+    Parse(usize, serde_json::Error),
+    #[error("invalid log entry {0}: {1:?}")]
+    InvalidEntry(usize, DidLogEntryError),
+    #[error("invalid versionId of log entry {0}")]
+    InvalidVersionId(usize),
+    #[error("versionTime of log entry {0} is before the previous entry")]
+    InvalidVersionTime(usize),
+    #[error("invalid parameters of log entry {0}: {1}")]
+    InvalidParameters(usize, &'static str),
+    #[error("SCID does not match the first log entry")]
+    InvalidScid,
+    #[error("DID of log entry {0} does not match")]
+    DidMismatch(usize),
+    #[error("log entry {0} is not signed by an authorized update key")]
+    UnauthorizedUpdateKey(usize),
+    #[error("invalid proof of log entry {0}: {1}")]
+    InvalidProof(usize, EddsaJcs2022Error),
+    #[error("log entry {0} follows a deactivation")]
+    Deactivated(usize),
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DidWebvhMetadata {
+    pub version_id: String,
+    pub version_time: String,
+    pub created: String,
+    pub updated: String,
+    pub scid: String,
+    pub portable: bool,
+    pub deactivated: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ttl: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub witness: Option<WitnessConfig>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DidWebvhResolution {
+    pub did_document: DidDocument,
+    pub did_document_metadata: DidWebvhMetadata,
+}
+
+impl From<DidWebvhResolution> for DidResolutionResponse {
+    fn from(resolution: DidWebvhResolution) -> Self {
+        DidResolutionResponse {
+            context: "https://w3id.org/did-resolution/v1".to_string(),
+            method_metadata: MethodMetadata {
+                published: true,
+                recovery_commitment: None,
+                update_commitment: None,
+                ttl: resolution.did_document_metadata.ttl,
+            },
+            did_document: resolution.did_document,
+            did_document_metadata: Some(DidDocumentMetadata {
+                created: Some(resolution.did_document_metadata.created),
+                updated: Some(resolution.did_document_metadata.updated),
+                version_id: Some(resolution.did_document_metadata.version_id),
+                deactivated: Some(resolution.did_document_metadata.deactivated),
+                method: None,
+            }),
+        }
+    }
+}
+
+pub fn parse_did_log(did_log: &str) -> Result<Vec<DidLogEntry>, DidLogVerificationError> {
+    did_log
+        .lines()
+        .map(str::trim)
+        .enumerate()
+        .filter(|(_, line)| !line.is_empty())
+        .map(|(i, line)| {
+            serde_json::from_str(line).map_err(|e| DidLogVerificationError::Parse(i + 1, e))
+        })
+        .collect()
+}
+
+// Parameters apply until a later entry overrides them.
+// NOTE: Pre-rotation and witnesses are turned off by an empty value, `[]` and `{}`.
+pub(crate) fn merge_parameters(current: &Parameters, next: &Parameters) -> Parameters {
+    Parameters {
+        portable: next.portable.or(current.portable),
+        update_keys: next.update_keys.clone().or(current.update_keys.clone()),
+        next_key_hashes: next
+            .next_key_hashes
+            .clone()
+            .or(current.next_key_hashes.clone())
+            .filter(|v| !v.is_empty()),
+        method: next.method.clone().or(current.method.clone()),
+        scid: current.scid.clone(),
+        deactivate: next.deactivate.or(current.deactivate),
+        witness: next
+            .witness
+            .clone()
+            .or(current.witness.clone())
+            .filter(|v| !v.witnesses.is_empty()),
+        ttl: next.ttl.or(current.ttl),
+    }
+}
+
+fn parse_version_time(
+    index: usize,
+    entry: &DidLogEntry,
+) -> Result<DateTime<FixedOffset>, DidLogVerificationError> {
+    DateTime::parse_from_rfc3339(&entry.version_time)
+        .map_err(|_| DidLogVerificationError::InvalidVersionTime(index))
+}
+
+// Keys allowed to sign `entry`. With pre-rotation active, the entry must sign with
+// its own updateKeys, each committed to by the previous nextKeyHashes, and set
+// nextKeyHashes again, to `[]` to turn pre-rotation off.
+fn authorized_update_keys(
+    index: usize,
+    previous: Option<&Parameters>,
+    entry: &DidLogEntry,
+) -> Result<Vec<String>, DidLogVerificationError> {
+    let Some(previous) = previous else {
+        return entry
+            .parameters
+            .update_keys
+            .clone()
+            .filter(|keys| !keys.is_empty())
+            .ok_or(DidLogVerificationError::InvalidParameters(
+                index,
+                "updateKeys is required in the first entry",
+            ));
+    };
+    let Some(next_key_hashes) = previous.next_key_hashes.as_ref() else {
+        return Ok(previous.update_keys.clone().unwrap_or_default());
+    };
+    if entry.parameters.next_key_hashes.is_none() {
+        return Err(DidLogVerificationError::InvalidParameters(
+            index,
+            "nextKeyHashes is required while pre-rotation is active",
+        ));
+    }
+    let update_keys =
+        entry
+            .parameters
+            .update_keys
+            .clone()
+            .ok_or(DidLogVerificationError::InvalidParameters(
+                index,
+                "updateKeys is required while pre-rotation is active",
+            ))?;
+    // NOTE: nextKeyHashes are committed with the hash algorithm of the previous entry.
+    let hash_algorithm = previous
+        .method
+        .as_deref()
+        .and_then(method_hash_algorithm)
+        .ok_or(DidLogVerificationError::InvalidParameters(
+            index,
+            "method is not supported",
+        ))?;
+    for key in update_keys.iter() {
+        let hash = generate_multihash_with_base58_encode(hash_algorithm, key.as_bytes()).map_err(
+            |_| DidLogVerificationError::InvalidEntry(index, DidLogEntryError::FaildMultihash),
+        )?;
+        if !next_key_hashes.contains(&hash) {
+            return Err(DidLogVerificationError::InvalidParameters(
+                index,
+                "updateKeys is not in the previous nextKeyHashes",
+            ));
+        }
+    }
+    Ok(update_keys)
+}
+
+fn verify_proofs(
+    index: usize,
+    entry: &DidLogEntry,
+    update_keys: &[String],
+) -> Result<(), DidLogVerificationError> {
+    let proofs = entry
+        .proof
+        .as_ref()
+        .filter(|proofs| !proofs.is_empty())
+        .ok_or(DidLogVerificationError::InvalidEntry(
+            index,
+            DidLogEntryError::InvalidProof,
+        ))?;
+    let mut document = entry.clone();
+    document.proof = None;
+    for proof in proofs {
+        proof.validate().map_err(|_| {
+            DidLogVerificationError::InvalidEntry(index, DidLogEntryError::InvalidProof)
+        })?;
+        let key = verification_method_to_multikey(&proof.verification_method)
+            .map_err(|e| DidLogVerificationError::InvalidProof(index, e))?;
+        if !update_keys.iter().any(|v| v == key) {
+            return Err(DidLogVerificationError::UnauthorizedUpdateKey(index));
+        }
+        verify_proof(&document, proof)
+            .map_err(|e| DidLogVerificationError::InvalidProof(index, e))?;
+    }
+    Ok(())
+}
+
+// Verifies the whole log of `did` and returns its latest state.
+pub fn verify_did_log(
+    did: &DidWebvh,
+    entries: &[DidLogEntry],
+) -> Result<DidWebvhResolution, DidLogVerificationError> {
+    let first = entries.first().ok_or(DidLogVerificationError::Empty)?;
+
+    // NOTE: SCID is the hash of the first entry with every SCID replaced by the placeholder.
+    if first.parameters.scid.as_deref() != Some(did.get_scid()) {
+        return Err(DidLogVerificationError::InvalidScid);
+    }
+    if first.parameters.method.is_none() {
+        return Err(DidLogVerificationError::InvalidParameters(
+            1,
+            "method is required in the first entry",
+        ));
+    }
+    let scid = first
+        .replace_to_scid_placeholder()
+        .and_then(|v| v.calc_entry_hash())
+        .map_err(|e| DidLogVerificationError::InvalidEntry(1, e))?;
+    if scid != did.get_scid() {
+        return Err(DidLogVerificationError::InvalidScid);
+    }
+
+    let mut parameters: Option<Parameters> = None;
+    let mut previous: Option<(&DidLogEntry, DateTime<FixedOffset>)> = None;
+    for (i, entry) in entries.iter().enumerate() {
+        let index = i + 1;
+        if parameters.as_ref().and_then(|v| v.deactivate) == Some(true) {
+            return Err(DidLogVerificationError::Deactivated(index));
+        }
+        entry.validate().map_err(|_| {
+            DidLogVerificationError::InvalidEntry(index, DidLogEntryError::InvalidFormat)
+        })?;
+        if index > 1 && entry.parameters.scid.is_some() {
+            return Err(DidLogVerificationError::InvalidParameters(
+                index,
+                "scid is only allowed in the first entry",
+            ));
+        }
+        if index > 1 && entry.parameters.portable.is_some() {
+            return Err(DidLogVerificationError::InvalidParameters(
+                index,
+                "portable is only allowed in the first entry",
+            ));
+        }
+
+        // NOTE: versionId is `<version number>-<entry hash>`, where the entry hash is
+        //       calculated with versionId set to the previous versionId (or the SCID).
+        let (version_number, entry_hash) = entry
+            .parse_verion_id()
+            .map_err(|e| DidLogVerificationError::InvalidEntry(index, e))?;
+        if version_number as usize != index {
+            return Err(DidLogVerificationError::InvalidVersionId(index));
+        }
+        let hash_algorithm = entry
+            .parameters
+            .method
+            .as_deref()
+            .or(parameters.as_ref().and_then(|v| v.method.as_deref()))
+            .and_then(method_hash_algorithm)
+            .ok_or(DidLogVerificationError::InvalidParameters(
+                index,
+                "method is not supported",
+            ))?;
+        let mut hashed = entry.clone();
+        hashed.version_id = previous
+            .map(|(v, _)| v.version_id.clone())
+            .unwrap_or_else(|| scid.clone());
+        let calculated = hashed
+            .calc_entry_hash_with(hash_algorithm)
+            .map_err(|e| DidLogVerificationError::InvalidEntry(index, e))?;
+        if calculated != entry_hash {
+            return Err(DidLogVerificationError::InvalidVersionId(index));
+        }
+
+        let version_time = parse_version_time(index, entry)?;
+        if let Some((_, previous_time)) = previous {
+            if version_time < previous_time {
+                return Err(DidLogVerificationError::InvalidVersionTime(index));
+            }
+        }
+
+        let update_keys = authorized_update_keys(index, parameters.as_ref(), entry)?;
+        verify_proofs(index, entry, &update_keys)?;
+
+        let merged = match parameters.as_ref() {
+            Some(current) => merge_parameters(current, &entry.parameters),
+            None => entry.parameters.clone(),
+        };
+        if merged.portable != Some(true) && entry.state.id != first.state.id {
+            return Err(DidLogVerificationError::DidMismatch(index));
+        }
+        parameters = Some(merged);
+        previous = Some((entry, version_time));
+    }
+
+    let (last, _) = previous.ok_or(DidLogVerificationError::Empty)?;
+    if last.state.id != did.get_did().to_string() {
+        return Err(DidLogVerificationError::DidMismatch(entries.len()));
+    }
+    let parameters = parameters.unwrap_or_else(|| first.parameters.clone());
+    let deactivated = parameters.deactivate == Some(true);
+    let mut did_document = last.state.clone();
+    if deactivated {
+        did_document.deactivated = Some(true);
+    }
+    Ok(DidWebvhResolution {
+        did_document,
+        did_document_metadata: DidWebvhMetadata {
+            version_id: last.version_id.clone(),
+            version_time: last.version_time.clone(),
+            created: first.version_time.clone(),
+            updated: last.version_time.clone(),
+            scid,
+            portable: parameters.portable == Some(true),
+            deactivated,
+            ttl: parameters.ttl,
+            witness: parameters.witness,
+        },
+    })
+}
+
+#[cfg(test)]
+pub mod tests {
+    use ed25519_dalek::SigningKey;
+    use rand_core::OsRng;
+
+    use super::*;
+    use crate::did_webvh::domain::crypto::proof::create_proof;
+    use crate::verifiable_credentials::eddsa_jcs_2022::encode_multikey;
+
+    fn multikey(key: &SigningKey) -> String {
+        encode_multikey(&key.verifying_key())
+    }
+
+    fn key_hash(key: &SigningKey) -> String {
+        let hash_algorithm = method_hash_algorithm("did:webvh:0.5").unwrap();
+        generate_multihash_with_base58_encode(hash_algorithm, multikey(key).as_bytes()).unwrap()
+    }
+
+    fn sign(mut entry: DidLogEntry, key: &SigningKey) -> DidLogEntry {
+        let proof = entry.generate_proof(&multikey(key), "authentication", "");
+        entry.proof = Some(vec![create_proof(&entry, proof, key).unwrap()]);
+        entry
+    }
+
+    fn set_version_id(mut entry: DidLogEntry, number: u32, previous: &str) -> DidLogEntry {
+        entry.version_id = previous.to_string();
+        entry.version_id = format!("{}-{}", number, entry.calc_entry_hash().unwrap());
+        entry
+    }
+
+    pub fn genesis(key: &SigningKey, next_key: Option<&SigningKey>) -> (DidWebvh, DidLogEntry) {
+        let mut parameters = serde_json::json!({
+            "updateKeys": [multikey(key)],
+            "method": "did:webvh:0.5",
+            "scid": "{SCID}",
+        });
+        if let Some(next_key) = next_key {
+            parameters["nextKeyHashes"] = serde_json::json!([key_hash(next_key)]);
+        }
+        let entry: DidLogEntry = serde_json::from_value(serde_json::json!({
+            "versionId": "{SCID}",
+            "versionTime": "2025-01-01T00:00:00Z",
+            "parameters": parameters,
+            "state": {
+                "@context": ["https://www.w3.org/ns/did/v1"],
+                "id": "did:webvh:{SCID}:example.com",
+            },
+        }))
+        .unwrap();
+        let scid = entry.calc_entry_hash().unwrap();
+        let entry: DidLogEntry = serde_json::from_str(
+            &serde_json::to_string(&entry)
+                .unwrap()
+                .replace("{SCID}", &scid),
+        )
+        .unwrap();
+        let entry = sign(set_version_id(entry, 1, &scid), key);
+        (DidWebvh::new(&scid, "example.com").unwrap(), entry)
+    }
+
+    pub fn next(
+        previous: &DidLogEntry,
+        parameters: serde_json::Value,
+        version_time: &str,
+        key: &SigningKey,
+    ) -> DidLogEntry {
+        let mut entry = previous.clone();
+        entry.proof = None;
+        entry.parameters = serde_json::from_value(parameters).unwrap();
+        entry.version_time = version_time.to_string();
+        let (number, _) = previous.parse_verion_id().unwrap();
+        sign(set_version_id(entry, number + 1, &previous.version_id), key)
+    }
+
+    #[test]
+    fn test_verify_did_log() {
+        let key = SigningKey::generate(&mut OsRng);
+        let next_key = SigningKey::generate(&mut OsRng);
+        let (did, first) = genesis(&key, Some(&next_key));
+        let second = next(
+            &first,
+            serde_json::json!({
+                "updateKeys": [multikey(&next_key)],
+                "nextKeyHashes": [],
+                "ttl": 60,
+            }),
+            "2025-01-02T00:00:00Z",
+            &next_key,
+        );
+        let log = [&first, &second]
+            .iter()
+            .map(|v| serde_json::to_string(v).unwrap())
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        let entries = parse_did_log(&log).unwrap();
+        let resolution = verify_did_log(&did, &entries).unwrap();
+        assert_eq!(resolution.did_document.id, did.get_did().to_string());
+        let metadata = resolution.did_document_metadata;
+        assert_eq!(metadata.version_id, second.version_id);
+        assert_eq!(metadata.created, "2025-01-01T00:00:00Z");
+        assert_eq!(metadata.updated, "2025-01-02T00:00:00Z");
+        assert_eq!(metadata.scid, did.get_scid());
+        assert_eq!(metadata.ttl, Some(60));
+        assert!(!metadata.deactivated);
+    }
+
+    #[test]
+    fn test_verify_did_log_tampered() {
+        let key = SigningKey::generate(&mut OsRng);
+        let (did, first) = genesis(&key, None);
+
+        let mut tampered = first.clone();
+        tampered.state.also_known_as = Some(vec!["https://example.com".to_string()]);
+        assert!(matches!(
+            verify_did_log(&did, &[tampered]),
+            Err(DidLogVerificationError::InvalidScid)
+        ));
+
+        let second = next(&first, serde_json::json!({}), "2025-01-02T00:00:00Z", &key);
+        let mut tampered = second.clone();
+        tampered.version_time = "2025-01-03T00:00:00Z".to_string();
+        assert!(matches!(
+            verify_did_log(&did, &[first.clone(), tampered]),
+            Err(DidLogVerificationError::InvalidVersionId(2))
+        ));
+
+        let other = DidWebvh::new(
+            "QmdEjpG2gwEWZAx8YjBrw7mF1iuCqgrMh8S63M7PaC1Ldr",
+            "example.com",
+        )
+        .unwrap();
+        assert!(matches!(
+            verify_did_log(&other, &[first]),
+            Err(DidLogVerificationError::InvalidScid)
+        ));
+    }
+
+    #[test]
+    fn test_verify_did_log_version_time() {
+        let key = SigningKey::generate(&mut OsRng);
+        let (did, first) = genesis(&key, None);
+        let second = next(&first, serde_json::json!({}), "2024-12-31T00:00:00Z", &key);
+        assert!(matches!(
+            verify_did_log(&did, &[first, second]),
+            Err(DidLogVerificationError::InvalidVersionTime(2))
+        ));
+    }
+
+    #[test]
+    fn test_verify_did_log_update_keys() {
+        let key = SigningKey::generate(&mut OsRng);
+        let other_key = SigningKey::generate(&mut OsRng);
+
+        // NOTE: Without pre-rotation, the previous updateKeys must sign.
+        let (did, first) = genesis(&key, None);
+        let second = next(
+            &first,
+            serde_json::json!({}),
+            "2025-01-02T00:00:00Z",
+            &other_key,
+        );
+        assert!(matches!(
+            verify_did_log(&did, &[first.clone(), second]),
+            Err(DidLogVerificationError::UnauthorizedUpdateKey(2))
+        ));
+        let second = next(
+            &first,
+            serde_json::json!({"updateKeys": [multikey(&other_key)]}),
+            "2025-01-02T00:00:00Z",
+            &key,
+        );
+        let third = next(
+            &second,
+            serde_json::json!({}),
+            "2025-01-03T00:00:00Z",
+            &other_key,
+        );
+        assert!(verify_did_log(&did, &[first, second, third]).is_ok());
+
+        // NOTE: With pre-rotation, the new updateKeys must be committed in nextKeyHashes.
+        let next_key = SigningKey::generate(&mut OsRng);
+        let (did, first) = genesis(&key, Some(&next_key));
+        let second = next(
+            &first,
+            serde_json::json!({"updateKeys": [multikey(&other_key)]}),
+            "2025-01-02T00:00:00Z",
+            &other_key,
+        );
+        assert!(matches!(
+            verify_did_log(&did, &[first.clone(), second]),
+            Err(DidLogVerificationError::InvalidParameters(2, _))
+        ));
+        let second = next(&first, serde_json::json!({}), "2025-01-02T00:00:00Z", &key);
+        assert!(matches!(
+            verify_did_log(&did, &[first.clone(), second]),
+            Err(DidLogVerificationError::InvalidParameters(2, _))
+        ));
+
+        // NOTE: Pre-rotation stays active until nextKeyHashes is set to [].
+        let second = next(
+            &first,
+            serde_json::json!({"updateKeys": [multikey(&next_key)]}),
+            "2025-01-02T00:00:00Z",
+            &next_key,
+        );
+        assert!(matches!(
+            verify_did_log(&did, &[first.clone(), second]),
+            Err(DidLogVerificationError::InvalidParameters(2, _))
+        ));
+        let third_key = SigningKey::generate(&mut OsRng);
+        let second = next(
+            &first,
+            serde_json::json!({
+                "updateKeys": [multikey(&next_key)],
+                "nextKeyHashes": [key_hash(&third_key)],
+            }),
+            "2025-01-02T00:00:00Z",
+            &next_key,
+        );
+        let third = next(
+            &second,
+            serde_json::json!({"updateKeys": [multikey(&third_key)], "nextKeyHashes": []}),
+            "2025-01-03T00:00:00Z",
+            &third_key,
+        );
+        let fourth = next(
+            &third,
+            serde_json::json!({}),
+            "2025-01-04T00:00:00Z",
+            &third_key,
+        );
+        assert!(verify_did_log(&did, &[first, second, third, fourth]).is_ok());
+    }
+
+    #[test]
+    fn test_merge_parameters_witness() {
+        let witness: Parameters = serde_json::from_value(serde_json::json!({
+            "witness": {
+                "threshold": 1,
+                "witnesses": [{"id": "did:key:z6MkrJVnaZkeFzdQyMZu1cgjg7k1pZZ6pvBQ7XJPt4swbTQ2", "weight": 1}],
+            },
+        }))
+        .unwrap();
+        let merged = merge_parameters(&witness, &Parameters::default());
+        assert_eq!(merged.witness, witness.witness);
+
+        let off: Parameters = serde_json::from_value(serde_json::json!({"witness": {}})).unwrap();
+        assert_eq!(merge_parameters(&witness, &off).witness, None);
+    }
+
+    #[test]
+    fn test_verify_did_log_deactivated() {
+        let key = SigningKey::generate(&mut OsRng);
+        let (did, first) = genesis(&key, None);
+        let second = next(
+            &first,
+            serde_json::json!({"deactivate": true}),
+            "2025-01-02T00:00:00Z",
+            &key,
+        );
+        let resolution = verify_did_log(&did, &[first.clone(), second.clone()]).unwrap();
+        assert!(resolution.did_document_metadata.deactivated);
+        assert_eq!(resolution.did_document.deactivated, Some(true));
+
+        let third = next(&second, serde_json::json!({}), "2025-01-03T00:00:00Z", &key);
+        assert!(matches!(
+            verify_did_log(&did, &[first, second, third]),
+            Err(DidLogVerificationError::Deactivated(3))
+        ));
+    }
+}
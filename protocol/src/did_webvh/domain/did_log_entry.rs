@@ -1,15 +1,16 @@
-use super::crypto::hash::generate_multihash_with_base58_encode;
-use super::did::{Did, DidWebvh};
+use super::crypto::hash::{generate_multihash_with_base58_encode, method_hash_algorithm};
+use super::did::Did;
 use super::did_document::DidDocument;
+use crate::keyring::multihash::{self, HashAlgorithm};
 use chrono::DateTime;
 use regex::Regex;
 use serde::{Deserialize, Serialize};
 use serde_json_canonicalizer;
 use validator::{Validate, ValidationError};
 
-const WEBVH_DID_METHOD: &str = "did:webvh:0.5";
+pub(crate) const WEBVH_DID_METHOD: &str = "did:webvh:0.5";
 const WEBVH_DID_CRYPTO_SUITE: &str = "eddsa-jcs-2022";
-const WEBVH_DID_SCID_PLACEHOLDER: &str = "{SCID}";
+pub const WEBVH_DID_SCID_PLACEHOLDER: &str = "{SCID}";
 
 #[derive(Debug, PartialEq, Eq)]
 pub enum DidLogEntryError {
@@ -63,15 +64,15 @@ fn verify_version_time(version_time: &str) -> Result<(), ValidationError> {
     Ok(())
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone, Validate, PartialEq)]
+#[derive(Serialize, Deserialize, Debug, Clone, Default, Validate, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct Parameters {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub portable: Option<bool>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub update_keys: Option<Vec<String>>,
+    // NOTE: `[]` turns pre-rotation off.
     #[serde(skip_serializing_if = "Option::is_none")]
-    #[validate(custom(function = "verify_next_key_hashes"))]
     pub next_key_hashes: Option<Vec<String>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     #[validate(custom(function = "verify_method"))]
@@ -86,13 +87,6 @@ pub struct Parameters {
     pub ttl: Option<u32>,
 }
 
-fn verify_next_key_hashes(next_key_hashes: &[String]) -> Result<(), ValidationError> {
-    if next_key_hashes.is_empty() {
-        return Err(ValidationError::new("Next key hashes must not be empty"));
-    }
-    Ok(())
-}
-
 fn verify_method(method: &str) -> Result<(), ValidationError> {
     if method != WEBVH_DID_METHOD {
         return Err(ValidationError::new("Invalid method"));
@@ -100,8 +94,9 @@ fn verify_method(method: &str) -> Result<(), ValidationError> {
     Ok(())
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
-#[serde(rename_all = "camelCase")]
+// NOTE: `{}` turns witnesses off.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase", default)]
 pub struct WitnessConfig {
     pub threshold: u32,
     pub witnesses: Vec<Witness>,
@@ -167,7 +162,7 @@ fn verify_proof_verification_method(verification_method: &str) -> Result<(), Val
     }
     let did = parts[0]
         .parse::<Did>()
-        .expect("Invalid verification method");
+        .map_err(|_| ValidationError::new("Invalid verification method"))?;
     if did.get_method() != "key" {
         return Err(ValidationError::new("Invalid verification method"));
     }
@@ -185,9 +180,13 @@ fn verify_proof_created(created: &str) -> Result<(), ValidationError> {
 impl DidLogEntry {
     // Create a new DIDLogEntry, with scid placeholder
     pub fn new(uri: &str) -> Result<Self, DidLogEntryError> {
-        let did = DidWebvh::new(WEBVH_DID_SCID_PLACEHOLDER, uri)
+        // NOTE: DidWebvh rejects the placeholder as SCID, so build the DID directly.
+        if uri.is_empty() || uri.contains('/') {
+            return Err(DidLogEntryError::InvalidFormat);
+        }
+        let did = Did::new("webvh", &format!("{}:{}", WEBVH_DID_SCID_PLACEHOLDER, uri))
             .map_err(|_| DidLogEntryError::InvalidFormat)?;
-        let state = DidDocument::new(did.get_did().clone());
+        let state = DidDocument::new(did.to_string());
         let version_id = WEBVH_DID_SCID_PLACEHOLDER.to_string();
         let version_time = chrono::Utc::now().to_rfc3339();
         let parameters = Parameters {
@@ -234,15 +233,19 @@ impl DidLogEntry {
         Ok((version_number.unwrap(), parts[1].to_string()))
     }
 
+    // NOTE: The SCID may appear anywhere in the entry (e.g. verification method ids),
+    //       so every occurrence is replaced, not only `scid` and the DID.
     pub fn replace_to_scid_placeholder(&self) -> Result<DidLogEntry, DidLogEntryError> {
-        let scid_placeholder = "{SCID}";
-        let mut entry = self.clone();
-        entry.parameters.scid = Some(scid_placeholder.to_string());
-        entry.version_id = scid_placeholder.to_string();
-        let did = DidWebvh::try_from(entry.state.id.clone())
-            .map_err(|_| DidLogEntryError::InvalidState)?
-            .replace_scid(scid_placeholder);
-        entry.state.id = did.into();
+        let scid = self
+            .parameters
+            .scid
+            .as_ref()
+            .ok_or(DidLogEntryError::InvalidParameters)?;
+        let json = serde_json::to_string(self).map_err(|_| DidLogEntryError::InvalidFormat)?;
+        let mut entry: DidLogEntry =
+            serde_json::from_str(&json.replace(scid.as_str(), WEBVH_DID_SCID_PLACEHOLDER))
+                .map_err(|_| DidLogEntryError::InvalidFormat)?;
+        entry.version_id = WEBVH_DID_SCID_PLACEHOLDER.to_string();
         Ok(entry)
     }
 
@@ -267,22 +270,52 @@ impl DidLogEntry {
     // The first entry, LogEntry with SCID placeholder can use this function to compute the SCID.
     // Otherwise, just be calculated as an EntryHash
     pub fn calc_entry_hash(&self) -> Result<String, DidLogEntryError> {
+        self.calc_entry_hash_with(self.hash_algorithm()?)
+    }
+
+    // NOTE: For verifiers, which take the algorithm from the `method` in effect.
+    pub fn calc_entry_hash_with(
+        &self,
+        hash_algorithm: HashAlgorithm,
+    ) -> Result<String, DidLogEntryError> {
         let mut entry = self.clone();
         if entry.proof.is_some() {
             entry.proof = None;
         }
         let jcs = serde_json_canonicalizer::to_string(&entry)
             .map_err(|_| DidLogEntryError::InvalidFormat)?;
-        generate_multihash_with_base58_encode(jcs.as_bytes())
+        generate_multihash_with_base58_encode(hash_algorithm, jcs.as_bytes())
             .map_err(|_| DidLogEntryError::FaildMultihash)
     }
 
+    // The hash algorithm of the spec version this entry is under, told by its `method`,
+    // or by the entry hash in its versionId for entries inheriting `method`.
+    pub fn hash_algorithm(&self) -> Result<HashAlgorithm, DidLogEntryError> {
+        match self.parameters.method.as_deref() {
+            Some(method) => {
+                method_hash_algorithm(method).ok_or(DidLogEntryError::InvalidParameters)
+            }
+            None => self.version_hash_algorithm(),
+        }
+    }
+
+    fn version_hash_algorithm(&self) -> Result<HashAlgorithm, DidLogEntryError> {
+        let (_, entry_hash) = self.parse_verion_id()?;
+        let decoded = bs58::decode(entry_hash)
+            .into_vec()
+            .map_err(|_| DidLogEntryError::InvalidVersionId)?;
+        multihash::decode(&decoded)
+            .map(|(algorithm, _)| algorithm)
+            .map_err(|_| DidLogEntryError::InvalidVersionId)
+    }
+
     // calculate the next key hashes by the Update Keys from the previous entry.
     pub fn calc_next_key_hash(&self, keys: &Vec<String>) -> Result<Self, DidLogEntryError> {
+        let hash_algorithm = self.hash_algorithm()?;
         let generate_hashed_keys = |keys: &Vec<String>| {
             keys.iter()
                 .map(|key| {
-                    generate_multihash_with_base58_encode(key.as_bytes())
+                    generate_multihash_with_base58_encode(hash_algorithm, key.as_bytes())
                         .map_err(|_| DidLogEntryError::FaildMultihash)
                 })
                 .collect::<Result<Vec<String>, DidLogEntryError>>()
@@ -355,6 +388,17 @@ mod tests {
         assert_eq!(new_entry.version_id, "{SCID}");
     }
 
+    #[test]
+    fn test_new_log_entry() {
+        let entry = DidLogEntry::new("example.com:devices").unwrap();
+        assert_eq!(entry.state.id, "did:webvh:{SCID}:example.com:devices");
+        assert_eq!(entry.parameters.scid.unwrap(), "{SCID}");
+        assert_eq!(
+            DidLogEntry::new("example.com/devices").unwrap_err(),
+            DidLogEntryError::InvalidFormat
+        );
+    }
+
     #[test]
     fn test_calc_entry_hash() {
         const JSON_LOG: &str = r#"{
@@ -376,16 +420,17 @@ mod tests {
 }"#;
         let mut entry: DidLogEntry = serde_json::from_str(JSON_LOG).unwrap();
         println!("ENTRY: {:?}\n", entry);
-        let scid = entry.calc_entry_hash().unwrap();
+        // NOTE: The vector is from a draft log, whose `method` is not supported,
+        //       so the hash algorithm is given.
+        assert!(entry.calc_entry_hash().is_err());
+        let scid = entry.calc_entry_hash_with(HashAlgorithm::Sha256).unwrap();
         assert_eq!(scid, "QmbUzhqS4Fx6ueq6gopKQBNe2Dyj4dddCTyPuN4pncYxYG");
 
         entry.version_id = scid.to_string();
         entry.parameters.scid = Some(scid.to_string());
-        let identifier = entry.state.id.get_method_specific_id();
-        let identifier = identifier.replace("{SCID}", scid.as_str());
-        entry.state.id = Did::new("webvh", identifier.as_str()).unwrap();
+        entry.state.id = entry.state.id.replace("{SCID}", scid.as_str());
         println!("ENTRY_REPLACE_SCID: {:?}\n", entry);
-        let entry_hash = entry.calc_entry_hash().unwrap();
+        let entry_hash = entry.calc_entry_hash_with(HashAlgorithm::Sha256).unwrap();
         assert_eq!(entry_hash, "QmeyX9Tripap4bpri4324AUDCeUpBXKHRBHW89rnWa4mKw");
     }
 }
@@ -0,0 +1,330 @@
+// Verification of did:webvh witness proofs (did-witness.json).
+// https://identity.foundation/didwebvh/v0.5/#did-witnesses
+
+use std::collections::{HashMap, HashSet};
+
+use ed25519_dalek::VerifyingKey;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use super::did::Did;
+use super::did_log::merge_parameters;
+use super::did_log_entry::{DidLogEntry, Parameters, Proof};
+use crate::verifiable_credentials::eddsa_jcs_2022::{verify_proof_value, EddsaJcs2022Error};
+
+#[derive(Debug, Error)]
+pub enum DidWitnessVerificationError {
+    #[error("failed to parse witness proofs: {0}")]
+    Parse(#[from] serde_json::Error),
+    #[error("witness proof for unknown versionId {0}")]
+    UnknownVersion(String),
+    #[error("invalid witness proof of {0}: {1}")]
+    InvalidProof(String, EddsaJcs2022Error),
+    #[error("log entry {0} is witnessed with weight {1}, below the threshold {2}")]
+    InsufficientWeight(usize, u32, u32),
+}
+
+// An entry of did-witness.json: the proofs of the witnesses approving `version_id`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct WitnessProof {
+    pub version_id: String,
+    pub proof: Vec<Proof>,
+}
+
+// The document a witness signs.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct WitnessedVersion<'a> {
+    version_id: &'a str,
+}
+
+pub fn parse_did_witness(
+    did_witness: &str,
+) -> Result<Vec<WitnessProof>, DidWitnessVerificationError> {
+    Ok(serde_json::from_str(did_witness)?)
+}
+
+// Every witness DID listed in the log.
+pub fn witness_dids(entries: &[DidLogEntry]) -> Vec<Did> {
+    let mut dids = Vec::new();
+    for witness in entries
+        .iter()
+        .filter_map(|v| v.parameters.witness.as_ref())
+        .flat_map(|v| v.witnesses.iter())
+    {
+        if !dids.contains(&witness.id) {
+            dids.push(witness.id.clone());
+        }
+    }
+    dids
+}
+
+// Checks that every entry of `entries`, already verified by `verify_did_log`, is
+// approved by witnesses with at least the threshold weight.
+// `witness_keys` maps verification method ids of the witnesses to their keys.
+// NOTE: The witness config in effect before an entry applies to that entry, so that
+//       the witnesses approve any change of themselves, e.g. turning witnessing off.
+//       Only the first entry is checked against its own config.
+//       A proof of a later version approves the earlier ones too.
+//       Proofs of unlisted or unresolved witnesses do not count.
+pub fn verify_witness_proofs(
+    entries: &[DidLogEntry],
+    witness_proofs: &[WitnessProof],
+    witness_keys: &HashMap<String, VerifyingKey>,
+) -> Result<(), DidWitnessVerificationError> {
+    let versions: HashMap<&str, usize> = entries
+        .iter()
+        .enumerate()
+        .map(|(i, v)| (v.version_id.as_str(), i + 1))
+        .collect();
+    let witnesses: HashSet<String> = witness_dids(entries)
+        .into_iter()
+        .map(|v| v.to_string())
+        .collect();
+
+    // The latest version approved by each witness.
+    let mut approved: HashMap<String, usize> = HashMap::new();
+    for witness_proof in witness_proofs {
+        let index = *versions
+            .get(witness_proof.version_id.as_str())
+            .ok_or_else(|| {
+                DidWitnessVerificationError::UnknownVersion(witness_proof.version_id.clone())
+            })?;
+        let document = WitnessedVersion {
+            version_id: &witness_proof.version_id,
+        };
+        for proof in witness_proof.proof.iter() {
+            let witness = proof
+                .verification_method
+                .split('#')
+                .next()
+                .unwrap_or_default();
+            if !witnesses.contains(witness) {
+                continue;
+            }
+            let Some(public_key) = witness_keys.get(&proof.verification_method) else {
+                continue;
+            };
+            verify_proof_value(&document, proof, &proof.proof_value, public_key).map_err(|e| {
+                DidWitnessVerificationError::InvalidProof(proof.verification_method.clone(), e)
+            })?;
+            let latest = approved.entry(witness.to_string()).or_default();
+            *latest = (*latest).max(index);
+        }
+    }
+
+    let mut parameters: Option<Parameters> = None;
+    for (i, entry) in entries.iter().enumerate() {
+        let index = i + 1;
+        let merged = match parameters.as_ref() {
+            Some(current) => merge_parameters(current, &entry.parameters),
+            None => entry.parameters.clone(),
+        };
+        let config = match parameters.as_ref() {
+            Some(current) => current.witness.as_ref(),
+            None => merged.witness.as_ref(),
+        };
+        if let Some(config) = config {
+            let weight: u32 = config
+                .witnesses
+                .iter()
+                .filter(|v| approved.get(&v.id.to_string()).is_some_and(|i| *i >= index))
+                .map(|v| v.weight)
+                .sum();
+            if weight < config.threshold {
+                return Err(DidWitnessVerificationError::InsufficientWeight(
+                    index,
+                    weight,
+                    config.threshold,
+                ));
+            }
+        }
+        parameters = Some(merged);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+pub mod tests {
+    use ed25519_dalek::SigningKey;
+    use rand_core::OsRng;
+
+    use super::*;
+    use crate::did_webvh::domain::did_log::tests::{genesis, next};
+    use crate::verifiable_credentials::eddsa_jcs_2022::{create_proof_value, CRYPTOSUITE};
+
+    const WITNESS1: &str = "did:webvh:QmWitness1:witness1.example.com";
+    const WITNESS2: &str = "did:webvh:QmWitness2:witness2.example.com";
+    const WITNESS1_KEY: &str = "did:webvh:QmWitness1:witness1.example.com#key-1";
+    const WITNESS2_KEY: &str = "did:webvh:QmWitness2:witness2.example.com#key-2";
+
+    pub fn witness_proof(version_id: &str, verification_method: &str, key: &SigningKey) -> Proof {
+        let mut proof = Proof {
+            r#type: "DataIntegrityProof".to_string(),
+            cryptosuite: CRYPTOSUITE.to_string(),
+            verification_method: verification_method.to_string(),
+            created: "2025-01-02T00:00:00Z".to_string(),
+            proof_purpose: "authentication".to_string(),
+            proof_value: String::new(),
+        };
+        let document = WitnessedVersion { version_id };
+        proof.proof_value = create_proof_value(&document, &proof, key).unwrap();
+        proof
+    }
+
+    fn witnessed_log(key: &SigningKey) -> Vec<DidLogEntry> {
+        let (_, first) = genesis(key, None);
+        let second = next(
+            &first,
+            serde_json::json!({"witness": {"threshold": 2, "witnesses": [
+                {"id": WITNESS1, "weight": 1},
+                {"id": WITNESS2, "weight": 1},
+            ]}}),
+            "2025-01-02T00:00:00Z",
+            key,
+        );
+        let third = next(&second, serde_json::json!({}), "2025-01-03T00:00:00Z", key);
+        vec![first, second, third]
+    }
+
+    #[test]
+    fn test_verify_witness_proofs_turned_off() {
+        let key = SigningKey::generate(&mut OsRng);
+        let witness1 = SigningKey::generate(&mut OsRng);
+        let witness2 = SigningKey::generate(&mut OsRng);
+        let witness_keys = HashMap::from([
+            (WITNESS1_KEY.to_string(), witness1.verifying_key()),
+            (WITNESS2_KEY.to_string(), witness2.verifying_key()),
+        ]);
+        let mut log = witnessed_log(&key);
+        let fourth = next(
+            &log[2],
+            serde_json::json!({"witness": {}}),
+            "2025-01-04T00:00:00Z",
+            &key,
+        );
+        log.push(fourth);
+
+        // NOTE: Turning witnessing off needs the approval of the witnesses before it.
+        let third = &log[2].version_id;
+        let proofs = vec![WitnessProof {
+            version_id: third.clone(),
+            proof: vec![
+                witness_proof(third, WITNESS1_KEY, &witness1),
+                witness_proof(third, WITNESS2_KEY, &witness2),
+            ],
+        }];
+        assert!(matches!(
+            verify_witness_proofs(&log, &proofs, &witness_keys),
+            Err(DidWitnessVerificationError::InsufficientWeight(4, 0, 2))
+        ));
+
+        let latest = &log[3].version_id;
+        let proofs = vec![WitnessProof {
+            version_id: latest.clone(),
+            proof: vec![
+                witness_proof(latest, WITNESS1_KEY, &witness1),
+                witness_proof(latest, WITNESS2_KEY, &witness2),
+            ],
+        }];
+        verify_witness_proofs(&log, &proofs, &witness_keys).unwrap();
+    }
+
+    #[test]
+    fn test_verify_witness_proofs() {
+        let key = SigningKey::generate(&mut OsRng);
+        let witness1 = SigningKey::generate(&mut OsRng);
+        let witness2 = SigningKey::generate(&mut OsRng);
+        let witness_keys = HashMap::from([
+            (WITNESS1_KEY.to_string(), witness1.verifying_key()),
+            (WITNESS2_KEY.to_string(), witness2.verifying_key()),
+        ]);
+        let log = witnessed_log(&key);
+        let latest = &log[2].version_id;
+
+        assert_eq!(
+            witness_dids(&log)
+                .iter()
+                .map(|v| v.to_string())
+                .collect::<Vec<_>>(),
+            vec![WITNESS1, WITNESS2]
+        );
+
+        // NOTE: The first entry has no witness config, so needs no proofs.
+        let proof2 = witness_proof(latest, WITNESS2_KEY, &witness2);
+        let proofs = vec![WitnessProof {
+            version_id: latest.clone(),
+            proof: vec![
+                witness_proof(latest, WITNESS1_KEY, &witness1),
+                proof2.clone(),
+            ],
+        }];
+        verify_witness_proofs(&log, &proofs, &witness_keys).unwrap();
+
+        let proofs = vec![
+            WitnessProof {
+                version_id: log[1].version_id.clone(),
+                proof: vec![witness_proof(&log[1].version_id, WITNESS2_KEY, &witness2)],
+            },
+            WitnessProof {
+                version_id: latest.clone(),
+                proof: vec![witness_proof(latest, WITNESS1_KEY, &witness1)],
+            },
+        ];
+        // NOTE: The second entry is approved by both, the third only by WITNESS1.
+        assert!(matches!(
+            verify_witness_proofs(&log, &proofs, &witness_keys),
+            Err(DidWitnessVerificationError::InsufficientWeight(3, 1, 2))
+        ));
+
+        let proofs = vec![WitnessProof {
+            version_id: latest.clone(),
+            proof: vec![witness_proof(latest, WITNESS1_KEY, &witness2), proof2],
+        }];
+        assert!(matches!(
+            verify_witness_proofs(&log, &proofs, &witness_keys),
+            Err(DidWitnessVerificationError::InvalidProof(..))
+        ));
+
+        // NOTE: The second entry sets the witnesses, which approve from the third entry on.
+        assert!(matches!(
+            verify_witness_proofs(&log, &[], &witness_keys),
+            Err(DidWitnessVerificationError::InsufficientWeight(3, 0, 2))
+        ));
+    }
+
+    #[test]
+    fn test_verify_witness_proofs_unlisted_witness() {
+        let key = SigningKey::generate(&mut OsRng);
+        let witness1 = SigningKey::generate(&mut OsRng);
+        let other = "did:webvh:QmOther:other.example.com#key-1";
+        let witness_keys = HashMap::from([
+            (WITNESS1_KEY.to_string(), witness1.verifying_key()),
+            (other.to_string(), witness1.verifying_key()),
+        ]);
+        let log = witnessed_log(&key);
+        let latest = &log[2].version_id;
+
+        let proofs = vec![WitnessProof {
+            version_id: latest.clone(),
+            proof: vec![
+                witness_proof(latest, WITNESS1_KEY, &witness1),
+                witness_proof(latest, other, &witness1),
+            ],
+        }];
+        assert!(matches!(
+            verify_witness_proofs(&log, &proofs, &witness_keys),
+            Err(DidWitnessVerificationError::InsufficientWeight(3, 1, 2))
+        ));
+
+        let proofs = vec![WitnessProof {
+            version_id: "4-QmUnknown".to_string(),
+            proof: vec![],
+        }];
+        assert!(matches!(
+            verify_witness_proofs(&log, &proofs, &witness_keys),
+            Err(DidWitnessVerificationError::UnknownVersion(_))
+        ));
+    }
+}
@@ -1,4 +1,6 @@
 pub mod crypto;
 pub mod did;
 pub mod did_document;
+pub mod did_log;
 pub mod did_log_entry;
+pub mod did_witness;
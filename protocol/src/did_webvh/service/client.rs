@@ -0,0 +1,20 @@
+use http::StatusCode;
+
+#[derive(Clone, Debug)]
+pub struct DidWebvhHttpClientResponse {
+    pub(crate) status_code: StatusCode,
+    pub(crate) body: String,
+}
+
+impl DidWebvhHttpClientResponse {
+    pub fn new(status_code: StatusCode, body: String) -> Self {
+        Self { status_code, body }
+    }
+}
+
+#[trait_variant::make(Send)]
+pub trait DidWebvhHttpClient {
+    type Error: std::error::Error;
+    async fn get_did_log(&self, url: &str) -> Result<DidWebvhHttpClientResponse, Self::Error>;
+    async fn get_did_witness(&self, url: &str) -> Result<DidWebvhHttpClientResponse, Self::Error>;
+}
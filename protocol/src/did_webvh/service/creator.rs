@@ -0,0 +1,268 @@
+// Creation of a did:webvh log (did.jsonl).
+// https://identity.foundation/didwebvh/v0.5/#create-register
+
+use thiserror::Error;
+
+use crate::did_webvh::domain::crypto::proof::create_proof;
+use crate::did_webvh::domain::did::{DidWebvh, DidWebvhError};
+use crate::did_webvh::domain::did_document::{DidDocument, VerificationMethod};
+use crate::did_webvh::domain::did_log_entry::{
+    DidLogEntry, DidLogEntryError, Parameters, WEBVH_DID_SCID_PLACEHOLDER,
+};
+use crate::keyring::jwk::{Jwk, SignKeyToJwkError};
+use crate::keyring::keypair::{Ed25519KeyPair, KeyPair, KeyPairing, SignKeyPair};
+use crate::verifiable_credentials::eddsa_jcs_2022::{encode_multikey, EddsaJcs2022Error};
+
+#[derive(Debug, Error)]
+pub enum CreateDidLogError {
+    #[error("DID log is empty")]
+    Empty,
+    #[error("invalid log entry: {0:?}")]
+    InvalidEntry(DidLogEntryError),
+    #[error("failed to parse did: {0}")]
+    DidParse(#[from] DidWebvhError),
+    #[error("failed to sign log entry: {0}")]
+    Proof(#[from] EddsaJcs2022Error),
+    #[error("failed to convert sign key to jwk: {0}")]
+    SignKeyJwk(#[from] SignKeyToJwkError),
+    #[error("failed to serialize log entry: {0}")]
+    Json(#[from] serde_json::Error),
+}
+
+impl From<DidLogEntryError> for CreateDidLogError {
+    fn from(e: DidLogEntryError) -> Self {
+        CreateDidLogError::InvalidEntry(e)
+    }
+}
+
+fn sign_entry(
+    mut entry: DidLogEntry,
+    update_key: &Ed25519KeyPair,
+) -> Result<DidLogEntry, CreateDidLogError> {
+    let multikey = encode_multikey(&update_key.get_public_key());
+    entry.proof = None;
+    let proof = entry.generate_proof(&multikey, "authentication", "");
+    let proof = create_proof(&entry, proof, &update_key.get_secret_key())?;
+    entry.proof = Some(vec![proof]);
+    Ok(entry)
+}
+
+fn set_version_id(
+    mut entry: DidLogEntry,
+    version_number: u32,
+    previous: &str,
+) -> Result<DidLogEntry, CreateDidLogError> {
+    entry.version_id = previous.to_string();
+    entry.version_id = format!("{}-{}", version_number, entry.calc_entry_hash()?);
+    Ok(entry)
+}
+
+// Builds the first log entry from `entry` made by `DidLogEntry::new`, whose state and
+// parameters may refer to the DID with the SCID placeholder.
+// `update_key` becomes the only update key unless `updateKeys` is already set.
+pub fn create_genesis_entry(
+    mut entry: DidLogEntry,
+    update_key: &Ed25519KeyPair,
+) -> Result<(DidWebvh, DidLogEntry), CreateDidLogError> {
+    if entry.parameters.update_keys.is_none() {
+        entry.parameters.update_keys = Some(vec![encode_multikey(&update_key.get_public_key())]);
+    }
+    entry.version_id = WEBVH_DID_SCID_PLACEHOLDER.to_string();
+    entry.parameters.scid = Some(WEBVH_DID_SCID_PLACEHOLDER.to_string());
+    entry.proof = None;
+
+    let scid = entry.calc_entry_hash()?;
+    let json = serde_json::to_string(&entry)?;
+    let entry: DidLogEntry =
+        serde_json::from_str(&json.replace(WEBVH_DID_SCID_PLACEHOLDER, &scid))?;
+    let did: DidWebvh = entry.state.id.to_string().parse()?;
+
+    let entry = set_version_id(entry, 1, &scid)?;
+    Ok((did, sign_entry(entry, update_key)?))
+}
+
+// The did:webvh counterpart of `DidRepository::create_identifier`. The sign and encrypt keys
+// of `keyring` get the same key ids as in a Sidetree DID document.
+pub fn create_genesis_entry_from_keyring(
+    uri: &str,
+    keyring: &KeyPairing,
+    update_key: &Ed25519KeyPair,
+) -> Result<(DidWebvh, DidLogEntry), CreateDidLogError> {
+    let mut entry = DidLogEntry::new(uri)?;
+    let did = entry.state.id.clone();
+    let verification_method = |fragment: &str, r#type: &str, jwk: Jwk| VerificationMethod {
+        id: format!("{}#{}", did, fragment),
+        r#type: r#type.to_string(),
+        controller: did.clone(),
+        public_key_multibase: None,
+        public_key_jwk: Some(jwk),
+        public_key_base58: None,
+        blockchain_account_id: None,
+    };
+    let sign_key_type = match keyring.sign {
+        SignKeyPair::Secp256k1(_) => "EcdsaSecp256k1VerificationKey2019",
+        SignKeyPair::Secp256r1(_) => "EcdsaSecp256r1VerificationKey2019",
+    };
+    let sign = verification_method(
+        "signingKey",
+        sign_key_type,
+        keyring.sign.get_public_key().try_into()?,
+    );
+    let encrypt = verification_method(
+        "encryptionKey",
+        "X25519KeyAgreementKey2019",
+        keyring.encrypt.get_public_key().into(),
+    );
+    entry.state.authentication = Some(vec![sign.id.clone().into()]);
+    entry.state.assertion_method = Some(vec![sign.id.clone().into()]);
+    entry.state.key_agreement = Some(vec![encrypt.id.clone().into()]);
+    entry.state.verification_method = Some(vec![sign, encrypt]);
+    create_genesis_entry(entry, update_key)
+}
+
+// Builds the entry following `previous`. `parameters` holds only what changes.
+pub fn create_next_entry(
+    previous: &DidLogEntry,
+    state: DidDocument,
+    parameters: Parameters,
+    update_key: &Ed25519KeyPair,
+) -> Result<DidLogEntry, CreateDidLogError> {
+    let (version_number, _) = previous.parse_verion_id()?;
+    let mut entry = previous.generate_next_log_entry()?;
+    entry.parameters = parameters;
+    entry.state = state;
+    let entry = set_version_id(entry, version_number + 1, &previous.version_id)?;
+    sign_entry(entry, update_key)
+}
+
+// Appends a new version to `log` and returns it.
+pub fn append_entry<'a>(
+    log: &'a mut Vec<DidLogEntry>,
+    state: DidDocument,
+    parameters: Parameters,
+    update_key: &Ed25519KeyPair,
+) -> Result<&'a DidLogEntry, CreateDidLogError> {
+    let previous = log.last().ok_or(CreateDidLogError::Empty)?;
+    let entry = create_next_entry(previous, state, parameters, update_key)?;
+    log.push(entry);
+    Ok(log.last().expect("just pushed"))
+}
+
+// One JSON entry per line, as hosted at `DidWebvh::did_log_url`.
+pub fn serialize_did_log(log: &[DidLogEntry]) -> Result<String, CreateDidLogError> {
+    let mut did_log = String::new();
+    for entry in log {
+        did_log.push_str(&serde_json::to_string(entry)?);
+        did_log.push('\n');
+    }
+    Ok(did_log)
+}
+
+#[cfg(test)]
+mod tests {
+    use rand_core::OsRng;
+
+    use super::*;
+    use crate::did::did_repository::{get_encrypt_key, get_sign_key};
+    use crate::did::sidetree::payload::DidResolutionResponse;
+    use crate::did_webvh::domain::did_document::Service;
+    use crate::did_webvh::domain::did_log::{
+        parse_did_log, verify_did_log, DidLogVerificationError,
+    };
+
+    fn key_pair() -> Ed25519KeyPair {
+        Ed25519KeyPair::new(ed25519_dalek::SigningKey::generate(&mut OsRng))
+    }
+
+    #[test]
+    fn test_create_and_append_did_log() {
+        let key = key_pair();
+        let mut entry = DidLogEntry::new("example.com:devices").unwrap();
+        entry.state.authentication = Some(vec![format!("{}#key-1", entry.state.id).into()]);
+        let (did, genesis) = create_genesis_entry(entry, &key).unwrap();
+
+        assert_eq!(did.get_uri(), "example.com:devices");
+        assert_eq!(genesis.state.id, did.get_did().to_string());
+        assert_eq!(
+            genesis.state.authentication.as_ref().unwrap()[0].id(),
+            format!("{}#key-1", did.get_did())
+        );
+        assert!(genesis.version_id.starts_with("1-"));
+
+        let mut log = vec![genesis];
+        let mut state = log[0].state.clone();
+        state.service = Some(vec![Service {
+            id: format!("{}#service", did.get_did()),
+            r#type: "LinkedDomains".to_string(),
+            service_endpoint: "https://example.com".into(),
+            description: None,
+        }]);
+        let parameters = Parameters {
+            ttl: Some(60),
+            ..Default::default()
+        };
+        append_entry(&mut log, state.clone(), parameters, &key).unwrap();
+
+        let did_log = serialize_did_log(&log).unwrap();
+        assert_eq!(did_log.lines().count(), 2);
+        let resolution = verify_did_log(&did, &parse_did_log(&did_log).unwrap()).unwrap();
+        assert_eq!(resolution.did_document, state);
+        assert_eq!(resolution.did_document_metadata.ttl, Some(60));
+        assert!(resolution
+            .did_document_metadata
+            .version_id
+            .starts_with("2-"));
+    }
+
+    #[test]
+    fn test_create_genesis_entry_from_keyring() {
+        let keyring = KeyPairing::create_keyring(OsRng);
+        let (did, genesis) =
+            create_genesis_entry_from_keyring("example.com", &keyring, &key_pair()).unwrap();
+
+        let resolution = verify_did_log(&did, &[genesis]).unwrap();
+        let document = resolution.did_document.clone();
+        let methods = document.verification_method.unwrap();
+        assert_eq!(methods[0].id, format!("{}#signingKey", did.get_did()));
+        assert_eq!(methods[1].id, format!("{}#encryptionKey", did.get_did()));
+        assert_eq!(
+            document.key_agreement.unwrap(),
+            vec![methods[1].id.clone().into()]
+        );
+
+        let response = DidResolutionResponse::from(resolution);
+        assert_eq!(response.did_document.id, did.get_did().to_string());
+        assert_eq!(
+            get_sign_key(&response.did_document).unwrap(),
+            keyring.sign.get_public_key()
+        );
+        assert_eq!(
+            get_encrypt_key(&response.did_document).unwrap(),
+            keyring.encrypt.get_public_key()
+        );
+    }
+
+    #[test]
+    fn test_append_entry_with_unauthorized_key() {
+        let key = key_pair();
+        let entry = DidLogEntry::new("example.com").unwrap();
+        let (did, genesis) = create_genesis_entry(entry, &key).unwrap();
+        let mut log = vec![genesis];
+        let state = log[0].state.clone();
+        append_entry(&mut log, state, Parameters::default(), &key_pair()).unwrap();
+
+        assert!(matches!(
+            verify_did_log(&did, &log),
+            Err(DidLogVerificationError::UnauthorizedUpdateKey(2))
+        ));
+        assert!(matches!(
+            append_entry(
+                &mut vec![],
+                log[0].state.clone(),
+                Parameters::default(),
+                &key
+            ),
+            Err(CreateDidLogError::Empty)
+        ));
+    }
+}
@@ -0,0 +1,493 @@
+use std::collections::HashMap;
+
+use http::StatusCode;
+
+use super::client::DidWebvhHttpClient;
+use crate::did::resolution::{ResolutionError, ResolutionErrorCode};
+use crate::did_webvh::domain::{
+    did::{DidWebvh, DidWebvhError},
+    did_log::{parse_did_log, verify_did_log, DidLogVerificationError, DidWebvhResolution},
+    did_log_entry::DidLogEntry,
+    did_witness::{
+        parse_did_witness, verify_witness_proofs, witness_dids, DidWitnessVerificationError,
+    },
+};
+use crate::verifiable_credentials::eddsa_jcs_2022::decode_multikey;
+
+#[derive(Debug, thiserror::Error)]
+pub enum ResolveIdentifierError<HttpClientError: std::error::Error> {
+    #[error("Failed to parse did: {0}")]
+    DidParse(#[from] DidWebvhError),
+    #[error("Failed to get did log. response: {0}")]
+    RequestFailed(String),
+    #[error("Failed to verify did log: {0}")]
+    Verification(#[from] DidLogVerificationError),
+    #[error("Failed to verify witness proofs: {0}")]
+    Witness(#[from] DidWitnessVerificationError),
+    #[error("did log is not witnessed")]
+    WitnessRequired,
+    #[error("Failed to send request: {0}")]
+    HttpClient(HttpClientError),
+}
+
+impl<E: std::error::Error> ResolutionError for ResolveIdentifierError<E> {
+    fn error_code(&self) -> ResolutionErrorCode {
+        // NOTE: A log failing verification is not a transient failure, so it must not
+        //       fall back to a pinned copy.
+        match self {
+            ResolveIdentifierError::DidParse(_)
+            | ResolveIdentifierError::Verification(_)
+            | ResolveIdentifierError::Witness(_)
+            | ResolveIdentifierError::WitnessRequired => ResolutionErrorCode::InvalidDid,
+            ResolveIdentifierError::RequestFailed(_) | ResolveIdentifierError::HttpClient(_) => {
+                ResolutionErrorCode::InternalError
+            }
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct DidWebvhResolver<C: DidWebvhHttpClient> {
+    client: C,
+    require_witness: bool,
+}
+
+impl<C> DidWebvhResolver<C>
+where
+    C: DidWebvhHttpClient + Send + Sync,
+    C::Error: Send + Sync,
+{
+    pub fn new(client: C) -> Self {
+        Self {
+            client,
+            require_witness: false,
+        }
+    }
+
+    // Rejects DIDs without a witness config in effect at the latest entry,
+    // e.g. for production devices.
+    pub fn require_witness(mut self, require_witness: bool) -> Self {
+        self.require_witness = require_witness;
+        self
+    }
+
+    // Fetches did.jsonl of `did` and verifies the whole log, including witness proofs.
+    // Returns None when the log is not hosted.
+    pub async fn resolve_identifier(
+        &self,
+        did: &str,
+    ) -> Result<Option<DidWebvhResolution>, ResolveIdentifierError<C::Error>> {
+        let did: DidWebvh = did.parse()?;
+        let Some((entries, resolution)) = self.get_verified_did_log(&did).await? else {
+            return Ok(None);
+        };
+
+        // NOTE: Witnesses listed earlier in the log still have to approve turning them off.
+        if self.require_witness && resolution.did_document_metadata.witness.is_none() {
+            return Err(ResolveIdentifierError::WitnessRequired);
+        }
+        let witnesses = witness_dids(&entries);
+        if witnesses.is_empty() {
+            return Ok(Some(resolution));
+        }
+
+        let response = self
+            .client
+            .get_did_witness(&did.did_witness_url())
+            .await
+            .map_err(ResolveIdentifierError::HttpClient)?;
+        let witness_proofs = match response.status_code {
+            StatusCode::OK => parse_did_witness(&response.body)?,
+            // NOTE: Fails below unless the threshold is 0.
+            StatusCode::NOT_FOUND => vec![],
+            _ => {
+                return Err(ResolveIdentifierError::RequestFailed(format!(
+                    "{:?}",
+                    response
+                )))
+            }
+        };
+
+        // NOTE: Witness logs are verified without their own witnesses, so resolution
+        //       does not recurse. An unresolvable witness just does not count, whether it
+        //       is not hosted, unreachable or has an invalid log.
+        let mut witness_keys = HashMap::new();
+        for witness in witnesses {
+            let Ok(witness) = DidWebvh::try_from(witness) else {
+                continue;
+            };
+            if let Ok(Some((_, witness))) = self.get_verified_did_log(&witness).await {
+                witness_keys.extend(ed25519_keys(&witness));
+            }
+        }
+        verify_witness_proofs(&entries, &witness_proofs, &witness_keys)?;
+
+        Ok(Some(resolution))
+    }
+
+    async fn get_verified_did_log(
+        &self,
+        did: &DidWebvh,
+    ) -> Result<Option<(Vec<DidLogEntry>, DidWebvhResolution)>, ResolveIdentifierError<C::Error>>
+    {
+        let response = self
+            .client
+            .get_did_log(&did.did_log_url())
+            .await
+            .map_err(ResolveIdentifierError::HttpClient)?;
+
+        match response.status_code {
+            StatusCode::OK => {
+                let entries = parse_did_log(&response.body)?;
+                let resolution = verify_did_log(did, &entries)?;
+                Ok(Some((entries, resolution)))
+            }
+            StatusCode::NOT_FOUND => Ok(None),
+            _ => Err(ResolveIdentifierError::RequestFailed(format!(
+                "{:?}",
+                response
+            ))),
+        }
+    }
+}
+
+// Ed25519 multikeys of the verification methods, by absolute id.
+// NOTE: Only ids of the DID itself count, or a witness could publish keys as another witness.
+fn ed25519_keys(
+    resolution: &DidWebvhResolution,
+) -> impl Iterator<Item = (String, ed25519_dalek::VerifyingKey)> + '_ {
+    let did = &resolution.did_document.id;
+    let prefix = format!("{}#", did);
+    resolution
+        .did_document
+        .verification_method
+        .iter()
+        .flatten()
+        .filter_map(move |v| {
+            let id = if v.id.starts_with('#') {
+                format!("{}{}", did, v.id)
+            } else {
+                v.id.clone()
+            };
+            if !id.starts_with(&prefix) {
+                return None;
+            }
+            let key = decode_multikey(v.public_key_multibase.as_deref()?).ok()?;
+            Some((id, key))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use ed25519_dalek::SigningKey;
+    use rand_core::OsRng;
+
+    use super::*;
+    use crate::did_webvh::domain::did_document::VerificationMethod;
+    use crate::did_webvh::domain::did_log::tests::genesis;
+    use crate::did_webvh::domain::did_log_entry::{Parameters, Witness, WitnessConfig};
+    use crate::did_webvh::domain::did_witness::{tests::witness_proof, WitnessProof};
+    use crate::did_webvh::service::client::DidWebvhHttpClientResponse;
+    use crate::did_webvh::service::creator::{
+        append_entry, create_genesis_entry, serialize_did_log,
+    };
+    use crate::keyring::keypair::{Ed25519KeyPair, KeyPair};
+    use crate::verifiable_credentials::eddsa_jcs_2022::encode_multikey;
+
+    #[derive(Debug, thiserror::Error)]
+    #[error("dummy error")]
+    struct DummyError;
+
+    struct MockHttpClient {
+        files: HashMap<String, String>,
+    }
+
+    impl MockHttpClient {
+        fn get(&self, url: &str) -> DidWebvhHttpClientResponse {
+            match self.files.get(url) {
+                Some(body) => DidWebvhHttpClientResponse::new(StatusCode::OK, body.clone()),
+                None => DidWebvhHttpClientResponse::new(StatusCode::NOT_FOUND, String::new()),
+            }
+        }
+    }
+
+    impl DidWebvhHttpClient for MockHttpClient {
+        type Error = DummyError;
+        async fn get_did_log(&self, url: &str) -> Result<DidWebvhHttpClientResponse, DummyError> {
+            Ok(self.get(url))
+        }
+        async fn get_did_witness(
+            &self,
+            url: &str,
+        ) -> Result<DidWebvhHttpClientResponse, DummyError> {
+            Ok(self.get(url))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_resolve_identifier() {
+        let key = SigningKey::generate(&mut OsRng);
+        let (did, entry) = genesis(&key, None);
+        let resolver = DidWebvhResolver::new(MockHttpClient {
+            files: HashMap::from([(
+                "https://example.com/.well-known/did.jsonl".to_string(),
+                serde_json::to_string(&entry).unwrap(),
+            )]),
+        });
+
+        let resolution = resolver
+            .resolve_identifier(&did.get_did().to_string())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(resolution.did_document.id, did.get_did().to_string());
+
+        let did = DidWebvh::new(did.get_scid(), "example.com:path").unwrap();
+        let resolution = resolver
+            .resolve_identifier(&did.get_did().to_string())
+            .await
+            .unwrap();
+        assert!(resolution.is_none());
+
+        assert!(matches!(
+            resolver.resolve_identifier("did:web:example.com").await,
+            Err(ResolveIdentifierError::DidParse(_))
+        ));
+
+        let resolver = resolver.require_witness(true);
+        assert!(matches!(
+            resolver
+                .resolve_identifier(&did.get_did().to_string())
+                .await,
+            Ok(None)
+        ));
+        let did = entry.state.id.to_string();
+        assert!(matches!(
+            resolver.resolve_identifier(&did).await,
+            Err(ResolveIdentifierError::WitnessRequired)
+        ));
+    }
+
+    // A witness DID hosted at `uri`, with its key as a Multikey verification method.
+    fn witness(
+        uri: &str,
+        key: &Ed25519KeyPair,
+        files: &mut HashMap<String, String>,
+    ) -> (DidWebvh, String) {
+        witness_with_methods(uri, key, &[], files)
+    }
+
+    // NOTE: `other_ids` are extra verification methods of the same key, e.g. spoofed ones.
+    fn witness_with_methods(
+        uri: &str,
+        key: &Ed25519KeyPair,
+        other_ids: &[String],
+        files: &mut HashMap<String, String>,
+    ) -> (DidWebvh, String) {
+        let mut entry = DidLogEntry::new(uri).unwrap();
+        let id = format!("{}#key-1", entry.state.id);
+        let method = |id: &str| VerificationMethod {
+            id: id.to_string(),
+            r#type: "Multikey".to_string(),
+            controller: entry.state.id.clone(),
+            public_key_multibase: Some(encode_multikey(&key.get_public_key())),
+            public_key_jwk: None,
+            public_key_base58: None,
+            blockchain_account_id: None,
+        };
+        let methods = std::iter::once(&id)
+            .chain(other_ids)
+            .map(|v| method(v))
+            .collect();
+        entry.state.verification_method = Some(methods);
+        let (did, entry) = create_genesis_entry(entry, key).unwrap();
+        files.insert(did.did_log_url(), serialize_did_log(&[entry]).unwrap());
+        let id = id.replace("{SCID}", did.get_scid());
+        (did, id)
+    }
+
+    #[tokio::test]
+    async fn test_resolve_identifier_with_witnesses() {
+        let mut files = HashMap::new();
+        let witness1_key = Ed25519KeyPair::new(SigningKey::generate(&mut OsRng));
+        let witness2_key = Ed25519KeyPair::new(SigningKey::generate(&mut OsRng));
+        let (witness1, witness1_method) =
+            witness("witness1.example.com", &witness1_key, &mut files);
+        let (witness2, witness2_method) =
+            witness("witness2.example.com", &witness2_key, &mut files);
+
+        let key = Ed25519KeyPair::new(SigningKey::generate(&mut OsRng));
+        let mut entry = DidLogEntry::new("example.com:devices").unwrap();
+        entry.parameters.witness = Some(WitnessConfig {
+            threshold: 2,
+            witnesses: vec![
+                Witness {
+                    id: witness1.get_did().clone(),
+                    weight: 1,
+                },
+                Witness {
+                    id: witness2.get_did().clone(),
+                    weight: 1,
+                },
+            ],
+        });
+        let (did, entry) = create_genesis_entry(entry, &key).unwrap();
+        files.insert(
+            did.did_log_url(),
+            serialize_did_log(std::slice::from_ref(&entry)).unwrap(),
+        );
+        let did = did.get_did().to_string();
+
+        let resolver = DidWebvhResolver::new(MockHttpClient {
+            files: files.clone(),
+        });
+        assert!(matches!(
+            resolver.resolve_identifier(&did).await,
+            Err(ResolveIdentifierError::Witness(
+                DidWitnessVerificationError::InsufficientWeight(1, 0, 2)
+            ))
+        ));
+
+        let sign = |method: &str, key: &Ed25519KeyPair| {
+            witness_proof(&entry.version_id, method, &key.get_secret_key())
+        };
+        let witness_url = entry
+            .state
+            .id
+            .parse::<DidWebvh>()
+            .unwrap()
+            .did_witness_url();
+        let witness_files = |proof: Vec<_>| {
+            let mut files = files.clone();
+            let proofs = vec![WitnessProof {
+                version_id: entry.version_id.clone(),
+                proof,
+            }];
+            files.insert(witness_url.clone(), serde_json::to_string(&proofs).unwrap());
+            DidWebvhResolver::new(MockHttpClient { files }).require_witness(true)
+        };
+
+        let resolver = witness_files(vec![
+            sign(&witness1_method, &witness1_key),
+            sign(&witness2_method, &witness2_key),
+        ]);
+        let resolution = resolver.resolve_identifier(&did).await.unwrap().unwrap();
+        assert_eq!(resolution.did_document.id.to_string(), did);
+
+        // NOTE: A witness with a broken log does not fail the resolution, but does not count.
+        let resolver = {
+            let mut resolver = witness_files(vec![
+                sign(&witness1_method, &witness1_key),
+                sign(&witness2_method, &witness2_key),
+            ]);
+            resolver
+                .client
+                .files
+                .insert(witness1.did_log_url(), "{".to_string());
+            resolver
+        };
+        assert!(matches!(
+            resolver.resolve_identifier(&did).await,
+            Err(ResolveIdentifierError::Witness(
+                DidWitnessVerificationError::InsufficientWeight(1, 1, 2)
+            ))
+        ));
+
+        let resolver = witness_files(vec![
+            sign(&witness1_method, &witness1_key),
+            sign(&witness2_method, &witness1_key),
+        ]);
+        assert!(matches!(
+            resolver.resolve_identifier(&did).await,
+            Err(ResolveIdentifierError::Witness(
+                DidWitnessVerificationError::InvalidProof(..)
+            ))
+        ));
+
+        // NOTE: A later entry turning the witnesses off without their approval fails,
+        //       and with it, the DID is no longer witnessed.
+        let mut log = vec![entry.clone()];
+        let parameters = Parameters {
+            witness: Some(WitnessConfig::default()),
+            ..Default::default()
+        };
+        append_entry(&mut log, entry.state.clone(), parameters, &key).unwrap();
+        let mut resolver = witness_files(vec![
+            sign(&witness1_method, &witness1_key),
+            sign(&witness2_method, &witness2_key),
+        ]);
+        let did_log_url = did.parse::<DidWebvh>().unwrap().did_log_url();
+        resolver
+            .client
+            .files
+            .insert(did_log_url, serialize_did_log(&log).unwrap());
+        assert!(matches!(
+            resolver.resolve_identifier(&did).await,
+            Err(ResolveIdentifierError::WitnessRequired)
+        ));
+        let resolver = resolver.require_witness(false);
+        assert!(matches!(
+            resolver.resolve_identifier(&did).await,
+            Err(ResolveIdentifierError::Witness(
+                DidWitnessVerificationError::InsufficientWeight(2, 0, 2)
+            ))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_resolve_identifier_with_spoofing_witness() {
+        let mut files = HashMap::new();
+        let witness1_key = Ed25519KeyPair::new(SigningKey::generate(&mut OsRng));
+        let witness2_key = Ed25519KeyPair::new(SigningKey::generate(&mut OsRng));
+        let (witness2, _) = witness("witness2.example.com", &witness2_key, &mut files);
+        // NOTE: witness1 claims a key of witness2 in its own document.
+        let spoofed_method = format!("{}#key-2", witness2.get_did());
+        let (witness1, witness1_method) = witness_with_methods(
+            "witness1.example.com",
+            &witness1_key,
+            std::slice::from_ref(&spoofed_method),
+            &mut files,
+        );
+
+        let key = Ed25519KeyPair::new(SigningKey::generate(&mut OsRng));
+        let mut entry = DidLogEntry::new("example.com:devices").unwrap();
+        entry.parameters.witness = Some(WitnessConfig {
+            threshold: 2,
+            witnesses: vec![
+                Witness {
+                    id: witness1.get_did().clone(),
+                    weight: 1,
+                },
+                Witness {
+                    id: witness2.get_did().clone(),
+                    weight: 1,
+                },
+            ],
+        });
+        let (did, entry) = create_genesis_entry(entry, &key).unwrap();
+        files.insert(
+            did.did_log_url(),
+            serialize_did_log(std::slice::from_ref(&entry)).unwrap(),
+        );
+        let sign =
+            |method: &str| witness_proof(&entry.version_id, method, &witness1_key.get_secret_key());
+        let proofs = vec![WitnessProof {
+            version_id: entry.version_id.clone(),
+            proof: vec![sign(&witness1_method), sign(&spoofed_method)],
+        }];
+        files.insert(
+            did.did_witness_url(),
+            serde_json::to_string(&proofs).unwrap(),
+        );
+
+        let resolver = DidWebvhResolver::new(MockHttpClient { files });
+        assert!(matches!(
+            resolver
+                .resolve_identifier(&did.get_did().to_string())
+                .await,
+            Err(ResolveIdentifierError::Witness(
+                DidWitnessVerificationError::InsufficientWeight(1, 1, 2)
+            ))
+        ));
+    }
+}
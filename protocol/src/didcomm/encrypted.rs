@@ -7,8 +7,10 @@ use thiserror::Error;
 
 use crate::{
     did::{
-        did_repository::{get_encrypt_key, get_sign_key, DidRepository, GetPublicKeyError},
-        sidetree::payload::DidDocument,
+        did_document::DidDocument,
+        did_repository::{
+            get_encrypt_key, get_sign_key, get_verification_key, DidResolver, GetPublicKeyError,
+        },
     },
     didcomm::types::{DidCommMessage, FindSenderError},
     keyring::keypair::{KeyPair, KeyPairing},
@@ -37,7 +39,7 @@ pub trait DidCommEncryptedService: Sync {
     ) -> Result<VerifiedContainer, Self::VerifyError>;
 }
 
-fn didcomm_generate<R: DidRepository, V: DidVcService>(
+fn didcomm_generate<R: DidResolver, V: DidVcService>(
     body: &VerifiableCredentials,
     from_keyring: &KeyPairing,
     to_doc: &DidDocument,
@@ -86,7 +88,7 @@ fn didcomm_generate<R: DidRepository, V: DidVcService>(
     Ok(serde_json::from_str::<DidCommMessage>(&seal_message)?)
 }
 
-async fn generate<R: DidRepository, V: DidVcService>(
+async fn generate<R: DidResolver, V: DidVcService>(
     did_repository: &R,
     vc_service: &V,
     model: VerifiableCredentials,
@@ -100,6 +102,7 @@ async fn generate<R: DidRepository, V: DidVcService>(
 > {
     let body = vc_service
         .generate(model, from_keyring)
+        .await
         .map_err(DidCommEncryptedServiceGenerateError::VcService)?;
     let to_doc = did_repository
         .find_identifier(to_did)
@@ -113,7 +116,7 @@ async fn generate<R: DidRepository, V: DidVcService>(
     didcomm_generate::<R, V>(&body, from_keyring, &to_doc, metadata, attachment_link)
 }
 
-fn didcomm_verify<R: DidRepository>(
+fn didcomm_verify<R: DidResolver>(
     from_doc: &DidDocument,
     my_keyring: &KeyPairing,
     message: &DidCommMessage,
@@ -156,7 +159,7 @@ fn didcomm_verify<R: DidRepository>(
     }
 }
 
-async fn verify<R: DidRepository>(
+async fn verify<R: DidResolver>(
     did_repository: &R,
     my_keyring: &KeyPairing,
     message: &DidCommMessage,
@@ -166,13 +169,17 @@ async fn verify<R: DidRepository>(
         .find_identifier(&other_did)
         .await
         .map_err(DidCommEncryptedServiceVerifyError::SidetreeFindRequestFailed)?
-        .ok_or(DidCommEncryptedServiceVerifyError::DidDocNotFound(
-            other_did,
-        ))?
-        .did_document;
+        .ok_or_else(|| DidCommEncryptedServiceVerifyError::DidDocNotFound(other_did.clone()))?;
+    if other_doc.is_deactivated() {
+        return Err(DidCommEncryptedServiceVerifyError::Deactivated(other_did));
+    }
+    let other_doc = other_doc.did_document;
     let mut container = didcomm_verify::<R>(&other_doc, my_keyring, message)?;
     // For performance, call low level api
-    let public_key = get_sign_key(&other_doc)?;
+    let public_key = match &container.message.proof {
+        Some(proof) => get_verification_key(&other_doc, &proof.verification_method)?,
+        None => get_sign_key(&other_doc)?,
+    };
     let body = CredentialSigner::verify(container.message, &public_key)?;
     container.message = body;
     Ok(container)
@@ -202,6 +209,8 @@ where
 pub enum DidCommEncryptedServiceVerifyError<FindIdentifierError: std::error::Error> {
     #[error("failed to get did document: {0}")]
     DidDocNotFound(String),
+    #[error("did is deactivated: {0}")]
+    Deactivated(String),
     #[error("something went wrong with vc service: {0}")]
     VcService(#[from] CredentialSignerVerifyError),
     #[error("failed to find identifier: {0}")]
@@ -220,7 +229,7 @@ pub enum DidCommEncryptedServiceVerifyError<FindIdentifierError: std::error::Err
 
 impl<R> DidCommEncryptedService for R
 where
-    R: DidRepository + DidVcService,
+    R: DidResolver + DidVcService,
 {
     type GenerateError =
         DidCommEncryptedServiceGenerateError<R::FindIdentifierError, R::GenerateError>;
@@ -246,7 +255,7 @@ where
 
 pub struct DidCommServiceWithAttachment<R>
 where
-    R: DidRepository + DidVcService,
+    R: DidResolver + DidVcService,
 {
     vc_service: R,
     attachment_link: String,
@@ -254,7 +263,7 @@ where
 
 impl<R> DidCommServiceWithAttachment<R>
 where
-    R: DidRepository + DidVcService,
+    R: DidResolver + DidVcService,
 {
     pub fn new(did_repository: R, attachment_link: String) -> Self {
         Self {
@@ -266,7 +275,7 @@ where
 
 impl<R> DidCommEncryptedService for DidCommServiceWithAttachment<R>
 where
-    R: DidRepository + DidVcService,
+    R: DidResolver + DidVcService,
 {
     type GenerateError =
         DidCommEncryptedServiceGenerateError<R::FindIdentifierError, R::GenerateError>;
@@ -317,7 +326,7 @@ mod tests {
             types::DidCommMessage,
         },
         keyring::keypair::KeyPairing,
-        verifiable_credentials::types::VerifiableCredentials,
+        verifiable_credentials::{did_vc::DidVcServiceGenerateError, types::VerifiableCredentials},
     };
 
     #[tokio::test]
@@ -393,17 +402,20 @@ mod tests {
             let message = json!({"test": "0123456789abcdef"});
             let issuance_date = Utc::now();
 
-            let model = VerifiableCredentials::new(from_did, message, issuance_date);
+            let model = VerifiableCredentials::new(from_did.clone(), message, issuance_date);
             let res = repo
                 .generate(model, &from_keyring, &to_did, None)
                 .await
                 .unwrap_err();
 
-            if let DidCommEncryptedServiceGenerateError::DidPublicKeyNotFound(
-                GetPublicKeyError::PublicKeyNotFound(did),
+            // NOTE: The sign key of the sender is looked up first, to refer to it in the proof.
+            if let DidCommEncryptedServiceGenerateError::VcService(
+                DidVcServiceGenerateError::SignKeyNotFound(GetPublicKeyError::PublicKeyNotFound(
+                    did,
+                )),
             ) = res
             {
-                assert_eq!(did, to_did);
+                assert_eq!(did, from_did);
             } else {
                 panic!("unexpected result: {:?}", res);
             }
@@ -423,10 +435,10 @@ mod tests {
             metadata: Option<&Value>,
             issuance_date: DateTime<Utc>,
         ) -> DidCommMessage {
-            let repo = MockDidRepository::from_single(BTreeMap::from_iter([(
-                to_did.to_string(),
-                to_keyring.clone(),
-            )]));
+            let repo = MockDidRepository::from_single(BTreeMap::from_iter([
+                (from_did.to_string(), from_keyring.clone()),
+                (to_did.to_string(), to_keyring.clone()),
+            ]));
 
             let model =
                 VerifiableCredentials::new(from_did.to_string(), message.clone(), issuance_date);
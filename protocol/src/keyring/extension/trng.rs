@@ -1,2 +1,260 @@
-// TODO: Impl TRNG based CryptoRng trait
+// TRNG extension based CryptoRng.
+//
+// The extension is a shared library exporting a function with the C ABI
+// `int32_t read(uint8_t *buf, size_t len)`, which fills `buf` with `len`
+// random bytes from the hardware RNG and returns 0 on success.
 // https://rust-random.github.io/rand/src/rand/rngs/std.rs.html#94
+
+use std::num::NonZeroU32;
+
+use libloading::Library;
+use rand_chacha::ChaCha20Rng;
+use rand_core::{CryptoRng, OsRng, RngCore, SeedableRng};
+use thiserror::Error;
+use zeroize::Zeroizing;
+
+use super::Extension;
+
+type TrngReadFn = unsafe extern "C" fn(buf: *mut u8, len: usize) -> i32;
+
+// NOTE: Hardware RNG drivers often cap the size of a single request.
+const MAX_READ_SIZE: usize = 256;
+
+// NOTE: Cutoffs of NIST SP 800-90B 4.4 health tests,
+//       assuming 4 bits of min-entropy per byte and a false positive rate of 2^-20.
+const REPETITION_COUNT_CUTOFF: usize = 6;
+const ADAPTIVE_PROPORTION_WINDOW: usize = 512;
+const ADAPTIVE_PROPORTION_CUTOFF: usize = 62;
+
+const TRNG_ERROR_CODE: u32 = rand_core::Error::CUSTOM_START;
+
+#[derive(Debug, Error)]
+pub enum TrngError {
+    #[error("failed to load extension: {0}")]
+    Load(#[from] libloading::Error),
+    #[error("extension returned error code: {0}")]
+    Read(i32),
+    #[error("health test failed: {0}")]
+    HealthTest(&'static str),
+    #[error("fallback to OsRng failed")]
+    OsRng,
+}
+
+pub struct Trng {
+    read: TrngReadFn,
+    // Whether to fill from OsRng when a read fails after loading, e.g. a failed health test.
+    os_rng_fallback: bool,
+    // NOTE: `read` points into this library, so keep it loaded while Trng lives.
+    _library: Library,
+}
+
+impl Trng {
+    pub fn load(extension: &Extension) -> Result<Self, TrngError> {
+        // SAFETY: The extension is trusted configuration and must export `symbol` with TrngReadFn ABI.
+        unsafe {
+            let library = Library::new(&extension.filename)?;
+            let read = *library.get::<TrngReadFn>(extension.symbol.as_bytes())?;
+            Ok(Trng {
+                read,
+                os_rng_fallback: false,
+                _library: library,
+            })
+        }
+    }
+
+    pub fn with_os_rng_fallback(mut self) -> Self {
+        self.os_rng_fallback = true;
+        self
+    }
+
+    pub fn read(&mut self, dest: &mut [u8]) -> Result<(), TrngError> {
+        for chunk in dest.chunks_mut(MAX_READ_SIZE) {
+            // SAFETY: `chunk` is a valid writable buffer of `chunk.len()` bytes.
+            let code = unsafe { (self.read)(chunk.as_mut_ptr(), chunk.len()) };
+            if code != 0 {
+                return Err(TrngError::Read(code));
+            }
+            repetition_count_test(chunk)?;
+        }
+        Ok(())
+    }
+
+    fn try_read(&mut self, dest: &mut [u8]) -> Result<(), TrngError> {
+        match self.read(dest) {
+            Err(e) if self.os_rng_fallback => {
+                log::warn!("TRNG extension failed, fallback to OsRng: {}", e);
+                OsRng.try_fill_bytes(dest).map_err(|_| TrngError::OsRng)
+            }
+            result => result,
+        }
+    }
+
+    // Startup health test. Should pass before any key is generated from this source.
+    pub fn health_test(&mut self) -> Result<(), TrngError> {
+        let mut sample = [0u8; ADAPTIVE_PROPORTION_WINDOW];
+        self.read(&mut sample)?;
+        adaptive_proportion_test(&sample)
+    }
+
+    // Seeds a ChaCha20 CSPRNG for key generation, so that a failed read is returned
+    // here rather than reached through the infallible RngCore::fill_bytes.
+    pub fn seed_rng(&mut self) -> Result<ChaCha20Rng, TrngError> {
+        let mut seed = Zeroizing::new([0u8; 32]);
+        self.try_read(&mut seed[..])?;
+        Ok(ChaCha20Rng::from_seed(*seed))
+    }
+}
+
+impl RngCore for Trng {
+    fn next_u32(&mut self) -> u32 {
+        rand_core::impls::next_u32_via_fill(self)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        rand_core::impls::next_u64_via_fill(self)
+    }
+
+    // NOTE: A failed read either falls back to OsRng or panics rather than yielding
+    //       unchecked bytes. Generate keys from seed_rng, which returns the failure.
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        if let Err(e) = self.try_read(dest) {
+            panic!("TRNG extension failed while generating random bytes: {}", e);
+        }
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand_core::Error> {
+        self.try_read(dest).map_err(|_| {
+            NonZeroU32::new(TRNG_ERROR_CODE)
+                .expect("custom error code is not zero")
+                .into()
+        })
+    }
+}
+
+impl CryptoRng for Trng {}
+
+fn repetition_count_test(sample: &[u8]) -> Result<(), TrngError> {
+    let mut count = 1;
+    for window in sample.windows(2) {
+        if window[0] == window[1] {
+            count += 1;
+            if count >= REPETITION_COUNT_CUTOFF {
+                return Err(TrngError::HealthTest("repetition count"));
+            }
+        } else {
+            count = 1;
+        }
+    }
+    Ok(())
+}
+
+fn adaptive_proportion_test(sample: &[u8]) -> Result<(), TrngError> {
+    for window in sample.chunks(ADAPTIVE_PROPORTION_WINDOW) {
+        let first = window[0];
+        let count = window.iter().filter(|&&v| v == first).count();
+        if count >= ADAPTIVE_PROPORTION_CUTOFF {
+            return Err(TrngError::HealthTest("adaptive proportion"));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    #[test]
+    pub fn test_repetition_count() {
+        let sample: Vec<u8> = (0..=255).collect();
+        assert!(repetition_count_test(&sample).is_ok());
+        assert!(repetition_count_test(&[0xAA; 5]).is_ok());
+        assert!(matches!(
+            repetition_count_test(&[0xAA; 6]),
+            Err(TrngError::HealthTest(_))
+        ));
+    }
+
+    #[test]
+    pub fn test_adaptive_proportion() {
+        let sample: Vec<u8> = (0..ADAPTIVE_PROPORTION_WINDOW).map(|v| v as u8).collect();
+        assert!(adaptive_proportion_test(&sample).is_ok());
+
+        let sample: Vec<u8> = (0..ADAPTIVE_PROPORTION_WINDOW)
+            .map(|v| if v % 4 == 0 { 0 } else { v as u8 | 1 })
+            .collect();
+        assert!(matches!(
+            adaptive_proportion_test(&sample),
+            Err(TrngError::HealthTest(_))
+        ));
+    }
+
+    #[test]
+    pub fn test_load_missing_library() {
+        let extension = Extension {
+            filename: "libnodex_missing_trng.so".to_string(),
+            symbol: "read".to_string(),
+        };
+        assert!(matches!(Trng::load(&extension), Err(TrngError::Load(_))));
+    }
+
+    // NOTE: A stuck source, failing the repetition count test.
+    #[cfg(all(target_os = "linux", target_env = "gnu"))]
+    fn stuck_trng() -> Trng {
+        unsafe extern "C" fn read(buf: *mut u8, len: usize) -> i32 {
+            std::ptr::write_bytes(buf, 0xAA, len);
+            0
+        }
+        Trng {
+            read,
+            os_rng_fallback: false,
+            _library: unsafe { Library::new("libc.so.6").unwrap() },
+        }
+    }
+
+    #[cfg(all(target_os = "linux", target_env = "gnu"))]
+    #[test]
+    #[should_panic(expected = "health test failed: repetition count")]
+    pub fn test_fill_bytes_health_test_failed() {
+        stuck_trng().fill_bytes(&mut [0u8; 32]);
+    }
+
+    #[cfg(all(target_os = "linux", target_env = "gnu"))]
+    #[test]
+    pub fn test_seed_rng_health_test_failed() {
+        assert!(matches!(
+            stuck_trng().seed_rng(),
+            Err(TrngError::HealthTest("repetition count"))
+        ));
+        assert!(stuck_trng().with_os_rng_fallback().seed_rng().is_ok());
+    }
+
+    #[cfg(all(target_os = "linux", target_env = "gnu"))]
+    #[test]
+    pub fn test_fill_bytes_os_rng_fallback() {
+        let mut trng = stuck_trng();
+        assert!(trng.try_fill_bytes(&mut [0u8; 32]).is_err());
+
+        let mut trng = trng.with_os_rng_fallback();
+        let mut dest = [0u8; 32];
+        trng.fill_bytes(&mut dest);
+        assert!(dest.iter().any(|&v| v != 0xAA));
+    }
+
+    // NOTE: getentropy(3) has the same ABI as the extension, so libc stands in for a TRNG.
+    #[cfg(all(target_os = "linux", target_env = "gnu"))]
+    #[test]
+    pub fn test_generate_keyring() {
+        use crate::keyring::keypair::{KeyPair, KeyPairing};
+
+        let extension = Extension {
+            filename: "libc.so.6".to_string(),
+            symbol: "getentropy".to_string(),
+        };
+        let mut trng = Trng::load(&extension).unwrap();
+        trng.health_test().unwrap();
+
+        let keyring = KeyPairing::create_keyring(trng.seed_rng().unwrap());
+        assert_eq!(keyring.update.get_secret_key().to_bytes().len(), 32);
+        assert_ne!(trng.next_u64(), trng.next_u64());
+    }
+}
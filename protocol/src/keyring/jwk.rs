@@ -4,9 +4,13 @@ pub use data_encoding;
 use data_encoding::{DecodeError, DecodePartial, BASE64URL_NOPAD};
 use k256::elliptic_curve::sec1::ToEncodedPoint;
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
 use thiserror::Error;
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+use crate::keyring::keypair::SignPublicKey;
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct Jwk {
     #[serde(rename = "kty")]
     kty: String,
@@ -19,6 +23,22 @@ pub struct Jwk {
 
     #[serde(rename = "y", skip_serializing_if = "Option::is_none")]
     y: Option<String>,
+
+    #[serde(rename = "kid", skip_serializing_if = "Option::is_none")]
+    kid: Option<String>,
+
+    #[serde(rename = "use", skip_serializing_if = "Option::is_none")]
+    r#use: Option<String>,
+
+    #[serde(rename = "alg", skip_serializing_if = "Option::is_none")]
+    alg: Option<String>,
+}
+
+// A JWK Set, e.g. for verifiers of JWS to look up keys by `kid`.
+// https://www.rfc-editor.org/rfc/rfc7517#section-5
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Jwks {
+    pub keys: Vec<Jwk>,
 }
 
 #[derive(Error, Debug)]
@@ -33,6 +53,18 @@ pub enum JwkToK256Error {
     Crypt(#[from] k256::elliptic_curve::Error),
 }
 
+#[derive(Error, Debug)]
+pub enum JwkToP256Error {
+    #[error("missing y")]
+    MissingY,
+    #[error("decode error")]
+    Decode(DecodePartial),
+    #[error("different crv")]
+    DifferentCrv,
+    #[error("crypt error: {0}")]
+    Crypt(#[from] p256::elliptic_curve::Error),
+}
+
 #[derive(Error, Debug)]
 pub enum JwkToX25519Error {
     #[error("decode error: {0:?}")]
@@ -41,22 +73,132 @@ pub enum JwkToX25519Error {
     DifferentCrv,
 }
 
+#[derive(Error, Debug)]
+pub enum JwkToEd25519Error {
+    #[error("decode error: {0:?}")]
+    Decode(Option<DecodeError>),
+    #[error("different crv")]
+    DifferentCrv,
+    #[error("crypt error: {0}")]
+    Crypt(#[from] ed25519_dalek::SignatureError),
+}
+
+#[derive(Error, Debug)]
+pub enum JwkThumbprintError {
+    #[error("missing y")]
+    MissingY,
+    #[error("unsupported kty: {0}")]
+    UnsupportedKty(String),
+}
+
 #[derive(Error, Debug)]
 pub enum K256ToJwkError {
     #[error("points are invalid")]
     PointsInvalid,
 }
 
-fn decode_base64url(
-    s: &str,
-) -> Result<k256::elliptic_curve::FieldBytes<k256::Secp256k1>, JwkToK256Error> {
-    let mut result = k256::elliptic_curve::FieldBytes::<k256::Secp256k1>::default();
-    BASE64URL_NOPAD
-        .decode_mut(s.as_bytes(), &mut result)
-        .map_err(JwkToK256Error::Decode)?;
+#[derive(Error, Debug)]
+pub enum P256ToJwkError {
+    #[error("points are invalid")]
+    PointsInvalid,
+}
+
+#[derive(Error, Debug)]
+pub enum SignKeyToJwkError {
+    #[error(transparent)]
+    K256(#[from] K256ToJwkError),
+    #[error(transparent)]
+    P256(#[from] P256ToJwkError),
+}
+
+impl Jwk {
+    fn new(kty: &str, crv: &str, x: String, y: Option<String>) -> Self {
+        Jwk {
+            kty: kty.to_string(),
+            crv: crv.to_string(),
+            x,
+            y,
+            kid: None,
+            r#use: None,
+            alg: None,
+        }
+    }
+
+    pub fn crv(&self) -> &str {
+        &self.crv
+    }
+
+    pub fn kid(&self) -> Option<&str> {
+        self.kid.as_deref()
+    }
+
+    pub fn key_use(&self) -> Option<&str> {
+        self.r#use.as_deref()
+    }
+
+    pub fn alg(&self) -> Option<&str> {
+        self.alg.as_deref()
+    }
+
+    pub fn with_kid(self, kid: impl Into<String>) -> Self {
+        Jwk {
+            kid: Some(kid.into()),
+            ..self
+        }
+    }
+
+    // `sig` or `enc`.
+    pub fn with_use(self, r#use: impl Into<String>) -> Self {
+        Jwk {
+            r#use: Some(r#use.into()),
+            ..self
+        }
+    }
+
+    pub fn with_alg(self, alg: impl Into<String>) -> Self {
+        Jwk {
+            alg: Some(alg.into()),
+            ..self
+        }
+    }
+
+    // The SHA-256 thumbprint of the required members, which does not depend on kid, use or alg.
+    // https://www.rfc-editor.org/rfc/rfc7638
+    pub fn thumbprint(&self) -> Result<String, JwkThumbprintError> {
+        // NOTE: Members in lexicographic order without whitespace, strings escaped as JSON.
+        let json = |v: &str| Value::from(v).to_string();
+        let members = match (self.kty.as_str(), &self.y) {
+            ("EC", Some(y)) => format!(
+                r#"{{"crv":{},"kty":"EC","x":{},"y":{}}}"#,
+                json(&self.crv),
+                json(&self.x),
+                json(y)
+            ),
+            ("EC", None) => return Err(JwkThumbprintError::MissingY),
+            ("OKP", _) => format!(
+                r#"{{"crv":{},"kty":"OKP","x":{}}}"#,
+                json(&self.crv),
+                json(&self.x)
+            ),
+            (kty, _) => return Err(JwkThumbprintError::UnsupportedKty(kty.to_string())),
+        };
+        Ok(BASE64URL_NOPAD.encode(&Sha256::digest(members.as_bytes())))
+    }
+}
+
+// NOTE: secp256k1 and P-256 share the same 32 bytes field size.
+fn decode_base64url(s: &str) -> Result<k256::FieldBytes, DecodePartial> {
+    let mut result = k256::FieldBytes::default();
+    BASE64URL_NOPAD.decode_mut(s.as_bytes(), &mut result)?;
     Ok(result)
 }
 
+// A SEC1 compressed point in `x` without `y`, which some DID documents use.
+fn decode_compressed(s: &str) -> Option<Vec<u8>> {
+    let point = BASE64URL_NOPAD.decode(s.as_bytes()).ok()?;
+    (point.len() == 33).then_some(point)
+}
+
 impl TryFrom<Jwk> for k256::PublicKey {
     type Error = JwkToK256Error;
     fn try_from(value: Jwk) -> Result<Self, Self::Error> {
@@ -64,13 +206,14 @@ impl TryFrom<Jwk> for k256::PublicKey {
             return Err(JwkToK256Error::DifferentCrv);
         }
         if let Some(y) = value.y {
-            let x = decode_base64url(&value.x)?;
-            let y = decode_base64url(&y)?;
+            let x = decode_base64url(&value.x).map_err(JwkToK256Error::Decode)?;
+            let y = decode_base64url(&y).map_err(JwkToK256Error::Decode)?;
             let pk = k256::EncodedPoint::from_affine_coordinates(&x, &y, false);
             let pk = k256::PublicKey::from_sec1_bytes(pk.as_bytes())?;
             Ok(pk)
         } else {
-            Err(JwkToK256Error::MissingY)
+            let point = decode_compressed(&value.x).ok_or(JwkToK256Error::MissingY)?;
+            Ok(k256::PublicKey::from_sec1_bytes(&point)?)
         }
     }
 }
@@ -79,19 +222,62 @@ impl TryFrom<k256::PublicKey> for Jwk {
     type Error = K256ToJwkError;
     fn try_from(value: k256::PublicKey) -> Result<Self, Self::Error> {
         let value = value.to_encoded_point(false);
-        let kty = "EC".to_string();
-        let crv = "secp256k1".to_string();
         match value.coordinates() {
             k256::elliptic_curve::sec1::Coordinates::Uncompressed { x, y } => {
                 let x = BASE64URL_NOPAD.encode(x);
                 let y = Some(BASE64URL_NOPAD.encode(y));
-                Ok(Jwk { kty, crv, x, y })
+                Ok(Jwk::new("EC", "secp256k1", x, y))
             }
             _ => Err(K256ToJwkError::PointsInvalid),
         }
     }
 }
 
+impl TryFrom<Jwk> for p256::PublicKey {
+    type Error = JwkToP256Error;
+    fn try_from(value: Jwk) -> Result<Self, Self::Error> {
+        if value.crv != "P-256" {
+            return Err(JwkToP256Error::DifferentCrv);
+        }
+        if let Some(y) = value.y {
+            let x = decode_base64url(&value.x).map_err(JwkToP256Error::Decode)?;
+            let y = decode_base64url(&y).map_err(JwkToP256Error::Decode)?;
+            let pk = p256::EncodedPoint::from_affine_coordinates(&x, &y, false);
+            let pk = p256::PublicKey::from_sec1_bytes(pk.as_bytes())?;
+            Ok(pk)
+        } else {
+            let point = decode_compressed(&value.x).ok_or(JwkToP256Error::MissingY)?;
+            Ok(p256::PublicKey::from_sec1_bytes(&point)?)
+        }
+    }
+}
+
+impl TryFrom<p256::PublicKey> for Jwk {
+    type Error = P256ToJwkError;
+    fn try_from(value: p256::PublicKey) -> Result<Self, Self::Error> {
+        let value = value.to_encoded_point(false);
+        match value.coordinates() {
+            p256::elliptic_curve::sec1::Coordinates::Uncompressed { x, y } => {
+                let x = BASE64URL_NOPAD.encode(x);
+                let y = Some(BASE64URL_NOPAD.encode(y));
+                Ok(Jwk::new("EC", "P-256", x, y))
+            }
+            _ => Err(P256ToJwkError::PointsInvalid),
+        }
+    }
+}
+
+impl TryFrom<SignPublicKey> for Jwk {
+    type Error = SignKeyToJwkError;
+    fn try_from(value: SignPublicKey) -> Result<Self, Self::Error> {
+        match value {
+            SignPublicKey::Secp256k1(pk) => Ok(pk.try_into()?),
+            SignPublicKey::Secp256r1(pk) => Ok(pk.try_into()?),
+            SignPublicKey::Ed25519(pk) => Ok(pk.into()),
+        }
+    }
+}
+
 impl TryFrom<Jwk> for x25519_dalek::PublicKey {
     type Error = JwkToX25519Error;
     fn try_from(value: Jwk) -> Result<Self, Self::Error> {
@@ -109,14 +295,28 @@ impl TryFrom<Jwk> for x25519_dalek::PublicKey {
 impl From<x25519_dalek::PublicKey> for Jwk {
     fn from(value: x25519_dalek::PublicKey) -> Self {
         let x = BASE64URL_NOPAD.encode(value.as_bytes());
-        let kty = "OKP".to_string();
-        let crv = "X25519".to_string();
-        Jwk {
-            kty,
-            crv,
-            x,
-            y: None,
+        Jwk::new("OKP", "X25519", x, None)
+    }
+}
+
+impl TryFrom<Jwk> for ed25519_dalek::VerifyingKey {
+    type Error = JwkToEd25519Error;
+    fn try_from(value: Jwk) -> Result<Self, Self::Error> {
+        if value.crv != "Ed25519" {
+            return Err(JwkToEd25519Error::DifferentCrv);
         }
+        let pk = BASE64URL_NOPAD
+            .decode(value.x.as_bytes())
+            .map_err(|e| JwkToEd25519Error::Decode(Some(e)))?;
+        let pk: [u8; 32] = pk.try_into().map_err(|_| JwkToEd25519Error::Decode(None))?;
+        Ok(ed25519_dalek::VerifyingKey::from_bytes(&pk)?)
+    }
+}
+
+impl From<ed25519_dalek::VerifyingKey> for Jwk {
+    fn from(value: ed25519_dalek::VerifyingKey) -> Self {
+        let x = BASE64URL_NOPAD.encode(value.as_bytes());
+        Jwk::new("OKP", "Ed25519", x, None)
     }
 }
 
@@ -143,4 +343,83 @@ pub mod tests {
         let _pk: k256::PublicKey = jwk.try_into().unwrap();
         assert_eq!(pk, _pk);
     }
+
+    #[test]
+    pub fn k256_compressed() {
+        let pk = k256::SecretKey::random(&mut OsRng).public_key();
+        let x = BASE64URL_NOPAD.encode(pk.to_encoded_point(true).as_bytes());
+        let jwk = Jwk::new("EC", "secp256k1", x, None);
+        let _pk: k256::PublicKey = jwk.try_into().unwrap();
+        assert_eq!(pk, _pk);
+
+        let jwk: Jwk = pk.try_into().unwrap();
+        let jwk = Jwk { y: None, ..jwk };
+        let result: Result<k256::PublicKey, _> = jwk.try_into();
+        assert!(matches!(result, Err(JwkToK256Error::MissingY)));
+    }
+
+    #[test]
+    pub fn p256_enc_dec() {
+        let sk = p256::SecretKey::random(&mut OsRng);
+        let pk = sk.public_key();
+        let jwk: Jwk = pk.try_into().unwrap();
+        assert_eq!(jwk.crv(), "P-256");
+        let _pk: p256::PublicKey = jwk.clone().try_into().unwrap();
+        assert_eq!(pk, _pk);
+        let result: Result<k256::PublicKey, _> = jwk.try_into();
+        assert!(matches!(result, Err(JwkToK256Error::DifferentCrv)));
+    }
+
+    #[test]
+    pub fn ed25519_enc_dec() {
+        let sk = ed25519_dalek::SigningKey::generate(&mut OsRng);
+        let pk = sk.verifying_key();
+        let jwk: Jwk = pk.into();
+        assert_eq!(jwk.kty, "OKP");
+        let _pk: ed25519_dalek::VerifyingKey = jwk.try_into().unwrap();
+        assert_eq!(pk, _pk);
+    }
+
+    #[test]
+    pub fn thumbprint() {
+        // https://www.rfc-editor.org/rfc/rfc8037#appendix-A.3
+        let jwk: Jwk = serde_json::from_str(
+            r#"{"kty":"OKP","crv":"Ed25519","x":"11qYAYKxCrfVS_7TyWQHOg7hcvPapiMlrwIaaPcHURo"}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            jwk.thumbprint().unwrap(),
+            "kPrK_qmxVWaYVA9wwBF6Iuo3vVzz7TxHCTwXBygrS4k"
+        );
+
+        let pk = k256::SecretKey::random(&mut OsRng).public_key();
+        let jwk: Jwk = pk.try_into().unwrap();
+        let thumbprint = jwk.thumbprint().unwrap();
+        let jwk = jwk.with_kid(&thumbprint).with_use("sig").with_alg("ES256K");
+        assert_eq!(jwk.thumbprint().unwrap(), thumbprint);
+
+        let json = serde_json::to_value(&jwk).unwrap();
+        assert_eq!(json["kid"], thumbprint);
+        assert_eq!(json["use"], "sig");
+        assert_eq!(json["alg"], "ES256K");
+        let _pk: k256::PublicKey = serde_json::from_value::<Jwk>(json)
+            .unwrap()
+            .try_into()
+            .unwrap();
+        assert_eq!(pk, _pk);
+
+        let jwk = Jwk { y: None, ..jwk };
+        assert!(matches!(
+            jwk.thumbprint(),
+            Err(JwkThumbprintError::MissingY)
+        ));
+    }
+
+    #[test]
+    pub fn ed25519_different_crv() {
+        let sk = x25519_dalek::StaticSecret::random_from_rng(OsRng);
+        let jwk: Jwk = x25519_dalek::PublicKey::from(&sk).into();
+        let result: Result<ed25519_dalek::VerifyingKey, _> = jwk.try_into();
+        assert!(matches!(result, Err(JwkToEd25519Error::DifferentCrv)));
+    }
 }
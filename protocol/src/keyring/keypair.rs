@@ -78,6 +78,152 @@ impl KeyPair<k256::SecretKey, k256::PublicKey> for K256KeyPair {
     }
 }
 
+#[derive(Clone)]
+pub struct P256KeyPair {
+    secret_key: p256::SecretKey,
+    public_key: p256::PublicKey,
+}
+
+impl P256KeyPair {
+    pub fn new(secret_key: p256::SecretKey) -> Self {
+        let public_key = secret_key.public_key();
+        P256KeyPair {
+            public_key,
+            secret_key,
+        }
+    }
+}
+
+impl KeyPair<p256::SecretKey, p256::PublicKey> for P256KeyPair {
+    type Error = KeyPairingError;
+    fn get_secret_key(&self) -> p256::SecretKey {
+        self.secret_key.clone()
+    }
+    fn get_public_key(&self) -> p256::PublicKey {
+        self.public_key
+    }
+    fn to_hex_key_pair(&self) -> KeyPairHex {
+        let sk = self.secret_key.to_bytes();
+        let secret_key = hex::encode(sk);
+        let pk = self.public_key.to_encoded_point(false);
+        let public_key = hex::encode(pk.as_bytes());
+        KeyPairHex {
+            secret_key,
+            public_key,
+        }
+    }
+    fn from_hex_key_pair(kp: &KeyPairHex) -> Result<Self, KeyPairingError> {
+        let secret_key = hex::decode(&kp.secret_key)?;
+        let secret_key = p256::SecretKey::from_slice(&secret_key)
+            .map_err(|e| KeyPairingError::Crypt(e.to_string()))?;
+        let public_key = hex::decode(&kp.public_key)?;
+        let public_key = p256::PublicKey::from_sec1_bytes(&public_key)
+            .map_err(|e| KeyPairingError::Crypt(e.to_string()))?;
+        Ok(P256KeyPair {
+            public_key,
+            secret_key,
+        })
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SignKeyType {
+    #[default]
+    Secp256k1,
+    Secp256r1,
+}
+
+#[derive(Clone)]
+pub enum SignSecretKey {
+    Secp256k1(k256::SecretKey),
+    Secp256r1(p256::SecretKey),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SignPublicKey {
+    Secp256k1(k256::PublicKey),
+    Secp256r1(p256::PublicKey),
+    // NOTE: Only for verifying peers, e.g. did:key and did:webvh. Our own sign key is never Ed25519.
+    Ed25519(ed25519_dalek::VerifyingKey),
+}
+
+#[derive(Clone)]
+pub enum SignKeyPair {
+    Secp256k1(K256KeyPair),
+    Secp256r1(P256KeyPair),
+}
+
+impl SignKeyPair {
+    pub fn new<T: RngCore + CryptoRng>(key_type: SignKeyType, csprng: &mut T) -> Self {
+        match key_type {
+            SignKeyType::Secp256k1 => {
+                SignKeyPair::Secp256k1(K256KeyPair::new(k256::SecretKey::random(csprng)))
+            }
+            SignKeyType::Secp256r1 => {
+                SignKeyPair::Secp256r1(P256KeyPair::new(p256::SecretKey::random(csprng)))
+            }
+        }
+    }
+
+    pub fn key_type(&self) -> SignKeyType {
+        match self {
+            SignKeyPair::Secp256k1(_) => SignKeyType::Secp256k1,
+            SignKeyPair::Secp256r1(_) => SignKeyType::Secp256r1,
+        }
+    }
+}
+
+impl From<K256KeyPair> for SignKeyPair {
+    fn from(value: K256KeyPair) -> Self {
+        SignKeyPair::Secp256k1(value)
+    }
+}
+
+impl From<P256KeyPair> for SignKeyPair {
+    fn from(value: P256KeyPair) -> Self {
+        SignKeyPair::Secp256r1(value)
+    }
+}
+
+impl KeyPair<SignSecretKey, SignPublicKey> for SignKeyPair {
+    type Error = KeyPairingError;
+    fn get_secret_key(&self) -> SignSecretKey {
+        match self {
+            SignKeyPair::Secp256k1(k) => SignSecretKey::Secp256k1(k.get_secret_key()),
+            SignKeyPair::Secp256r1(k) => SignSecretKey::Secp256r1(k.get_secret_key()),
+        }
+    }
+    fn get_public_key(&self) -> SignPublicKey {
+        match self {
+            SignKeyPair::Secp256k1(k) => SignPublicKey::Secp256k1(k.get_public_key()),
+            SignKeyPair::Secp256r1(k) => SignPublicKey::Secp256r1(k.get_public_key()),
+        }
+    }
+    fn to_hex_key_pair(&self) -> KeyPairHex {
+        match self {
+            SignKeyPair::Secp256k1(k) => k.to_hex_key_pair(),
+            SignKeyPair::Secp256r1(k) => k.to_hex_key_pair(),
+        }
+    }
+    fn from_hex_key_pair(kp: &KeyPairHex) -> Result<Self, KeyPairingError> {
+        // NOTE: KeyPairHex does not record the curve, so pick the one whose
+        //       secret key derives the stored public key.
+        if let Ok(k) = K256KeyPair::from_hex_key_pair(kp) {
+            if k.secret_key.public_key() == k.public_key {
+                return Ok(SignKeyPair::Secp256k1(k));
+            }
+        }
+        let k = P256KeyPair::from_hex_key_pair(kp)?;
+        if k.secret_key.public_key() != k.public_key {
+            return Err(KeyPairingError::Crypt(
+                "public key does not match secret key".to_string(),
+            ));
+        }
+        Ok(SignKeyPair::Secp256r1(k))
+    }
+}
+
 #[derive(Clone)]
 pub struct X25519KeyPair {
     secret_key: x25519_dalek::StaticSecret,
@@ -130,9 +276,67 @@ impl KeyPair<x25519_dalek::StaticSecret, x25519_dalek::PublicKey> for X25519KeyP
     }
 }
 
+#[derive(Clone)]
+pub struct Ed25519KeyPair {
+    secret_key: ed25519_dalek::SigningKey,
+    public_key: ed25519_dalek::VerifyingKey,
+}
+
+impl Ed25519KeyPair {
+    pub fn new(secret_key: ed25519_dalek::SigningKey) -> Self {
+        let public_key = secret_key.verifying_key();
+        Ed25519KeyPair {
+            public_key,
+            secret_key,
+        }
+    }
+}
+
+impl KeyPair<ed25519_dalek::SigningKey, ed25519_dalek::VerifyingKey> for Ed25519KeyPair {
+    type Error = KeyPairingError;
+    fn get_secret_key(&self) -> ed25519_dalek::SigningKey {
+        self.secret_key.clone()
+    }
+    fn get_public_key(&self) -> ed25519_dalek::VerifyingKey {
+        self.public_key
+    }
+    fn to_hex_key_pair(&self) -> KeyPairHex {
+        let sk = self.secret_key.to_bytes();
+        let secret_key = hex::encode(sk);
+        let pk = self.public_key.as_bytes();
+        let public_key = hex::encode(pk);
+        KeyPairHex {
+            secret_key,
+            public_key,
+        }
+    }
+    fn from_hex_key_pair(kp: &KeyPairHex) -> Result<Self, KeyPairingError> {
+        let secret_key = hex::decode(&kp.secret_key)?;
+        let secret_key: [u8; 32] = secret_key.try_into().map_err(|e: Vec<u8>| {
+            KeyPairingError::Crypt(format!("array length mismatch: {}", e.len()))
+        })?;
+        let secret_key = ed25519_dalek::SigningKey::from_bytes(&secret_key);
+        let public_key = hex::decode(&kp.public_key)?;
+        let public_key: [u8; 32] = public_key.try_into().map_err(|e: Vec<u8>| {
+            KeyPairingError::Crypt(format!("array length mismatch: {}", e.len()))
+        })?;
+        let public_key = ed25519_dalek::VerifyingKey::from_bytes(&public_key)
+            .map_err(|e| KeyPairingError::Crypt(e.to_string()))?;
+        if secret_key.verifying_key() != public_key {
+            return Err(KeyPairingError::Crypt(
+                "public key does not match secret key".to_string(),
+            ));
+        }
+        Ok(Ed25519KeyPair {
+            public_key,
+            secret_key,
+        })
+    }
+}
+
 #[derive(Clone)]
 pub struct KeyPairing {
-    pub sign: K256KeyPair,
+    pub sign: SignKeyPair,
     pub update: K256KeyPair,
     pub recovery: K256KeyPair,
     pub encrypt: X25519KeyPair,
@@ -147,8 +351,15 @@ pub struct KeyPairingHex {
 }
 
 impl KeyPairing {
-    pub fn create_keyring<T: RngCore + CryptoRng>(mut csprng: T) -> Self {
-        let sign = K256KeyPair::new(k256::SecretKey::random(&mut csprng));
+    pub fn create_keyring<T: RngCore + CryptoRng>(csprng: T) -> Self {
+        Self::create_keyring_with_sign_key_type(csprng, SignKeyType::default())
+    }
+
+    pub fn create_keyring_with_sign_key_type<T: RngCore + CryptoRng>(
+        mut csprng: T,
+        sign_key_type: SignKeyType,
+    ) -> Self {
+        let sign = SignKeyPair::new(sign_key_type, &mut csprng);
         let update = K256KeyPair::new(k256::SecretKey::random(&mut csprng));
         let recovery = K256KeyPair::new(k256::SecretKey::random(&mut csprng));
         let encrypt = X25519KeyPair::new(x25519_dalek::StaticSecret::random_from_rng(&mut csprng));
@@ -176,7 +387,7 @@ impl TryFrom<&KeyPairingHex> for KeyPairing {
     type Error = KeyPairingError;
 
     fn try_from(hex: &KeyPairingHex) -> Result<Self, Self::Error> {
-        let sign = K256KeyPair::from_hex_key_pair(&hex.sign)?;
+        let sign = SignKeyPair::from_hex_key_pair(&hex.sign)?;
         let update = K256KeyPair::from_hex_key_pair(&hex.update)?;
         let recovery = K256KeyPair::from_hex_key_pair(&hex.recovery)?;
         let encrypt = X25519KeyPair::from_hex_key_pair(&hex.encrypt)?;
@@ -200,9 +411,54 @@ pub mod tests {
     pub fn test_create_keyring() {
         let keyring = KeyPairing::create_keyring(OsRng);
 
-        assert_eq!(keyring.sign.get_secret_key().to_bytes().len(), 32);
+        assert_eq!(keyring.sign.key_type(), SignKeyType::Secp256k1);
         assert_eq!(keyring.update.get_secret_key().to_bytes().len(), 32);
         assert_eq!(keyring.recovery.get_secret_key().to_bytes().len(), 32);
         assert_eq!(keyring.encrypt.get_secret_key().as_bytes().len(), 32);
     }
+
+    #[test]
+    pub fn test_create_keyring_with_p256() {
+        let keyring = KeyPairing::create_keyring_with_sign_key_type(OsRng, SignKeyType::Secp256r1);
+        assert_eq!(keyring.sign.key_type(), SignKeyType::Secp256r1);
+
+        let hex = KeyPairingHex::from(&keyring);
+        let decoded = KeyPairing::try_from(&hex).unwrap();
+        assert_eq!(decoded.sign.key_type(), SignKeyType::Secp256r1);
+        assert_eq!(decoded.sign.get_public_key(), keyring.sign.get_public_key());
+    }
+
+    #[test]
+    pub fn test_sign_key_pair_detects_k256() {
+        let keypair = K256KeyPair::new(k256::SecretKey::random(&mut OsRng));
+        let decoded = SignKeyPair::from_hex_key_pair(&keypair.to_hex_key_pair()).unwrap();
+        assert_eq!(decoded.key_type(), SignKeyType::Secp256k1);
+        assert_eq!(
+            decoded.get_public_key(),
+            SignPublicKey::Secp256k1(keypair.get_public_key())
+        );
+    }
+
+    #[test]
+    pub fn test_ed25519_hex_key_pair() {
+        let keypair = Ed25519KeyPair::new(ed25519_dalek::SigningKey::generate(&mut OsRng));
+        let hex = keypair.to_hex_key_pair();
+        let decoded = Ed25519KeyPair::from_hex_key_pair(&hex).unwrap();
+
+        assert_eq!(decoded.get_public_key(), keypair.get_public_key());
+        assert_eq!(
+            decoded.get_secret_key().to_bytes(),
+            keypair.get_secret_key().to_bytes()
+        );
+
+        let other = Ed25519KeyPair::new(ed25519_dalek::SigningKey::generate(&mut OsRng));
+        let mismatched = KeyPairHex {
+            public_key: other.to_hex_key_pair().public_key.clone(),
+            secret_key: hex.secret_key.clone(),
+        };
+        assert!(matches!(
+            Ed25519KeyPair::from_hex_key_pair(&mismatched),
+            Err(KeyPairingError::Crypt(_))
+        ));
+    }
 }
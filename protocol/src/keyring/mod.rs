@@ -1,3 +1,5 @@
 pub mod extension;
 pub mod jwk;
 pub mod keypair;
+pub mod multihash;
+pub mod multikey;
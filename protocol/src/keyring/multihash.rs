@@ -0,0 +1,144 @@
+// Multihash with the hash algorithms we support, shared by Sidetree and did:webvh.
+// https://github.com/multiformats/multihash
+// https://github.com/multiformats/multicodec/blob/master/table.csv
+
+use blake2::digest::consts::U32;
+use serde::{Deserialize, Serialize};
+use sha2::Digest;
+use thiserror::Error;
+
+// NOTE: Every algorithm here has 32 bytes digests.
+const DIGEST_SIZE: usize = 32;
+
+#[derive(Debug, Error)]
+pub enum MultihashError {
+    #[error("invalid multihash: {0}")]
+    Invalid(#[from] multihash::Error),
+    #[error("unsupported hash algorithm: {0:#x}")]
+    UnsupportedAlgorithm(u64),
+    #[error("invalid digest size: {0}")]
+    DigestSize(u8),
+}
+
+// Serialized as the multicodec names, e.g. in DID method parameters.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum HashAlgorithm {
+    #[default]
+    #[serde(rename = "sha2-256")]
+    Sha256,
+    #[serde(rename = "sha3-256")]
+    Sha3_256,
+    #[serde(rename = "blake2b-256")]
+    Blake2b256,
+}
+
+impl HashAlgorithm {
+    pub const ALL: [HashAlgorithm; 3] = [
+        HashAlgorithm::Sha256,
+        HashAlgorithm::Sha3_256,
+        HashAlgorithm::Blake2b256,
+    ];
+
+    pub fn code(&self) -> u64 {
+        match self {
+            HashAlgorithm::Sha256 => 0x12,
+            HashAlgorithm::Sha3_256 => 0x16,
+            HashAlgorithm::Blake2b256 => 0xb220,
+        }
+    }
+
+    pub fn from_code(code: u64) -> Result<Self, MultihashError> {
+        Self::ALL
+            .into_iter()
+            .find(|v| v.code() == code)
+            .ok_or(MultihashError::UnsupportedAlgorithm(code))
+    }
+
+    pub fn digest(&self, data: &[u8]) -> Vec<u8> {
+        match self {
+            HashAlgorithm::Sha256 => sha2::Sha256::digest(data).to_vec(),
+            HashAlgorithm::Sha3_256 => sha3::Sha3_256::digest(data).to_vec(),
+            HashAlgorithm::Blake2b256 => blake2::Blake2b::<U32>::digest(data).to_vec(),
+        }
+    }
+
+    // <varint code><varint size><digest>
+    pub fn multihash(&self, data: &[u8]) -> Vec<u8> {
+        let mut bytes = vec![];
+        let mut code = self.code();
+        while code >= 0x80 {
+            bytes.push(code as u8 | 0x80);
+            code >>= 7;
+        }
+        bytes.push(code as u8);
+        bytes.push(DIGEST_SIZE as u8);
+        bytes.extend(self.digest(data));
+        bytes
+    }
+}
+
+// The algorithm and the digest of `bytes`. Truncated digests are rejected.
+pub fn decode(bytes: &[u8]) -> Result<(HashAlgorithm, Vec<u8>), MultihashError> {
+    let multihash = multihash::Multihash::<DIGEST_SIZE>::from_bytes(bytes)?;
+    let algorithm = HashAlgorithm::from_code(multihash.code())?;
+    if multihash.size() as usize != DIGEST_SIZE {
+        return Err(MultihashError::DigestSize(multihash.size()));
+    }
+    Ok((algorithm, multihash.digest().to_vec()))
+}
+
+// Whether `multihash` is the hash of `data` by the algorithm in itself.
+pub fn verify(multihash: &[u8], data: &[u8]) -> bool {
+    decode(multihash).is_ok_and(|(algorithm, _)| algorithm.multihash(data) == multihash)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_multihash() {
+        for (algorithm, prefix, digest) in [
+            (
+                HashAlgorithm::Sha256,
+                vec![0x12, 0x20],
+                "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824",
+            ),
+            (
+                HashAlgorithm::Sha3_256,
+                vec![0x16, 0x20],
+                "3338be694f50c5f338814986cdf0686453a888b84f424d792af4b9202398f392",
+            ),
+            (
+                HashAlgorithm::Blake2b256,
+                vec![0xa0, 0xe4, 0x02, 0x20],
+                "324dcf027dd4a30a932c441f365a25e86b173defa4b8e58948253471b81b72cf",
+            ),
+        ] {
+            let multihash = algorithm.multihash(b"hello");
+            assert_eq!(multihash[..prefix.len()], prefix);
+            assert_eq!(hex::encode(&multihash[prefix.len()..]), digest);
+            assert_eq!(decode(&multihash).unwrap().0, algorithm);
+            assert!(verify(&multihash, b"hello"));
+            assert!(!verify(&multihash, b"world"));
+        }
+    }
+
+    #[test]
+    fn test_decode_error() {
+        assert!(matches!(
+            decode(&[0x13, 0x01, 0x00]),
+            Err(MultihashError::UnsupportedAlgorithm(0x13))
+        ));
+        assert!(matches!(
+            decode(&[0x12, 0x01, 0x00]),
+            Err(MultihashError::DigestSize(1))
+        ));
+        let multihash = HashAlgorithm::Sha256.multihash(b"hello");
+        assert!(decode(&multihash[..multihash.len() - 1]).is_err());
+        assert_eq!(
+            serde_json::to_string(&HashAlgorithm::Blake2b256).unwrap(),
+            r#""blake2b-256""#
+        );
+    }
+}
@@ -0,0 +1,214 @@
+// Multikey encoding of public keys, i.e. base58btc multibase of the multicodec prefixed key.
+// https://www.w3.org/TR/controller-document/#multikey
+
+use k256::elliptic_curve::sec1::ToEncodedPoint;
+use thiserror::Error;
+
+use super::jwk::{Jwk, K256ToJwkError, P256ToJwkError};
+use super::keypair::SignPublicKey;
+
+// NOTE: multicodec codes, varint encoded.
+const ED25519_PUB: [u8; 2] = [0xed, 0x01];
+const SECP256K1_PUB: [u8; 2] = [0xe7, 0x01];
+const P256_PUB: [u8; 2] = [0x80, 0x24];
+const X25519_PUB: [u8; 2] = [0xec, 0x01];
+
+#[derive(Debug, Error)]
+pub enum MultikeyError {
+    #[error("invalid multibase: {0}")]
+    Multibase(String),
+    #[error("unsupported multicodec: {0}")]
+    UnsupportedCodec(String),
+    #[error("unsupported key type: {0}")]
+    UnsupportedKeyType(String),
+    #[error("invalid public key: {0}")]
+    PublicKey(String),
+}
+
+#[derive(Debug, Error)]
+pub enum MultikeyToJwkError {
+    #[error(transparent)]
+    K256(#[from] K256ToJwkError),
+    #[error(transparent)]
+    P256(#[from] P256ToJwkError),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Multikey {
+    Ed25519(ed25519_dalek::VerifyingKey),
+    Secp256k1(k256::PublicKey),
+    P256(p256::PublicKey),
+    X25519(x25519_dalek::PublicKey),
+}
+
+impl Multikey {
+    fn codec(&self) -> [u8; 2] {
+        match self {
+            Multikey::Ed25519(_) => ED25519_PUB,
+            Multikey::Secp256k1(_) => SECP256K1_PUB,
+            Multikey::P256(_) => P256_PUB,
+            Multikey::X25519(_) => X25519_PUB,
+        }
+    }
+
+    // NOTE: EC points are compressed.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        match self {
+            Multikey::Ed25519(v) => v.as_bytes().to_vec(),
+            Multikey::Secp256k1(v) => v.to_encoded_point(true).as_bytes().to_vec(),
+            Multikey::P256(v) => v.to_encoded_point(true).as_bytes().to_vec(),
+            Multikey::X25519(v) => v.as_bytes().to_vec(),
+        }
+    }
+
+    pub fn encode(&self) -> String {
+        let mut bytes = self.codec().to_vec();
+        bytes.extend(self.to_bytes());
+        format!("z{}", bs58::encode(bytes).into_string())
+    }
+
+    pub fn decode(multikey: &str) -> Result<Self, MultikeyError> {
+        let decoded = multikey
+            .strip_prefix('z')
+            .and_then(|v| bs58::decode(v).into_vec().ok())
+            .ok_or_else(|| MultikeyError::Multibase(multikey.to_string()))?;
+        if decoded.len() < 2 {
+            return Err(MultikeyError::UnsupportedCodec(multikey.to_string()));
+        }
+        let (codec, key) = decoded.split_at(2);
+        Self::from_bytes([codec[0], codec[1]], key, multikey)
+    }
+
+    // A raw public key of the verification method type, e.g. the one of publicKeyBase58.
+    pub fn from_key_type(r#type: &str, key: &[u8]) -> Result<Self, MultikeyError> {
+        let codec = match r#type {
+            "Ed25519VerificationKey2018" | "Ed25519VerificationKey2020" => ED25519_PUB,
+            "EcdsaSecp256k1VerificationKey2019" => SECP256K1_PUB,
+            "EcdsaSecp256r1VerificationKey2019" => P256_PUB,
+            "X25519KeyAgreementKey2019" | "X25519KeyAgreementKey2020" => X25519_PUB,
+            _ => return Err(MultikeyError::UnsupportedKeyType(r#type.to_string())),
+        };
+        Self::from_bytes(codec, key, r#type)
+    }
+
+    // NOTE: `source` is only for error messages.
+    fn from_bytes(codec: [u8; 2], key: &[u8], source: &str) -> Result<Self, MultikeyError> {
+        let invalid_key = || MultikeyError::PublicKey(source.to_string());
+        match codec {
+            ED25519_PUB => {
+                let key: [u8; 32] = key.try_into().map_err(|_| invalid_key())?;
+                let key =
+                    ed25519_dalek::VerifyingKey::from_bytes(&key).map_err(|_| invalid_key())?;
+                Ok(Multikey::Ed25519(key))
+            }
+            SECP256K1_PUB => k256::PublicKey::from_sec1_bytes(key)
+                .map(Multikey::Secp256k1)
+                .map_err(|_| invalid_key()),
+            P256_PUB => p256::PublicKey::from_sec1_bytes(key)
+                .map(Multikey::P256)
+                .map_err(|_| invalid_key()),
+            X25519_PUB => {
+                let key: [u8; 32] = key.try_into().map_err(|_| invalid_key())?;
+                Ok(Multikey::X25519(key.into()))
+            }
+            _ => Err(MultikeyError::UnsupportedCodec(source.to_string())),
+        }
+    }
+
+    // The X25519 key for key agreement, derived from an Ed25519 key.
+    pub fn to_x25519(&self) -> Option<x25519_dalek::PublicKey> {
+        match self {
+            Multikey::Ed25519(v) => Some(v.to_montgomery().to_bytes().into()),
+            Multikey::X25519(v) => Some(*v),
+            _ => None,
+        }
+    }
+
+    pub fn to_jwk(&self) -> Result<Jwk, MultikeyToJwkError> {
+        match self {
+            Multikey::Ed25519(v) => Ok((*v).into()),
+            Multikey::Secp256k1(v) => Ok((*v).try_into()?),
+            Multikey::P256(v) => Ok((*v).try_into()?),
+            Multikey::X25519(v) => Ok((*v).into()),
+        }
+    }
+}
+
+impl From<SignPublicKey> for Multikey {
+    fn from(value: SignPublicKey) -> Self {
+        match value {
+            SignPublicKey::Secp256k1(v) => Multikey::Secp256k1(v),
+            SignPublicKey::Secp256r1(v) => Multikey::P256(v),
+            SignPublicKey::Ed25519(v) => Multikey::Ed25519(v),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand_core::OsRng;
+
+    use super::*;
+    use crate::keyring::keypair::{KeyPair, KeyPairing};
+
+    #[test]
+    fn test_decode_multikey() {
+        for (multikey, crv) in [
+            (
+                "z6MkhaXgBZDvotDkL5257faiztiGiC2QtKLGpbnnEGta2doK",
+                "Ed25519",
+            ),
+            (
+                "zQ3shokFTS3brHcDQrn82RUDfCZESWL1ZdCEJwekUDPQiYBme",
+                "secp256k1",
+            ),
+            ("zDnaerDaTF5BXEavCrfRZEk316dpbLsfPDZ3WJ5hRTPFU2169", "P-256"),
+            ("z6LSj72tK8brWgZja8NLRwPigth2T9QRiG1uH9oKZuKjdh9p", "X25519"),
+        ] {
+            let key = Multikey::decode(multikey).unwrap();
+            assert_eq!(key.encode(), multikey);
+            assert_eq!(key.to_jwk().unwrap().crv(), crv);
+        }
+
+        assert!(matches!(
+            Multikey::decode("6MkhaXgBZDvotDkL5257faiztiGiC2QtKLGpbnnEGta2doK"),
+            Err(MultikeyError::Multibase(_))
+        ));
+        assert!(matches!(
+            Multikey::decode("zQmdEjpG2gwEWZAx8YjBrw7mF1iuCqgrMh8S63M7PaC1Ldr"),
+            Err(MultikeyError::UnsupportedCodec(_))
+        ));
+    }
+
+    #[test]
+    fn test_from_key_type() {
+        let key = Multikey::decode("z6MkhaXgBZDvotDkL5257faiztiGiC2QtKLGpbnnEGta2doK").unwrap();
+        assert_eq!(
+            Multikey::from_key_type("Ed25519VerificationKey2018", &key.to_bytes()).unwrap(),
+            key
+        );
+        assert!(matches!(
+            Multikey::from_key_type("X25519KeyAgreementKey2019", &key.to_bytes()[1..]),
+            Err(MultikeyError::PublicKey(_))
+        ));
+        assert!(matches!(
+            Multikey::from_key_type("JsonWebKey2020", &key.to_bytes()),
+            Err(MultikeyError::UnsupportedKeyType(_))
+        ));
+    }
+
+    #[test]
+    fn test_to_x25519() {
+        let key = Multikey::decode("z6MkhaXgBZDvotDkL5257faiztiGiC2QtKLGpbnnEGta2doK").unwrap();
+        let x25519 = Multikey::X25519(key.to_x25519().unwrap());
+        assert_eq!(
+            x25519.encode(),
+            "z6LSj72tK8brWgZja8NLRwPigth2T9QRiG1uH9oKZuKjdh9p"
+        );
+
+        let keyring = KeyPairing::create_keyring(OsRng);
+        let key = Multikey::from(keyring.sign.get_public_key());
+        assert!(key.to_x25519().is_none());
+        assert_eq!(Multikey::decode(&key.encode()).unwrap(), key);
+    }
+}
@@ -1,10 +1,15 @@
 pub mod did;
+pub mod did_key;
+pub mod did_peer;
+pub mod did_web;
 pub mod did_webvh;
 pub mod didcomm;
 pub mod keyring;
 pub mod verifiable_credentials;
+pub use ed25519_dalek;
 pub use http;
 pub use k256;
+pub use p256;
 pub use rand_core;
 pub use serde_jcs;
 pub use sha2;
@@ -3,20 +3,26 @@ use thiserror::Error;
 
 use super::types::Proof;
 use crate::{
-    keyring::keypair::{K256KeyPair, KeyPair},
-    verifiable_credentials::{jws, types::VerifiableCredentials},
+    keyring::keypair::{Ed25519KeyPair, KeyPair, SignKeyPair, SignSecretKey},
+    verifiable_credentials::{
+        eddsa_jcs_2022::{self, EddsaJcs2022Error},
+        jws::{self, JwsVerifyingKey},
+        types::VerifiableCredentials,
+    },
 };
 
 pub struct CredentialSignerSuite<'a> {
     pub did: &'a str,
     pub key_id: &'a str,
-    pub context: &'a K256KeyPair,
+    pub context: &'a SignKeyPair,
 }
 
 #[derive(Debug, Error)]
 pub enum CredentialSignerSignError {
     #[error("jws error: {0:?}")]
     Jws(#[from] jws::JwsEncodeError),
+    #[error("data integrity proof error: {0:?}")]
+    DataIntegrity(#[from] EddsaJcs2022Error),
     #[error("json parse error: {0:?}")]
     Json(#[from] serde_json::Error),
 }
@@ -25,10 +31,14 @@ pub enum CredentialSignerSignError {
 pub enum CredentialSignerVerifyError {
     #[error("jws error: {0:?}")]
     Jws(#[from] jws::JwsDecodeError),
+    #[error("data integrity proof error: {0:?}")]
+    DataIntegrity(#[from] EddsaJcs2022Error),
     #[error("json parse error: {0:?}")]
     Json(#[from] serde_json::Error),
     #[error("proof not found")]
     ProofNotFound,
+    #[error("verification method {0} is not of the issuer")]
+    IssuerMismatch(String),
 }
 
 pub struct CredentialSigner {}
@@ -38,16 +48,23 @@ impl CredentialSigner {
         mut object: VerifiableCredentials,
         suite: CredentialSignerSuite,
     ) -> Result<VerifiableCredentials, CredentialSignerSignError> {
-        let jws = jws::sign(&json!(object), &suite.context.get_secret_key())?;
+        let secret_key = suite.context.get_secret_key();
+        let proof_type = match secret_key {
+            SignSecretKey::Secp256k1(_) => "EcdsaSecp256k1Signature2019",
+            SignSecretKey::Secp256r1(_) => "EcdsaSecp256r1Signature2019",
+        };
+        let jws = jws::sign(&json!(object), &secret_key)?;
         let did = suite.did;
         let key_id = suite.key_id;
         object.proof = Some(Proof {
-            r#type: "EcdsaSecp256k1Signature2019".to_string(),
+            r#type: proof_type.to_string(),
             proof_purpose: "authentication".to_string(),
             // Assume that object.issuance_date is correct data
             created: object.issuance_date,
             verification_method: format!("{}#{}", did, key_id),
-            jws,
+            jws: Some(jws),
+            cryptosuite: None,
+            proof_value: None,
             domain: None,
             controller: None,
             challenge: None,
@@ -55,19 +72,72 @@ impl CredentialSigner {
         Ok(object)
     }
 
-    pub fn verify(
+    pub fn verify<K: JwsVerifyingKey + ?Sized>(
         mut object: VerifiableCredentials,
-        public_key: &k256::PublicKey,
+        public_key: &K,
     ) -> Result<VerifiableCredentials, CredentialSignerVerifyError> {
         let proof = object
             .proof
             .take()
             .ok_or(CredentialSignerVerifyError::ProofNotFound)?;
-        let jws = proof.jws;
+        let jws = proof
+            .jws
+            .ok_or(CredentialSignerVerifyError::ProofNotFound)?;
         let payload = serde_json::to_value(&object)?;
         jws::verify(&payload, &jws, public_key)?;
         Ok(object)
     }
+
+    // Signs with an eddsa-jcs-2022 DataIntegrityProof, referring to `key` as a did:key.
+    pub fn sign_eddsa_jcs_2022(
+        mut object: VerifiableCredentials,
+        key: &Ed25519KeyPair,
+    ) -> Result<VerifiableCredentials, CredentialSignerSignError> {
+        object.proof = None;
+        let mut proof = Proof {
+            r#type: eddsa_jcs_2022::PROOF_TYPE.to_string(),
+            proof_purpose: "assertionMethod".to_string(),
+            // Assume that object.issuance_date is correct data
+            created: object.issuance_date,
+            verification_method: eddsa_jcs_2022::did_key_verification_method(&key.get_public_key()),
+            jws: None,
+            cryptosuite: Some(eddsa_jcs_2022::CRYPTOSUITE.to_string()),
+            proof_value: None,
+            domain: None,
+            controller: None,
+            challenge: None,
+        };
+        proof.proof_value = Some(eddsa_jcs_2022::create_proof_value(
+            &object,
+            &proof,
+            &key.get_secret_key(),
+        )?);
+        object.proof = Some(proof);
+        Ok(object)
+    }
+
+    // The public key is resolved from the did:key verification method of the proof,
+    // which must be of the issuer.
+    pub fn verify_eddsa_jcs_2022(
+        mut object: VerifiableCredentials,
+    ) -> Result<VerifiableCredentials, CredentialSignerVerifyError> {
+        let proof = object
+            .proof
+            .take()
+            .ok_or(CredentialSignerVerifyError::ProofNotFound)?;
+        let proof_value = proof
+            .proof_value
+            .as_deref()
+            .ok_or(CredentialSignerVerifyError::ProofNotFound)?;
+        if proof.verification_method.split('#').next() != Some(object.issuer.id.as_str()) {
+            return Err(CredentialSignerVerifyError::IssuerMismatch(
+                proof.verification_method,
+            ));
+        }
+        let public_key = eddsa_jcs_2022::resolve_verification_method(&proof.verification_method)?;
+        eddsa_jcs_2022::verify_proof_value(&object, &proof, proof_value, &public_key)?;
+        Ok(object)
+    }
 }
 
 #[cfg(test)]
@@ -76,7 +146,10 @@ pub mod tests {
     use chrono::{DateTime, Utc};
 
     use super::*;
-    use crate::verifiable_credentials::types::{CredentialSubject, Issuer};
+    use crate::{
+        keyring::keypair::{K256KeyPair, P256KeyPair},
+        verifiable_credentials::types::{CredentialSubject, Issuer},
+    };
 
     const PRIVATE_KEY: [u8; 32] = [
         0xc7, 0x39, 0x80, 0x5a, 0xb0, 0x3d, 0xa6, 0x2d, 0xdb, 0xe0, 0x33, 0x90, 0xac, 0xdf, 0x76,
@@ -103,7 +176,7 @@ pub mod tests {
     #[test]
     pub fn test_sign() {
         let sk = k256::SecretKey::from_slice(&PRIVATE_KEY).unwrap();
-        let context = K256KeyPair::new(sk);
+        let context: SignKeyPair = K256KeyPair::new(sk).into();
 
         let model = VerifiableCredentials {
             id: None,
@@ -136,8 +209,8 @@ pub mod tests {
         match result.proof {
             Some(proof) => {
                 assert_eq!(
-                    proof.jws,
-                    "eyJhbGciOiJFUzI1NksiLCJiNjQiOmZhbHNlLCJjcml0IjpbImI2NCJdfQ..LK8OcOuMgWU4Y5Zpz9jeQ8b5UsgDmjKJTBpuxFepGlp-hGVHVgyZz8QkZseqQRdUXn6JouVYo1jFsQCq_7p7ig"
+                    proof.jws.unwrap(),
+                    "eyJhbGciOiJFUzI1NksiLCJiNjQiOmZhbHNlLCJjcml0IjpbImI2NCJdLCJraWQiOiJ6d21CTHA2Mm1xWDJYQ0pXTGdzVmtJcWZCVXR2YXUwWVViN1htZmdEckdFIn0..O0UYDX-ROKvp_DGeEJpv2waET3uXGjt0VWYG2i_tuuNmSTzh7ty_C354xFFTbZGRsWiFuP2d4kZzouVmYJ0lMA"
                 );
                 assert_eq!(proof.proof_purpose, "authentication");
                 assert_eq!(proof.r#type, "EcdsaSecp256k1Signature2019");
@@ -153,7 +226,81 @@ pub mod tests {
     #[test]
     pub fn test_verify() {
         let sk = k256::SecretKey::from_slice(&PRIVATE_KEY).unwrap();
-        let context = K256KeyPair::new(sk);
+        let context: SignKeyPair = K256KeyPair::new(sk).into();
+
+        let model = VerifiableCredentials {
+            id: None,
+            r#type: vec!["type".to_string()],
+            issuer: Issuer {
+                id: "issuer".to_string(),
+            },
+            context: vec!["context".to_string()],
+            issuance_date: Utc::now(),
+            credential_subject: CredentialSubject {
+                id: None,
+                container: json!(r#"{"k":"0123456789abcdef"}"#),
+            },
+            expiration_date: None,
+            proof: None,
+        };
+
+        let vc = CredentialSigner::sign(
+            model.clone(),
+            CredentialSignerSuite {
+                did: "did:nodex:test:000000000000000000000000000000",
+                key_id: "signingKey",
+                context: &context,
+            },
+        )
+        .unwrap();
+
+        let verified_model = CredentialSigner::verify(vc, &context.get_public_key()).unwrap();
+
+        assert_eq!(model, verified_model);
+    }
+
+    #[test]
+    pub fn test_sign_and_verify_eddsa_jcs_2022() {
+        let key = Ed25519KeyPair::new(ed25519_dalek::SigningKey::generate(&mut rand_core::OsRng));
+        let did = format!(
+            "did:key:{}",
+            eddsa_jcs_2022::encode_multikey(&key.get_public_key())
+        );
+
+        let model = VerifiableCredentials::new(did, json!({"k": "0123456789abcdef"}), Utc::now());
+
+        let vc = CredentialSigner::sign_eddsa_jcs_2022(model.clone(), &key).unwrap();
+        let proof = vc.proof.as_ref().unwrap();
+        assert_eq!(proof.r#type, "DataIntegrityProof");
+        assert_eq!(proof.cryptosuite.as_deref(), Some("eddsa-jcs-2022"));
+        assert!(proof.jws.is_none());
+
+        let json = serde_json::to_string(&vc).unwrap();
+        let vc: VerifiableCredentials = serde_json::from_str(&json).unwrap();
+        let verified_model = CredentialSigner::verify_eddsa_jcs_2022(vc.clone()).unwrap();
+        assert_eq!(model, verified_model);
+
+        let mut tampered = vc;
+        tampered.credential_subject.container = json!({"k": "tampered"});
+        assert!(CredentialSigner::verify_eddsa_jcs_2022(tampered).is_err());
+
+        // NOTE: A valid proof by another key must not pass as the issuer's.
+        let spoofed = VerifiableCredentials::new(
+            "did:nodex:test:000000000000000000000000000000".to_string(),
+            json!({"k": "0123456789abcdef"}),
+            Utc::now(),
+        );
+        let spoofed = CredentialSigner::sign_eddsa_jcs_2022(spoofed, &key).unwrap();
+        assert!(matches!(
+            CredentialSigner::verify_eddsa_jcs_2022(spoofed),
+            Err(CredentialSignerVerifyError::IssuerMismatch(_))
+        ));
+    }
+
+    #[test]
+    pub fn test_sign_and_verify_p256() {
+        let sk = p256::SecretKey::from_slice(&PRIVATE_KEY).unwrap();
+        let context: SignKeyPair = P256KeyPair::new(sk).into();
 
         let model = VerifiableCredentials {
             id: None,
@@ -180,6 +327,10 @@ pub mod tests {
             },
         )
         .unwrap();
+        assert_eq!(
+            vc.proof.as_ref().unwrap().r#type,
+            "EcdsaSecp256r1Signature2019"
+        );
 
         let verified_model = CredentialSigner::verify(vc, &context.get_public_key()).unwrap();
 
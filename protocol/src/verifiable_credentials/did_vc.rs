@@ -1,8 +1,10 @@
 use thiserror::Error;
 
 use crate::{
-    did::did_repository::{get_sign_key, DidRepository, GetPublicKeyError},
-    keyring::keypair,
+    did::did_repository::{
+        get_sign_key, get_sign_key_id, get_verification_key, DidResolver, GetPublicKeyError,
+    },
+    keyring::keypair::{self, KeyPair},
     verifiable_credentials::{
         credential_signer::{
             CredentialSigner, CredentialSignerSignError, CredentialSignerSuite,
@@ -16,7 +18,7 @@ use crate::{
 pub trait DidVcService: Sync {
     type GenerateError: std::error::Error + Send + Sync;
     type VerifyError: std::error::Error + Send + Sync;
-    fn generate(
+    async fn generate(
         &self,
         model: VerifiableCredentials,
         from_keyring: &keypair::KeyPairing,
@@ -27,35 +29,58 @@ pub trait DidVcService: Sync {
     ) -> Result<VerifiableCredentials, Self::VerifyError>;
 }
 
+#[derive(Debug, Error)]
+pub enum DidVcServiceGenerateError<FindIdentifierError: std::error::Error> {
+    #[error("sign key not found in the did document of the issuer: {0}")]
+    SignKeyNotFound(#[from] GetPublicKeyError),
+    #[error("failed to get did document: {0}")]
+    DidDocNotFound(String),
+    #[error("failed to find identifier: {0}")]
+    FindIdentifier(FindIdentifierError),
+    #[error("credential signer error")]
+    SignFailed(#[from] CredentialSignerSignError),
+}
+
 #[derive(Debug, Error)]
 pub enum DidVcServiceVerifyError<FindIdentifierError: std::error::Error> {
     #[error("did public key not found. did: {0}")]
     PublicKeyNotFound(#[from] GetPublicKeyError),
     #[error("failed to get did document: {0}")]
     DidDocNotFound(String),
+    #[error("did is deactivated: {0}")]
+    Deactivated(String),
     #[error("failed to find identifier: {0}")]
     FindIdentifier(FindIdentifierError),
     #[error("credential signer error")]
     VerifyFailed(#[from] CredentialSignerVerifyError),
 }
 
-impl<R: DidRepository> DidVcService for R {
-    type GenerateError = CredentialSignerSignError;
+impl<R: DidResolver> DidVcService for R {
+    type GenerateError = DidVcServiceGenerateError<R::FindIdentifierError>;
     type VerifyError = DidVcServiceVerifyError<R::FindIdentifierError>;
-    fn generate(
+    // NOTE: The proof refers to the verification method of the sign key
+    //       in the DID document of the issuer.
+    async fn generate(
         &self,
         model: VerifiableCredentials,
         from_keyring: &keypair::KeyPairing,
     ) -> Result<VerifiableCredentials, Self::GenerateError> {
         let did = &model.issuer.id.clone();
-        CredentialSigner::sign(
+        let did_document = self
+            .find_identifier(did)
+            .await
+            .map_err(Self::GenerateError::FindIdentifier)?
+            .ok_or(DidVcServiceGenerateError::DidDocNotFound(did.clone()))?
+            .did_document;
+        let key_id = get_sign_key_id(&did_document, &from_keyring.sign.get_public_key())?;
+        Ok(CredentialSigner::sign(
             model,
             CredentialSignerSuite {
                 did,
-                key_id: "signingKey",
+                key_id,
                 context: &from_keyring.sign,
             },
-        )
+        )?)
     }
 
     async fn verify(
@@ -66,12 +91,18 @@ impl<R: DidRepository> DidVcService for R {
             .find_identifier(&model.issuer.id)
             .await
             .map_err(Self::VerifyError::FindIdentifier)?;
-        let did_document = did_document
-            .ok_or(DidVcServiceVerifyError::DidDocNotFound(
-                model.issuer.id.clone(),
-            ))?
-            .did_document;
-        let public_key = get_sign_key(&did_document)?;
+        let did_document = did_document.ok_or(DidVcServiceVerifyError::DidDocNotFound(
+            model.issuer.id.clone(),
+        ))?;
+        if did_document.is_deactivated() {
+            return Err(DidVcServiceVerifyError::Deactivated(model.issuer.id));
+        }
+        let did_document = did_document.did_document;
+        // NOTE: Without proof, verification fails anyway.
+        let public_key = match &model.proof {
+            Some(proof) => get_verification_key(&did_document, &proof.verification_method)?,
+            None => get_sign_key(&did_document)?,
+        };
         Ok(CredentialSigner::verify(model, &public_key)?)
     }
 }
@@ -87,7 +118,10 @@ mod tests {
     use super::{DidVcService, DidVcServiceVerifyError, VerifiableCredentials};
     use crate::{
         did::{did_repository::mocks::MockDidRepository, test_utils::create_random_did},
-        keyring::keypair::KeyPairing,
+        did_key::did_repository::{did_key, DidKeyRepository, PublicKeyFormat},
+        did_peer::did_repository::{did_peer, DidPeerRepository},
+        keyring::{keypair::KeyPairing, multikey::Multikey},
+        verifiable_credentials::{jws, types::Proof},
     };
 
     #[tokio::test]
@@ -107,7 +141,7 @@ mod tests {
         let issuance_date = Utc::now();
 
         let model = VerifiableCredentials::new(from_did.clone(), message.clone(), issuance_date);
-        let res = service.generate(model, &from_keyring).unwrap();
+        let res = service.generate(model, &from_keyring).await.unwrap();
 
         let verified = service.verify(res).await.unwrap();
 
@@ -115,7 +149,98 @@ mod tests {
         assert_eq!(verified.credential_subject.container, message);
     }
 
-    mod generate_failed {}
+    #[tokio::test]
+    async fn test_generate_and_verify_did_peer() {
+        let from_keyring = KeyPairing::create_keyring(OsRng);
+        let from_did = did_peer(&from_keyring, &[]).unwrap();
+        let service = DidPeerRepository::new().public_key_format(PublicKeyFormat::JsonWebKey2020);
+
+        let message = json!({"test": "0123456789abcdef"});
+        let model = VerifiableCredentials::new(from_did.clone(), message.clone(), Utc::now());
+        let res = service.generate(model, &from_keyring).await.unwrap();
+        assert_eq!(
+            res.proof.as_ref().unwrap().verification_method,
+            format!("{}#key-1", from_did)
+        );
+
+        let verified = service.verify(res).await.unwrap();
+        assert_eq!(verified.credential_subject.container, message);
+    }
+
+    #[tokio::test]
+    async fn test_verify_did_key_ed25519() {
+        let secret_key = ed25519_dalek::SigningKey::generate(&mut OsRng);
+        let from_did = did_key(&Multikey::Ed25519(secret_key.verifying_key()));
+        let service = DidKeyRepository::new().public_key_format(PublicKeyFormat::JsonWebKey2020);
+
+        let message = json!({"test": "0123456789abcdef"});
+        let mut model = VerifiableCredentials::new(from_did.clone(), message.clone(), Utc::now());
+        let jws = jws::sign(&json!(model), &secret_key).unwrap();
+        model.proof = Some(Proof {
+            r#type: "JsonWebSignature2020".to_string(),
+            proof_purpose: "assertionMethod".to_string(),
+            created: model.issuance_date,
+            verification_method: format!("{}#{}", from_did, &from_did["did:key:".len()..]),
+            jws: Some(jws),
+            cryptosuite: None,
+            proof_value: None,
+            domain: None,
+            controller: None,
+            challenge: None,
+        });
+
+        let verified = service.verify(model).await.unwrap();
+        assert_eq!(verified.credential_subject.container, message);
+    }
+
+    mod generate_failed {
+        use super::*;
+        use crate::did::did_repository::GetPublicKeyError;
+        use crate::verifiable_credentials::did_vc::DidVcServiceGenerateError;
+
+        #[tokio::test]
+        async fn test_did_not_found() {
+            let from_did = create_random_did();
+            let service = MockDidRepository::from_single(BTreeMap::new());
+
+            let model = VerifiableCredentials::new(from_did.clone(), json!({}), Utc::now());
+            let res = service
+                .generate(model, &KeyPairing::create_keyring(OsRng))
+                .await
+                .unwrap_err();
+
+            if let DidVcServiceGenerateError::DidDocNotFound(did) = res {
+                assert_eq!(did, from_did);
+            } else {
+                panic!("unexpected error: {:?}", res);
+            }
+        }
+
+        #[tokio::test]
+        async fn test_sign_key_not_found() {
+            let from_did = create_random_did();
+            let service = MockDidRepository::from_single(BTreeMap::from_iter([(
+                from_did.clone(),
+                KeyPairing::create_keyring(OsRng),
+            )]));
+
+            // NOTE: The sign key is not the one in the DID document of the issuer.
+            let model = VerifiableCredentials::new(from_did.clone(), json!({}), Utc::now());
+            let res = service
+                .generate(model, &KeyPairing::create_keyring(OsRng))
+                .await
+                .unwrap_err();
+
+            if let DidVcServiceGenerateError::SignKeyNotFound(
+                GetPublicKeyError::PublicKeyNotFound(did),
+            ) = res
+            {
+                assert_eq!(did, from_did);
+            } else {
+                panic!("unexpected error: {:?}", res);
+            }
+        }
+    }
 
     mod verify_failed {
         use super::*;
@@ -123,16 +248,19 @@ mod tests {
             IllegalPublicKeyLengthDidRepository, NoPublicKeyDidRepository,
         };
 
-        fn create_did_vc(
+        async fn create_did_vc(
             from_did: &str,
             from_keyring: &KeyPairing,
             message: &Value,
             issuance_date: DateTime<Utc>,
         ) -> VerifiableCredentials {
-            let service = MockDidRepository::from_single(BTreeMap::new());
+            let service = MockDidRepository::from_single(BTreeMap::from_iter([(
+                from_did.to_string(),
+                from_keyring.clone(),
+            )]));
             let model =
                 VerifiableCredentials::new(from_did.to_string(), message.clone(), issuance_date);
-            service.generate(model, from_keyring).unwrap()
+            service.generate(model, from_keyring).await.unwrap()
         }
 
         #[tokio::test]
@@ -148,7 +276,8 @@ mod tests {
                 &KeyPairing::create_keyring(OsRng),
                 &json!({}),
                 Utc::now(),
-            );
+            )
+            .await;
 
             let res = service.verify(model).await.unwrap_err();
 
@@ -167,7 +296,8 @@ mod tests {
                 &KeyPairing::create_keyring(OsRng),
                 &json!({}),
                 Utc::now(),
-            );
+            )
+            .await;
 
             let mock_repository = NoPublicKeyDidRepository;
             let service = mock_repository;
@@ -189,7 +319,8 @@ mod tests {
                 &KeyPairing::create_keyring(OsRng),
                 &json!({}),
                 Utc::now(),
-            );
+            )
+            .await;
             // for failing credential signer
             model.proof = None;
 
@@ -207,6 +338,30 @@ mod tests {
             }
         }
 
+        #[tokio::test]
+        async fn test_verification_method_not_found() {
+            let from_did = create_random_did();
+            let from_keyring = KeyPairing::create_keyring(OsRng);
+
+            let mut model = create_did_vc(&from_did, &from_keyring, &json!({}), Utc::now()).await;
+            if let Some(proof) = model.proof.as_mut() {
+                proof.verification_method = format!("{}#signingKey", create_random_did());
+            }
+
+            let mock_repository = MockDidRepository::from_single(BTreeMap::from_iter([(
+                from_did.clone(),
+                from_keyring,
+            )]));
+            let service = mock_repository;
+
+            let res = service.verify(model).await.unwrap_err();
+
+            if let DidVcServiceVerifyError::PublicKeyNotFound(_) = res {
+            } else {
+                panic!("unexpected error: {:?}", res);
+            }
+        }
+
         #[tokio::test]
         async fn test_public_key_length_mismatch() {
             let from_did = create_random_did();
@@ -216,7 +371,8 @@ mod tests {
                 &KeyPairing::create_keyring(OsRng),
                 &json!({}),
                 Utc::now(),
-            );
+            )
+            .await;
 
             let mock_repository = IllegalPublicKeyLengthDidRepository;
             let service = mock_repository;
@@ -238,7 +394,8 @@ mod tests {
                 &KeyPairing::create_keyring(OsRng),
                 &json!({}),
                 Utc::now(),
-            );
+            )
+            .await;
 
             let mock_repository = MockDidRepository::from_single(BTreeMap::from_iter([(
                 from_did.clone(),
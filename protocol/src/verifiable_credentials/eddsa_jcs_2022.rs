@@ -0,0 +1,240 @@
+// eddsa-jcs-2022 Data Integrity cryptosuite.
+// https://www.w3.org/TR/vc-di-eddsa/#eddsa-jcs-2022
+
+use ed25519_dalek::{Signature, Signer, SigningKey, VerifyingKey};
+use serde::Serialize;
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use thiserror::Error;
+
+use crate::keyring::multikey::Multikey;
+
+pub const PROOF_TYPE: &str = "DataIntegrityProof";
+pub const CRYPTOSUITE: &str = "eddsa-jcs-2022";
+
+#[derive(Debug, Error)]
+pub enum EddsaJcs2022Error {
+    #[error("invalid verification method: {0}")]
+    InvalidVerificationMethod(String),
+    #[error("invalid multikey: {0}")]
+    InvalidMultikey(String),
+    #[error("invalid proof config: {0}")]
+    InvalidProofConfig(&'static str),
+    #[error("invalid proof value")]
+    InvalidProofValue,
+    #[error("failed to canonicalize: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("signature verification failed: {0}")]
+    Signature(#[from] ed25519_dalek::SignatureError),
+}
+
+pub fn encode_multikey(public_key: &VerifyingKey) -> String {
+    Multikey::Ed25519(*public_key).encode()
+}
+
+// NOTE: Only Ed25519 keys are valid for this cryptosuite.
+pub fn decode_multikey(multikey: &str) -> Result<VerifyingKey, EddsaJcs2022Error> {
+    match Multikey::decode(multikey) {
+        Ok(Multikey::Ed25519(key)) => Ok(key),
+        _ => Err(EddsaJcs2022Error::InvalidMultikey(multikey.to_string())),
+    }
+}
+
+// `did:key:<multikey>#<multikey>`
+pub fn did_key_verification_method(public_key: &VerifyingKey) -> String {
+    let multikey = encode_multikey(public_key);
+    format!("did:key:{}#{}", multikey, multikey)
+}
+
+// The multikey of `did:key:<multikey>#<multikey>`.
+pub fn verification_method_to_multikey(
+    verification_method: &str,
+) -> Result<&str, EddsaJcs2022Error> {
+    verification_method
+        .strip_prefix("did:key:")
+        .and_then(|v| v.split('#').next())
+        .filter(|v| !v.is_empty())
+        .ok_or_else(|| {
+            EddsaJcs2022Error::InvalidVerificationMethod(verification_method.to_string())
+        })
+}
+
+pub fn resolve_verification_method(
+    verification_method: &str,
+) -> Result<VerifyingKey, EddsaJcs2022Error> {
+    decode_multikey(verification_method_to_multikey(verification_method)?)
+}
+
+// The proof options, i.e. the proof without proofValue, with the @context of `document`.
+// NOTE: null members are dropped, since an absent option may be serialized as null.
+fn proof_config<P: Serialize>(document: &Value, proof: &P) -> Result<Value, EddsaJcs2022Error> {
+    let Value::Object(mut config) = serde_json::to_value(proof)? else {
+        return Err(EddsaJcs2022Error::InvalidProofConfig(
+            "proof is not an object",
+        ));
+    };
+    config.remove("proofValue");
+    config.retain(|_, v| !v.is_null());
+    if config.get("type").and_then(Value::as_str) != Some(PROOF_TYPE) {
+        return Err(EddsaJcs2022Error::InvalidProofConfig("type"));
+    }
+    if config.get("cryptosuite").and_then(Value::as_str) != Some(CRYPTOSUITE) {
+        return Err(EddsaJcs2022Error::InvalidProofConfig("cryptosuite"));
+    }
+    if let Some(context) = document.get("@context") {
+        config.insert("@context".to_string(), context.clone());
+    }
+    Ok(Value::Object(config))
+}
+
+fn hash_data<T: Serialize, P: Serialize>(
+    document: &T,
+    proof: &P,
+) -> Result<Vec<u8>, EddsaJcs2022Error> {
+    let document = serde_json::to_value(document)?;
+    let proof_config = serde_json_canonicalizer::to_string(&proof_config(&document, proof)?)?;
+    let document = serde_json_canonicalizer::to_string(&document)?;
+    let mut hash_data = Sha256::digest(proof_config.as_bytes()).to_vec();
+    hash_data.extend_from_slice(&Sha256::digest(document.as_bytes()));
+    Ok(hash_data)
+}
+
+// Returns the proofValue of `proof` over `document`, which must not contain the proof itself.
+pub fn create_proof_value<T: Serialize, P: Serialize>(
+    document: &T,
+    proof: &P,
+    secret_key: &SigningKey,
+) -> Result<String, EddsaJcs2022Error> {
+    let signature = secret_key.sign(&hash_data(document, proof)?);
+    Ok(format!(
+        "z{}",
+        bs58::encode(signature.to_bytes()).into_string()
+    ))
+}
+
+pub fn verify_proof_value<T: Serialize, P: Serialize>(
+    document: &T,
+    proof: &P,
+    proof_value: &str,
+    public_key: &VerifyingKey,
+) -> Result<(), EddsaJcs2022Error> {
+    let signature = proof_value
+        .strip_prefix('z')
+        .and_then(|v| bs58::decode(v).into_vec().ok())
+        .ok_or(EddsaJcs2022Error::InvalidProofValue)?;
+    let signature = Signature::from_slice(&signature)?;
+    Ok(public_key.verify_strict(&hash_data(document, proof)?, &signature)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use rand_core::OsRng;
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_decode_multikey() {
+        let multikey = "z6Mkkr7iopdwZUgE87YaypKXSuBTsT6C7TyaUABmnHMuqmTY";
+        let key = decode_multikey(multikey).unwrap();
+        assert_eq!(encode_multikey(&key), multikey);
+
+        assert!(decode_multikey("6Mkkr7iopdwZUgE87YaypKXSuBTsT6C7TyaUABmnHMuqmTY").is_err());
+        assert!(decode_multikey("zQmdEjpG2gwEWZAx8YjBrw7mF1iuCqgrMh8S63M7PaC1Ldr").is_err());
+
+        let x25519 = Multikey::X25519(x25519_dalek::PublicKey::from([9u8; 32])).encode();
+        assert!(matches!(
+            decode_multikey(&x25519),
+            Err(EddsaJcs2022Error::InvalidMultikey(_))
+        ));
+    }
+
+    #[test]
+    fn test_verification_method_to_multikey() {
+        assert_eq!(
+            verification_method_to_multikey("did:key:z6Mkkr7#z6Mkkr7").unwrap(),
+            "z6Mkkr7"
+        );
+        assert!(verification_method_to_multikey("did:web:example.com#key").is_err());
+    }
+
+    #[test]
+    fn test_create_and_verify_proof_value() {
+        let key = SigningKey::generate(&mut OsRng);
+        let document = json!({"id": "urn:example", "value": [1, 2, 3]});
+        let proof = json!({
+            "type": PROOF_TYPE,
+            "cryptosuite": CRYPTOSUITE,
+            "verificationMethod": did_key_verification_method(&key.verifying_key()),
+            "proofPurpose": "assertionMethod",
+            "challenge": null,
+        });
+        let proof_value = create_proof_value(&document, &proof, &key).unwrap();
+
+        let public_key =
+            resolve_verification_method(proof["verificationMethod"].as_str().unwrap()).unwrap();
+        // NOTE: Key order, null members and proofValue do not change the hash.
+        let reordered = json!({
+            "proofPurpose": "assertionMethod",
+            "verificationMethod": proof["verificationMethod"],
+            "cryptosuite": CRYPTOSUITE,
+            "type": PROOF_TYPE,
+            "proofValue": proof_value,
+        });
+        verify_proof_value(&document, &reordered, &proof_value, &public_key).unwrap();
+
+        let tampered = json!({"id": "urn:example", "value": [1, 2]});
+        assert!(verify_proof_value(&tampered, &proof, &proof_value, &public_key).is_err());
+        let other = SigningKey::generate(&mut OsRng).verifying_key();
+        assert!(verify_proof_value(&document, &proof, &proof_value, &other).is_err());
+
+        let mut wrong_suite = proof.clone();
+        wrong_suite["cryptosuite"] = json!("ecdsa-jcs-2019");
+        assert!(matches!(
+            create_proof_value(&document, &wrong_suite, &key),
+            Err(EddsaJcs2022Error::InvalidProofConfig("cryptosuite"))
+        ));
+    }
+
+    // https://www.w3.org/TR/vc-di-eddsa/#representation-eddsa-jcs-2022
+    #[test]
+    fn test_vector() {
+        let secret_key = bs58::decode("3u2en7t5LR2WtQH5PfFqMqwVHBeXouLzo6haApm8XHqvjxq")
+            .into_vec()
+            .unwrap();
+        let secret_key = SigningKey::from_bytes(secret_key[2..].try_into().unwrap());
+        assert_eq!(
+            encode_multikey(&secret_key.verifying_key()),
+            "z6MkrJVnaZkeFzdQyMZu1cgjg7k1pZZ6pvBQ7XJPt4swbTQ2"
+        );
+        let document = json!({
+            "@context": [
+                "https://www.w3.org/ns/credentials/v2",
+                "https://www.w3.org/ns/credentials/examples/v2"
+            ],
+            "id": "urn:uuid:58172aac-d8ba-11ed-83dd-0b3aef56cc33",
+            "type": ["VerifiableCredential", "AlumniCredential"],
+            "name": "Alumni Credential",
+            "description": "A minimum viable example of an Alumni Credential.",
+            "issuer": "https://vc.example/issuers/5678",
+            "validFrom": "2023-01-01T00:00:00Z",
+            "credentialSubject": {
+                "id": "did:example:abcdefgh",
+                "alumniOf": "The School of Examples"
+            }
+        });
+        let proof = json!({
+            "type": PROOF_TYPE,
+            "cryptosuite": CRYPTOSUITE,
+            "created": "2023-02-24T23:36:38Z",
+            "verificationMethod": did_key_verification_method(&secret_key.verifying_key()),
+            "proofPurpose": "assertionMethod",
+        });
+        let proof_value = create_proof_value(&document, &proof, &secret_key).unwrap();
+        assert_eq!(
+            proof_value,
+            "z2HnFSSPPBzR36zdDgK8PbEHeXbR56YF24jwMpt3R1eHXQzJDMWS93FCzpvJpwTWd3GAVFuUfjoJdcnTMuVor51aX"
+        );
+        verify_proof_value(&document, &proof, &proof_value, &secret_key.verifying_key()).unwrap();
+    }
+}
@@ -1,14 +1,16 @@
 use std::convert::TryInto;
 
 use data_encoding::BASE64URL_NOPAD;
-use k256::ecdsa::{
-    signature::{Signer, Verifier},
-    Signature, SigningKey, VerifyingKey,
-};
+use k256::ecdsa::signature::{Signer, Verifier};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use thiserror::Error;
 
+use crate::keyring::{
+    jwk::Jwk,
+    keypair::{SignPublicKey, SignSecretKey},
+};
+
 // TODO: Design the interface to have an implementation with accelerators.
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -16,6 +18,8 @@ struct JwsHeader {
     alg: String,
     b64: bool,
     crit: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    kid: Option<String>,
 }
 
 #[derive(Debug, Error)]
@@ -50,12 +54,161 @@ pub enum JwsDecodeError {
     FromUtf8Error(#[from] std::string::FromUtf8Error),
 }
 
-pub fn sign(object: &Value, secret_key: &k256::SecretKey) -> Result<String, JwsEncodeError> {
+// NOTE: Every algorithm supported here produces a 64 bytes raw signature (r || s for ECDSA).
+const SIGNATURE_LENGTH: usize = 64;
+
+pub trait JwsSigningKey {
+    fn alg(&self) -> &'static str;
+    // NOTE: RFC 7638 thumbprint of the public key, matching the kid published in the JWKS.
+    fn kid(&self) -> Option<String>;
+    fn sign_message(&self, message: &[u8]) -> Result<Vec<u8>, JwsEncodeError>;
+}
+
+fn thumbprint<K: TryInto<Jwk>>(public_key: K) -> Option<String> {
+    public_key.try_into().ok()?.thumbprint().ok()
+}
+
+// NOTE: r || s of an ECDSA signature, both 32 bytes.
+fn split_signature(signature: &[u8]) -> Result<([u8; 32], [u8; 32]), JwsDecodeError> {
+    let invalid = || JwsDecodeError::InvalidSignatureLength(signature.len());
+    if signature.len() != SIGNATURE_LENGTH {
+        return Err(invalid());
+    }
+    let (r, s) = signature.split_at(32);
+    Ok((
+        r.try_into().map_err(|_| invalid())?,
+        s.try_into().map_err(|_| invalid())?,
+    ))
+}
+
+pub trait JwsVerifyingKey {
+    fn alg(&self) -> &'static str;
+    fn verify_message(&self, message: &[u8], signature: &[u8]) -> Result<(), JwsDecodeError>;
+}
+
+impl JwsSigningKey for k256::SecretKey {
+    fn alg(&self) -> &'static str {
+        "ES256K"
+    }
+    fn kid(&self) -> Option<String> {
+        thumbprint(self.public_key())
+    }
+    fn sign_message(&self, message: &[u8]) -> Result<Vec<u8>, JwsEncodeError> {
+        let signing_key: k256::ecdsa::SigningKey = self.into();
+        let signature: k256::ecdsa::Signature = signing_key.try_sign(message)?;
+        Ok(signature.to_vec())
+    }
+}
+
+impl JwsVerifyingKey for k256::PublicKey {
+    fn alg(&self) -> &'static str {
+        "ES256K"
+    }
+    fn verify_message(&self, message: &[u8], signature: &[u8]) -> Result<(), JwsDecodeError> {
+        let (r, s) = split_signature(signature)?;
+        let wrapped_signature = k256::ecdsa::Signature::from_scalars(r, s)?;
+        let verify_key = k256::ecdsa::VerifyingKey::from(self);
+        Ok(verify_key.verify(message, &wrapped_signature)?)
+    }
+}
+
+impl JwsSigningKey for p256::SecretKey {
+    fn alg(&self) -> &'static str {
+        "ES256"
+    }
+    fn kid(&self) -> Option<String> {
+        thumbprint(self.public_key())
+    }
+    fn sign_message(&self, message: &[u8]) -> Result<Vec<u8>, JwsEncodeError> {
+        let signing_key: p256::ecdsa::SigningKey = self.into();
+        let signature: p256::ecdsa::Signature = signing_key.try_sign(message)?;
+        Ok(signature.to_vec())
+    }
+}
+
+impl JwsVerifyingKey for p256::PublicKey {
+    fn alg(&self) -> &'static str {
+        "ES256"
+    }
+    fn verify_message(&self, message: &[u8], signature: &[u8]) -> Result<(), JwsDecodeError> {
+        let (r, s) = split_signature(signature)?;
+        let wrapped_signature = p256::ecdsa::Signature::from_scalars(r, s)?;
+        let verify_key = p256::ecdsa::VerifyingKey::from(self);
+        Ok(verify_key.verify(message, &wrapped_signature)?)
+    }
+}
+
+impl JwsSigningKey for SignSecretKey {
+    fn alg(&self) -> &'static str {
+        match self {
+            SignSecretKey::Secp256k1(sk) => sk.alg(),
+            SignSecretKey::Secp256r1(sk) => sk.alg(),
+        }
+    }
+    fn kid(&self) -> Option<String> {
+        match self {
+            SignSecretKey::Secp256k1(sk) => sk.kid(),
+            SignSecretKey::Secp256r1(sk) => sk.kid(),
+        }
+    }
+    fn sign_message(&self, message: &[u8]) -> Result<Vec<u8>, JwsEncodeError> {
+        match self {
+            SignSecretKey::Secp256k1(sk) => sk.sign_message(message),
+            SignSecretKey::Secp256r1(sk) => sk.sign_message(message),
+        }
+    }
+}
+
+impl JwsVerifyingKey for SignPublicKey {
+    fn alg(&self) -> &'static str {
+        match self {
+            SignPublicKey::Secp256k1(pk) => pk.alg(),
+            SignPublicKey::Secp256r1(pk) => pk.alg(),
+            SignPublicKey::Ed25519(pk) => pk.alg(),
+        }
+    }
+    fn verify_message(&self, message: &[u8], signature: &[u8]) -> Result<(), JwsDecodeError> {
+        match self {
+            SignPublicKey::Secp256k1(pk) => pk.verify_message(message, signature),
+            SignPublicKey::Secp256r1(pk) => pk.verify_message(message, signature),
+            SignPublicKey::Ed25519(pk) => pk.verify_message(message, signature),
+        }
+    }
+}
+
+impl JwsSigningKey for ed25519_dalek::SigningKey {
+    fn alg(&self) -> &'static str {
+        "EdDSA"
+    }
+    fn kid(&self) -> Option<String> {
+        thumbprint(self.verifying_key())
+    }
+    fn sign_message(&self, message: &[u8]) -> Result<Vec<u8>, JwsEncodeError> {
+        let signature: ed25519_dalek::Signature = self.try_sign(message)?;
+        Ok(signature.to_vec())
+    }
+}
+
+impl JwsVerifyingKey for ed25519_dalek::VerifyingKey {
+    fn alg(&self) -> &'static str {
+        "EdDSA"
+    }
+    fn verify_message(&self, message: &[u8], signature: &[u8]) -> Result<(), JwsDecodeError> {
+        let signature = ed25519_dalek::Signature::from_slice(signature)?;
+        Ok(self.verify_strict(message, &signature)?)
+    }
+}
+
+pub fn sign<K: JwsSigningKey + ?Sized>(
+    object: &Value,
+    secret_key: &K,
+) -> Result<String, JwsEncodeError> {
     // NOTE: header
     let header = JwsHeader {
-        alg: "ES256K".to_string(),
+        alg: secret_key.alg().to_string(),
         b64: false,
         crit: vec!["b64".to_string()],
+        kid: secret_key.kid(),
     };
     let header = serde_jcs::to_string(&header)?;
     let header = BASE64URL_NOPAD.encode(header.as_bytes());
@@ -66,17 +219,16 @@ pub fn sign(object: &Value, secret_key: &k256::SecretKey) -> Result<String, JwsE
     let message: &[u8] = message.as_bytes();
 
     // NOTE: signature
-    let signing_key: SigningKey = secret_key.into();
-    let signature: Signature = signing_key.try_sign(message)?;
-    let signature = BASE64URL_NOPAD.encode(&signature.to_vec());
+    let signature = secret_key.sign_message(message)?;
+    let signature = BASE64URL_NOPAD.encode(&signature);
 
     Ok([header, "".to_string(), signature].join("."))
 }
 
-pub fn verify(
+pub fn verify<K: JwsVerifyingKey + ?Sized>(
     object: &Value,
     jws: &str,
-    public_key: &k256::PublicKey,
+    public_key: &K,
 ) -> Result<(), JwsDecodeError> {
     let split: Vec<String> = jws.split('.').map(|v| v.to_string()).collect();
 
@@ -93,7 +245,7 @@ pub fn verify(
     let decoded = String::from_utf8(decoded)?;
     let header = serde_json::from_str::<JwsHeader>(&decoded)?;
 
-    if header.alg != *"ES256K" {
+    if header.alg != public_key.alg() {
         return Err(JwsDecodeError::InvalidAlgorithm(header.alg));
     }
     if header.b64 {
@@ -114,19 +266,16 @@ pub fn verify(
 
     // NOTE: signature
     let signature = BASE64URL_NOPAD.decode(_signature.as_bytes())?;
-    if signature.len() != 64 {
+    if signature.len() != SIGNATURE_LENGTH {
         return Err(JwsDecodeError::InvalidSignatureLength(signature.len()));
     }
-    let r: &[u8; 32] = &signature[0..32].try_into().unwrap();
-    let s: &[u8; 32] = &signature[32..].try_into().unwrap();
-    let wrapped_signature = Signature::from_scalars(*r, *s)?;
-
-    let verify_key = VerifyingKey::from(public_key);
-    Ok(verify_key.verify(message.as_bytes(), &wrapped_signature)?)
+    public_key.verify_message(message.as_bytes(), &signature)
 }
 
 #[cfg(test)]
 pub mod tests {
+    use serde_json::json;
+
     use super::*;
 
     const SECRET_KEY: [u8; 32] = [
@@ -147,7 +296,7 @@ pub mod tests {
 
     fn signature() -> String {
         String::from(
-            "eyJhbGciOiJFUzI1NksiLCJiNjQiOmZhbHNlLCJjcml0IjpbImI2NCJdfQ..vuhCrs1zs9Mlhof0TXgN9XQEY9ZJ2g2kZsH4Ef99wn5MR0pQOhkAHvgYZfHBvXOR795WnWKF_rUiE85abp5CAA",
+            "eyJhbGciOiJFUzI1NksiLCJiNjQiOmZhbHNlLCJjcml0IjpbImI2NCJdLCJraWQiOiJ6d21CTHA2Mm1xWDJYQ0pXTGdzVmtJcWZCVXR2YXUwWVViN1htZmdEckdFIn0..NyriMxb2ryWtkt38czHY08qI9l73CFTF8-i2o0tquUpEsc1-tudS-1VWzJDOJOxohXkR3uK0DdnIh88I8W0cMw",
         )
     }
 
@@ -157,7 +306,13 @@ pub mod tests {
         let json: Value = serde_json::from_str(&message()).unwrap();
         let result = sign(&json, &sk).unwrap();
 
-        assert_eq!(result, signature())
+        assert_eq!(result, signature());
+
+        let header = result.split('.').next().unwrap();
+        let header = BASE64URL_NOPAD.decode(header.as_bytes()).unwrap();
+        let header: JwsHeader = serde_json::from_slice(&header).unwrap();
+        let jwk: Jwk = sk.public_key().try_into().unwrap();
+        assert_eq!(header.kid, Some(jwk.thumbprint().unwrap()));
     }
 
     #[test]
@@ -166,4 +321,69 @@ pub mod tests {
         let json: Value = serde_json::from_str(&message()).unwrap();
         verify(&json, &signature(), &pk).unwrap();
     }
+
+    #[test]
+    pub fn test_eddsa_sign_and_verify() {
+        let sk = ed25519_dalek::SigningKey::from_bytes(&SECRET_KEY);
+        let json: Value = serde_json::from_str(&message()).unwrap();
+        let result = sign(&json, &sk).unwrap();
+
+        let header = result.split('.').next().unwrap();
+        let header = BASE64URL_NOPAD.decode(header.as_bytes()).unwrap();
+        let header: JwsHeader = serde_json::from_slice(&header).unwrap();
+        assert_eq!(header.alg, "EdDSA");
+
+        verify(&json, &result, &sk.verifying_key()).unwrap();
+    }
+
+    #[test]
+    pub fn test_es256_sign_and_verify() {
+        let sk = p256::SecretKey::from_slice(&SECRET_KEY).unwrap();
+        let json: Value = serde_json::from_str(&message()).unwrap();
+        let result = sign(&json, &SignSecretKey::Secp256r1(sk.clone())).unwrap();
+
+        let header = result.split('.').next().unwrap();
+        let header = BASE64URL_NOPAD.decode(header.as_bytes()).unwrap();
+        let header: JwsHeader = serde_json::from_slice(&header).unwrap();
+        assert_eq!(header.alg, "ES256");
+
+        verify(&json, &result, &SignPublicKey::Secp256r1(sk.public_key())).unwrap();
+
+        let pk = k256::PublicKey::from_sec1_bytes(&PUBLIC_KEY).unwrap();
+        let result = verify(&json, &result, &pk);
+        assert!(matches!(result, Err(JwsDecodeError::InvalidAlgorithm(alg)) if alg == "ES256"));
+    }
+
+    #[test]
+    pub fn test_verify_message_invalid_signature_length() {
+        let pk = k256::PublicKey::from_sec1_bytes(&PUBLIC_KEY).unwrap();
+        let result = pk.verify_message(message().as_bytes(), &[0u8; 63]);
+        assert!(matches!(
+            result,
+            Err(JwsDecodeError::InvalidSignatureLength(63))
+        ));
+
+        let pk = p256::SecretKey::from_slice(&SECRET_KEY)
+            .unwrap()
+            .public_key();
+        let result = pk.verify_message(message().as_bytes(), &[]);
+        assert!(matches!(
+            result,
+            Err(JwsDecodeError::InvalidSignatureLength(0))
+        ));
+    }
+
+    #[test]
+    pub fn test_verify_algorithm_mismatch() {
+        let sk = ed25519_dalek::SigningKey::from_bytes(&SECRET_KEY);
+        let json: Value = serde_json::from_str(&message()).unwrap();
+
+        let pk = k256::PublicKey::from_sec1_bytes(&PUBLIC_KEY).unwrap();
+        let result = verify(&json, &sign(&json, &sk).unwrap(), &pk);
+        assert!(matches!(result, Err(JwsDecodeError::InvalidAlgorithm(alg)) if alg == "EdDSA"));
+
+        let tampered = json!({"k": "fedcba9876543210"});
+        let result = verify(&tampered, &sign(&json, &sk).unwrap(), &sk.verifying_key());
+        assert!(matches!(result, Err(JwsDecodeError::CryptError(_))));
+    }
 }
@@ -1,4 +1,5 @@
 pub mod credential_signer;
 pub mod did_vc;
+pub mod eddsa_jcs_2022;
 pub mod jws;
 pub mod types;
@@ -38,8 +38,15 @@ pub struct Proof {
     #[serde(rename = "verificationMethod")]
     pub verification_method: String,
 
-    #[serde(rename = "jws")]
-    pub jws: String,
+    #[serde(rename = "jws", skip_serializing_if = "Option::is_none")]
+    pub jws: Option<String>,
+
+    // NOTE: Data Integrity proofs (e.g. eddsa-jcs-2022) carry these instead of jws.
+    #[serde(rename = "cryptosuite", skip_serializing_if = "Option::is_none")]
+    pub cryptosuite: Option<String>,
+
+    #[serde(rename = "proofValue", skip_serializing_if = "Option::is_none")]
+    pub proof_value: Option<String>,
 
     #[serde(rename = "controller")]
     pub controller: Option<String>,